@@ -0,0 +1,133 @@
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use std::hint::black_box;
+use synckit_core::crdt::text::{PositionUnit, Text};
+
+/// Minimal deterministic PRNG (LCG) so runs are reproducible
+struct Lcg(u64);
+
+impl Lcg {
+    fn below(&mut self, n: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % n.max(1)
+    }
+}
+
+/// Build a document of `chars` characters typed in short bursts at random
+/// positions, so it is split into many blocks
+fn fragmented_text(chars: usize) -> Text {
+    let mut text = Text::new(1);
+    let mut rng = Lcg(42);
+    while text.len() < chars {
+        let position = rng.below(text.len() + 1);
+        text.insert(position, "hello");
+    }
+    text
+}
+
+/// Benchmark single-character inserts at random positions
+fn bench_random_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("text_random_insert");
+
+    for size in [100_000, 250_000].iter() {
+        let base = fragmented_text(*size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            let mut rng = Lcg(7);
+            b.iter_batched(
+                || base.clone(),
+                |mut text| {
+                    for _ in 0..100 {
+                        let position = rng.below(text.len() + 1);
+                        text.insert(black_box(position), "x");
+                    }
+                    text
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark short deletes at random positions
+fn bench_random_delete(c: &mut Criterion) {
+    let mut group = c.benchmark_group("text_random_delete");
+
+    for size in [100_000, 250_000].iter() {
+        let base = fragmented_text(*size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), size, |b, _| {
+            let mut rng = Lcg(7);
+            b.iter_batched(
+                || base.clone(),
+                |mut text| {
+                    for _ in 0..100 {
+                        let position = rng.below(text.len());
+                        text.delete(black_box(position), 3);
+                    }
+                    text
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark single-character inserts at random UTF-16 positions, as a
+/// browser editor sends them
+fn bench_random_insert_utf16(c: &mut Criterion) {
+    let mut base = fragmented_text(100_000);
+    let mut rng = Lcg(13);
+    for _ in 0..1_000 {
+        let position = rng.below(base.len() + 1);
+        base.insert(position, "😀");
+    }
+    base.set_unit(PositionUnit::Utf16);
+
+    c.bench_function("text_random_insert_utf16_100k", |b| {
+        let mut rng = Lcg(7);
+        b.iter_batched(
+            || base.clone(),
+            |mut text| {
+                for _ in 0..100 {
+                    let position = rng.below(text.len() + 1);
+                    text.insert(black_box(position), "x");
+                }
+                text
+            },
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+/// Benchmark sequential typing at the end of a large document
+fn bench_append(c: &mut Criterion) {
+    let base = fragmented_text(100_000);
+
+    c.bench_function("text_append_100k", |b| {
+        b.iter_batched(
+            || base.clone(),
+            |mut text| {
+                for _ in 0..100 {
+                    let position = text.len();
+                    text.insert(black_box(position), "a");
+                }
+                text
+            },
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_random_insert,
+    bench_random_delete,
+    bench_random_insert_utf16,
+    bench_append,
+);
+criterion_main!(benches);
@@ -0,0 +1,305 @@
+//! Bounded Counter: a counter that never goes below zero
+//!
+//! Concurrent decrements on a `PNCounter` can take it below zero, since no
+//! replica sees the others' decrements in time. A bounded counter splits
+//! the value into per-replica rights instead: a replica may only decrement
+//! what it holds, so the sum of all rights, which is the value, can't drop
+//! below zero without any coordination.
+//!
+//! # Rights
+//!
+//! - An increment gives the incrementing replica that many rights
+//! - A decrement spends the replica's own rights
+//! - A transfer moves rights to another replica, e.g. to one that ran out
+//!
+//! The state is grow-only totals: increments and decrements per replica,
+//! and transfers per (from, to) pair. Everything merges by pointwise
+//! maximum, and mutators return delta-states like `PNCounter`.
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::crdt::BoundedCounter;
+//!
+//! let mut seats1 = BoundedCounter::new("replica1".to_string());
+//! let mut seats2 = BoundedCounter::new("replica2".to_string());
+//!
+//! seats1.increment(10).unwrap();
+//! seats2.merge(&seats1);
+//!
+//! // Replica2 holds no rights until some are transferred
+//! assert!(seats2.decrement(1).is_err());
+//!
+//! let transfer = seats1.transfer(&"replica2".to_string(), 4).unwrap();
+//! seats2.merge(&transfer);
+//! seats2.decrement(4).unwrap();
+//!
+//! seats1.merge(&seats2);
+//! assert_eq!(seats1.value(), 6);
+//! ```
+
+use crate::error::{Result, SyncError};
+use crate::sync::VectorClock;
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Non-negative counter CRDT with per-replica rights
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoundedCounter {
+    /// Replica identifier
+    replica_id: ClientID,
+
+    /// Increment totals per replica
+    increments: VectorClock,
+
+    /// Decrement totals per replica
+    decrements: VectorClock,
+
+    /// Transfer totals: sender → receiver → rights moved
+    transfers: HashMap<ClientID, VectorClock>,
+}
+
+impl BoundedCounter {
+    /// Create a new counter for the given replica
+    pub fn new(replica_id: ClientID) -> Self {
+        Self::from_parts(
+            replica_id,
+            VectorClock::new(),
+            VectorClock::new(),
+            HashMap::new(),
+        )
+    }
+
+    /// Rebuild a counter from its totals
+    pub fn from_parts(
+        replica_id: ClientID,
+        increments: VectorClock,
+        decrements: VectorClock,
+        transfers: HashMap<ClientID, VectorClock>,
+    ) -> Self {
+        Self {
+            replica_id,
+            increments,
+            decrements,
+            transfers,
+        }
+    }
+
+    /// Increment the counter, gaining that many rights
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` if the
+    /// replica's total would overflow.
+    pub fn increment(&mut self, amount: u64) -> Result<BoundedCounter> {
+        let total = Self::bump(&mut self.increments, &self.replica_id, amount)?;
+        let mut delta = Self::new(self.replica_id.clone());
+        delta.increments.update(&self.replica_id, total);
+        Ok(delta)
+    }
+
+    /// Decrement the counter, spending this replica's rights
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` if the
+    /// replica doesn't hold enough rights.
+    pub fn decrement(&mut self, amount: u64) -> Result<BoundedCounter> {
+        self.check_rights(amount)?;
+
+        let total = Self::bump(&mut self.decrements, &self.replica_id, amount)?;
+        let mut delta = Self::new(self.replica_id.clone());
+        delta.decrements.update(&self.replica_id, total);
+        Ok(delta)
+    }
+
+    /// Move rights from this replica to another
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` if the
+    /// replica doesn't hold enough rights or transfers to itself.
+    pub fn transfer(&mut self, to: &ClientID, amount: u64) -> Result<BoundedCounter> {
+        if *to == self.replica_id {
+            return Err(SyncError::InvalidOperation(
+                "Cannot transfer rights to the same replica".to_string(),
+            ));
+        }
+        self.check_rights(amount)?;
+
+        let sent = self.transfers.entry(self.replica_id.clone()).or_default();
+        let total = Self::bump(sent, to, amount)?;
+        let mut delta = Self::new(self.replica_id.clone());
+        delta
+            .transfers
+            .entry(self.replica_id.clone())
+            .or_default()
+            .update(to, total);
+        Ok(delta)
+    }
+
+    /// Get the current counter value
+    ///
+    /// Sum of all increments minus all decrements, never below zero.
+    pub fn value(&self) -> u64 {
+        let increments: i128 = self.increments.clocks().values().map(|&n| n as i128).sum();
+        let decrements: i128 = self.decrements.clocks().values().map(|&n| n as i128).sum();
+        (increments - decrements).clamp(0, u64::MAX as i128) as u64
+    }
+
+    /// Get the rights a replica holds, as far as this replica knows
+    pub fn rights(&self, replica_id: &ClientID) -> u64 {
+        let received: i128 = self
+            .transfers
+            .values()
+            .map(|sent| sent.get(replica_id) as i128)
+            .sum();
+        let sent: i128 = self
+            .transfers
+            .get(replica_id)
+            .map(|sent| sent.clocks().values().map(|&n| n as i128).sum())
+            .unwrap_or(0);
+
+        let rights = self.increments.get(replica_id) as i128 + received
+            - sent
+            - self.decrements.get(replica_id) as i128;
+        rights.clamp(0, u64::MAX as i128) as u64
+    }
+
+    /// Get the rights this replica holds
+    pub fn local_rights(&self) -> u64 {
+        self.rights(&self.replica_id)
+    }
+
+    /// Merge another counter's state into this one
+    ///
+    /// Takes the pointwise maximum of all totals.
+    /// This operation is commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &BoundedCounter) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+        for (from, sent) in &other.transfers {
+            self.transfers.entry(from.clone()).or_default().merge(sent);
+        }
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Get the increment totals per replica
+    pub fn increments(&self) -> &VectorClock {
+        &self.increments
+    }
+
+    /// Get the decrement totals per replica
+    pub fn decrements(&self) -> &VectorClock {
+        &self.decrements
+    }
+
+    /// Get the transfer totals: sender → receiver → rights moved
+    pub fn transfers(&self) -> &HashMap<ClientID, VectorClock> {
+        &self.transfers
+    }
+
+    /// Fail unless this replica holds at least amount rights
+    fn check_rights(&self, amount: u64) -> Result<()> {
+        let rights = self.local_rights();
+        if amount > rights {
+            return Err(SyncError::InvalidOperation(format!(
+                "Not enough rights on {}: {} > {}",
+                self.replica_id, amount, rights
+            )));
+        }
+        Ok(())
+    }
+
+    /// Raise a total by amount, keeping it within the wire range (`i64`)
+    fn bump(totals: &mut VectorClock, replica_id: &ClientID, amount: u64) -> Result<u64> {
+        let current = totals.get(replica_id);
+        let total = current
+            .checked_add(amount)
+            .filter(|&total| total <= i64::MAX as u64)
+            .ok_or_else(|| {
+                SyncError::InvalidOperation(format!(
+                    "Counter total overflows: {} + {}",
+                    current, amount
+                ))
+            })?;
+        totals.update(replica_id, total);
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(name: &str) -> ClientID {
+        name.to_string()
+    }
+
+    #[test]
+    fn test_decrement_within_rights() {
+        let mut counter = BoundedCounter::new(id("replica1"));
+        counter.increment(5).unwrap();
+        counter.decrement(3).unwrap();
+
+        assert_eq!(counter.value(), 2);
+        assert_eq!(counter.local_rights(), 2);
+        assert!(matches!(
+            counter.decrement(3),
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert_eq!(counter.value(), 2);
+    }
+
+    #[test]
+    fn test_transfer_moves_rights() {
+        let mut counter1 = BoundedCounter::new(id("replica1"));
+        let mut counter2 = BoundedCounter::new(id("replica2"));
+        counter1.increment(10).unwrap();
+
+        let delta = counter1.transfer(&id("replica2"), 4).unwrap();
+        counter2.merge(&delta);
+
+        assert_eq!(counter1.local_rights(), 6);
+        assert_eq!(counter2.local_rights(), 4);
+        assert!(counter1.transfer(&id("replica2"), 7).is_err());
+        assert!(counter1.transfer(&id("replica1"), 1).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_decrements_stay_non_negative() {
+        let mut counter1 = BoundedCounter::new(id("replica1"));
+        counter1.increment(3).unwrap();
+        let mut counter2 = counter1.clone();
+        counter2.replica_id = id("replica2");
+        counter2.merge(&counter1.transfer(&id("replica2"), 1).unwrap());
+
+        // Both try to take every seat while partitioned
+        assert!(counter1.decrement(3).is_err());
+        counter1.decrement(2).unwrap();
+        assert!(counter2.decrement(2).is_err());
+        counter2.decrement(1).unwrap();
+
+        counter1.merge(&counter2);
+        counter2.merge(&counter1);
+        assert_eq!(counter1.value(), 0);
+        assert_eq!(counter2.value(), 0);
+    }
+
+    #[test]
+    fn test_deltas_converge_with_full_merge() {
+        let mut counter1 = BoundedCounter::new(id("replica1"));
+        let mut counter2 = BoundedCounter::new(id("replica2"));
+
+        let mut buffer = counter1.increment(8).unwrap();
+        buffer.merge(&counter1.transfer(&id("replica2"), 3).unwrap());
+        buffer.merge(&counter1.decrement(2).unwrap());
+
+        let mut full = counter2.clone();
+        full.merge(&counter1);
+        counter2.merge(&buffer);
+
+        assert_eq!(counter2, full);
+        assert_eq!(counter2.value(), 6);
+        assert_eq!(counter2.local_rights(), 3);
+    }
+}
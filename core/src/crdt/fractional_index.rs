@@ -31,6 +31,24 @@
 //! assert!(first < between);
 //! assert!(between < second);
 //! ```
+//!
+//! # Concurrent inserts
+//!
+//! `between` is deterministic, so two replicas inserting between the same
+//! neighbours get the same key. `between_unique` appends a suffix encoding
+//! the replica ID, so keys from different replicas never collide, and
+//! `between_jittered` appends random digits instead.
+//!
+//! ```
+//! use synckit_core::crdt::FractionalIndex;
+//!
+//! let first = FractionalIndex::first();
+//! let a = FractionalIndex::between_unique(Some(&first), None, "replica-a");
+//! let b = FractionalIndex::between_unique(Some(&first), None, "replica-b");
+//!
+//! assert_ne!(a, b);
+//! assert!(first < a && first < b);
+//! ```
 
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -110,62 +128,155 @@ impl FractionalIndex {
         Self { position: midpoint }
     }
 
-    /// Compute the midpoint between two position strings
+    /// Generate a position between two positions that no other replica can generate
+    ///
+    /// `None` stands for the start or end of the list. The key is a midpoint
+    /// followed by an encoding of `replica_id` that can be read back from the
+    /// end of the key, so keys from different replicas are always distinct.
+    ///
+    /// # Panics
+    ///
+    /// Panics if left >= right, or if `replica_id` is longer than 3781 bytes.
+    pub fn between_unique(
+        left: Option<&FractionalIndex>,
+        right: Option<&FractionalIndex>,
+        replica_id: &str,
+    ) -> Self {
+        let bytes = replica_id.as_bytes();
+        assert!(
+            bytes.len() < (BASE * (BASE - 1)) as usize,
+            "Replica ID too long for a position suffix"
+        );
+
+        let mut suffix: Vec<u32> = bytes
+            .iter()
+            .flat_map(|&byte| [byte as u32 / BASE, byte as u32 % BASE])
+            .collect();
+        let len = bytes.len() as u32;
+        suffix.extend([len / (BASE - 1), len % (BASE - 1) + 1]);
+
+        Self::between_with_suffix(left, right, &suffix)
+    }
+
+    /// Generate a position between two positions with a random suffix
     ///
-    /// Uses a digit-by-digit average approach with proper handling of edge cases.
+    /// `None` stands for the start or end of the list. Collisions between
+    /// replicas are possible but vanishingly unlikely (64 random bits); use
+    /// `between_unique` when a replica ID is at hand.
+    ///
+    /// # Panics
+    ///
+    /// Panics if left >= right
+    pub fn between_jittered(
+        left: Option<&FractionalIndex>,
+        right: Option<&FractionalIndex>,
+    ) -> Self {
+        let bytes = uuid::Uuid::new_v4().into_bytes();
+        let mut random = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+
+        let mut suffix = Vec::with_capacity(11);
+        for _ in 0..10 {
+            suffix.push((random % BASE as u64) as u32);
+            random /= BASE as u64;
+        }
+        suffix.push((random % (BASE as u64 - 1)) as u32 + 1);
+
+        Self::between_with_suffix(left, right, &suffix)
+    }
+
+    /// Midpoint of the bounds followed by the given digits
+    ///
+    /// The suffix must not end in the zero digit.
+    fn between_with_suffix(
+        left: Option<&FractionalIndex>,
+        right: Option<&FractionalIndex>,
+        suffix: &[u32],
+    ) -> Self {
+        if let (Some(left), Some(right)) = (left, right) {
+            assert!(
+                left < right,
+                "Left position must be less than right position"
+            );
+        }
+
+        let left = left
+            .map(|pos| Self::to_digits(&pos.position))
+            .unwrap_or_default();
+        let right = right.map(|pos| Self::to_digits(&pos.position));
+
+        // The midpoint is never a prefix of right, so anything appended to it
+        // still sorts before right
+        let mut digits = Self::midpoint(&left, right.as_deref());
+        digits.extend_from_slice(suffix);
+
+        Self {
+            position: digits.into_iter().map(Self::value_to_char).collect(),
+        }
+    }
+
+    /// Compute the midpoint between two position strings
     fn compute_midpoint(left: &str, right: &str) -> String {
-        let mut result = String::new();
-        let left_chars: Vec<char> = left.chars().collect();
-        let right_chars: Vec<char> = right.chars().collect();
+        let right = Self::to_digits(right);
+        Self::midpoint(&Self::to_digits(left), Some(&right))
+            .into_iter()
+            .map(Self::value_to_char)
+            .collect()
+    }
 
-        let mut i = 0;
-        loop {
-            let left_digit = if i < left_chars.len() {
-                Self::char_to_value(left_chars[i])
-            } else {
-                0 // Treat missing chars as '0' (smallest)
-            };
+    /// Compute a key strictly between left and right (`None`: no upper bound)
+    ///
+    /// Walks the digits once: copies the common prefix, then either fits a
+    /// digit between the first differing ones, or keeps left's digit and
+    /// continues with no upper bound. Every step consumes a digit of right,
+    /// or of left once unbounded, so it always terminates. The result never
+    /// ends in the zero digit and is never a prefix of right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the keys differ only by trailing zero digits ("a" and
+    /// "a0"), which leaves no key between them.
+    fn midpoint(mut left: &[u32], mut right: Option<&[u32]>) -> Vec<u32> {
+        let mut result = Vec::new();
 
-            let right_digit = if i < right_chars.len() {
-                Self::char_to_value(right_chars[i])
-            } else {
-                BASE // Treat right's end as one past largest digit
+        loop {
+            // Missing digits of left count as the smallest digit
+            let left_digit = left.first().copied().unwrap_or(0);
+            let right_digit = match right {
+                Some(right) => *right
+                    .first()
+                    .expect("No position between keys that differ only by trailing zeros"),
+                None => BASE,
             };
 
             match left_digit.cmp(&right_digit) {
-                std::cmp::Ordering::Less => {
-                    if left_digit + 1 < right_digit {
-                        // Space between digits: use average and we're done
-                        let mid = (left_digit + right_digit) / 2;
-                        result.push(Self::value_to_char(mid));
-                        break;
-                    } else {
-                        // Adjacent digits (e.g., 'a' and 'b'): copy left, continue deeper
-                        result.push(Self::value_to_char(left_digit));
-                        i += 1;
-                        // Continue to find space in remaining positions
-                    }
-                }
-                std::cmp::Ordering::Equal => {
+                Ordering::Equal => {
                     // Same digit: copy and continue
-                    result.push(Self::value_to_char(left_digit));
-                    i += 1;
+                    result.push(left_digit);
+                    left = left.get(1..).unwrap_or_default();
+                    right = right.map(|right| &right[1..]);
+                }
+                Ordering::Less if right_digit - left_digit > 1 => {
+                    // Space between digits: use average and we're done
+                    result.push((left_digit + right_digit) / 2);
+                    return result;
                 }
-                std::cmp::Ordering::Greater => {
+                Ordering::Less => {
+                    // Adjacent digits: copy left's, then anything above the rest of left
+                    result.push(left_digit);
+                    left = left.get(1..).unwrap_or_default();
+                    right = None;
+                }
+                Ordering::Greater => {
                     // left_digit > right_digit shouldn't happen (assertion prevents)
                     unreachable!("left should be < right");
                 }
             }
-
-            // Safety: prevent infinite loops
-            if i > 20 {
-                // If we've gone 20 chars deep, just append a mid character
-                result.push(Self::value_to_char(BASE / 2));
-                break;
-            }
         }
+    }
 
-        result
+    /// Convert a position string to digit values
+    fn to_digits(position: &str) -> Vec<u32> {
+        position.chars().map(Self::char_to_value).collect()
     }
 
     /// Convert a character to its position value
@@ -365,6 +476,74 @@ mod tests {
         FractionalIndex::between(&b, &a);
     }
 
+    #[test]
+    fn test_deep_insertion_has_no_depth_cap() {
+        let left = FractionalIndex::first();
+        let mut right = FractionalIndex::after(&left);
+
+        // Always insert right after `left`: keys get longer every few steps
+        for _ in 0..2000 {
+            let middle = FractionalIndex::between(&left, &right);
+            assert!(left < middle && middle < right);
+            right = middle;
+        }
+        assert!(right.as_str().len() > 20);
+    }
+
+    #[test]
+    fn test_adjacent_digits_need_no_extra_depth() {
+        let a = FractionalIndex::from_str("a1".to_string());
+        let b = FractionalIndex::from_str("a2".to_string());
+        let middle = FractionalIndex::between(&a, &b);
+
+        assert_eq!(middle.as_str(), "a1V");
+    }
+
+    #[test]
+    fn test_unique_keys_differ_per_replica() {
+        let left = FractionalIndex::from_str("a1".to_string());
+        let right = FractionalIndex::from_str("a2".to_string());
+
+        let a = FractionalIndex::between_unique(Some(&left), Some(&right), "a");
+        let b = FractionalIndex::between_unique(Some(&left), Some(&right), "b");
+        let ab = FractionalIndex::between_unique(Some(&left), Some(&right), "ab");
+
+        for key in [&a, &b, &ab] {
+            assert!(&left < key && key < &right);
+            assert!(!key.as_str().ends_with('0'));
+        }
+        assert_ne!(a, b);
+        assert_ne!(a, ab);
+
+        // Same replica, same neighbours: same key
+        assert_eq!(
+            a,
+            FractionalIndex::between_unique(Some(&left), Some(&right), "a")
+        );
+    }
+
+    #[test]
+    fn test_unique_keys_at_list_ends() {
+        let only = FractionalIndex::between_unique(None, None, "replica1");
+        let before = FractionalIndex::between_unique(None, Some(&only), "replica1");
+        let after = FractionalIndex::between_unique(Some(&only), None, "replica1");
+
+        assert!(before < only && only < after);
+    }
+
+    #[test]
+    fn test_jittered_keys() {
+        let left = FractionalIndex::first();
+        let right = FractionalIndex::after(&left);
+
+        let a = FractionalIndex::between_jittered(Some(&left), Some(&right));
+        let b = FractionalIndex::between_jittered(Some(&left), Some(&right));
+
+        assert_ne!(a, b);
+        assert!(left < a && a < right);
+        assert!(left < b && b < right);
+    }
+
     #[test]
     fn test_serialization() {
         let pos = FractionalIndex::first();
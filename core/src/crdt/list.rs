@@ -0,0 +1,465 @@
+//! List: ordered list CRDT built on fractional indexing
+//!
+//! Every element carries a `FractionalIndex` position; reading the list
+//! sorts elements by it. Inserting generates a position between the new
+//! neighbours, so no other element is renumbered.
+//!
+//! # Semantics
+//!
+//! - **Insert:** positions are generated with `FractionalIndex::between_unique`,
+//!   so concurrent inserts into the same gap never tie
+//! - **Move:** assigns a new position under Last-Write-Wins; concurrent moves
+//!   of the same element resolve to exactly one location
+//! - **Set:** replaces the element's JSON payload under Last-Write-Wins
+//! - **Delete:** wins over concurrent moves and sets; the element's ID is
+//!   kept as a tombstone so a merge can't bring it back
+//!
+//! Timestamps come from a Lamport clock per list, so no wall clock is
+//! involved.
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::crdt::List;
+//! use serde_json::json;
+//!
+//! let mut list1 = List::new("replica1".to_string());
+//! let milk = list1.push(json!({ "title": "Buy milk" }));
+//! list1.push(json!({ "title": "Walk dog" }));
+//!
+//! let mut list2 = List::new("replica2".to_string());
+//! list2.merge(&list1);
+//! list2.move_to(&milk, 1).unwrap();
+//! list1.merge(&list2);
+//!
+//! assert_eq!(list1.values(), vec![json!({ "title": "Walk dog" }), json!({ "title": "Buy milk" })]);
+//! ```
+
+use super::fractional_index::FractionalIndex;
+use crate::error::{Result, SyncError};
+use crate::sync::{LWWField, Timestamp};
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap};
+
+/// Unique ID of a list element: (creating replica, Lamport clock)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ElementId {
+    /// Replica that inserted the element
+    pub replica_id: ClientID,
+
+    /// Lamport clock of the insert
+    pub counter: u64,
+}
+
+impl ElementId {
+    /// Create a new element ID
+    pub fn new(replica_id: ClientID, counter: u64) -> Self {
+        Self {
+            replica_id,
+            counter,
+        }
+    }
+}
+
+impl std::fmt::Display for ElementId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.replica_id, self.counter)
+    }
+}
+
+/// A list element with its LWW metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Element {
+    /// Element ID
+    id: ElementId,
+
+    /// JSON payload
+    value: LWWField,
+
+    /// Position in the list
+    position: FractionalIndex,
+
+    /// Timestamp of the insert or latest move
+    moved: Timestamp,
+
+    /// Whether the element was deleted (tombstone)
+    deleted: bool,
+}
+
+/// Serialized form of a list: its elements, without the order index
+#[derive(Serialize, Deserialize)]
+struct ListState {
+    replica_id: ClientID,
+    clock: u64,
+    elements: Vec<Element>,
+}
+
+/// Ordered list CRDT with move support
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ListState", into = "ListState")]
+pub struct List {
+    /// Replica identifier
+    replica_id: ClientID,
+
+    /// Lamport clock: highest clock seen so far
+    clock: u64,
+
+    /// All elements, deleted ones included
+    elements: HashMap<ElementId, Element>,
+
+    /// Live elements sorted by position (ID breaks ties)
+    order: BTreeSet<(FractionalIndex, ElementId)>,
+}
+
+impl List {
+    /// Create an empty list for the given replica
+    pub fn new(replica_id: ClientID) -> Self {
+        Self {
+            replica_id,
+            clock: 0,
+            elements: HashMap::new(),
+            order: BTreeSet::new(),
+        }
+    }
+
+    /// Insert a value at the given index
+    ///
+    /// Returns the new element's ID, or `SyncError::InvalidOperation` if
+    /// index > len.
+    pub fn insert(&mut self, index: usize, value: JsonValue) -> Result<ElementId> {
+        let position = self.position_at(index, None)?;
+        let timestamp = self.tick();
+        let id = ElementId::new(self.replica_id.clone(), timestamp.clock);
+
+        self.order.insert((position.clone(), id.clone()));
+        self.elements.insert(
+            id.clone(),
+            Element {
+                id: id.clone(),
+                value: LWWField::new(value, timestamp.clone()),
+                position,
+                moved: timestamp,
+                deleted: false,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Append a value to the end of the list
+    pub fn push(&mut self, value: JsonValue) -> ElementId {
+        self.insert(self.len(), value)
+            .expect("inserting at len is always valid")
+    }
+
+    /// Delete an element
+    ///
+    /// Returns false if the element doesn't exist or is already deleted.
+    pub fn delete(&mut self, id: &ElementId) -> bool {
+        match self.elements.get_mut(id) {
+            Some(element) if !element.deleted => {
+                element.deleted = true;
+                self.order.remove(&(element.position.clone(), id.clone()));
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replace an element's payload
+    ///
+    /// Returns false if the element doesn't exist or is deleted.
+    pub fn set(&mut self, id: &ElementId, value: JsonValue) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+
+        let timestamp = self.tick();
+        if let Some(element) = self.elements.get_mut(id) {
+            element.value = LWWField::new(value, timestamp);
+        }
+        true
+    }
+
+    /// Move an element so it ends up at the given index
+    ///
+    /// The index counts the other elements, as if the element were removed
+    /// first; `len() - 1` moves it to the end.
+    ///
+    /// Returns `SyncError::InvalidOperation` if the element doesn't exist
+    /// or is deleted, or the index is out of bounds.
+    pub fn move_to(&mut self, id: &ElementId, index: usize) -> Result<()> {
+        if !self.contains(id) {
+            return Err(SyncError::InvalidOperation(format!(
+                "No list element {}",
+                id
+            )));
+        }
+
+        let position = self.position_at(index, Some(id))?;
+        let timestamp = self.tick();
+        if let Some(element) = self.elements.get_mut(id) {
+            self.order.remove(&(element.position.clone(), id.clone()));
+            self.order.insert((position.clone(), id.clone()));
+            element.position = position;
+            element.moved = timestamp;
+        }
+        Ok(())
+    }
+
+    /// Get an element's payload
+    pub fn get(&self, id: &ElementId) -> Option<&JsonValue> {
+        self.elements
+            .get(id)
+            .filter(|element| !element.deleted)
+            .map(|element| &element.value.value)
+    }
+
+    /// Check if an element is in the list
+    pub fn contains(&self, id: &ElementId) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// Get the index of an element
+    pub fn index_of(&self, id: &ElementId) -> Option<usize> {
+        self.order.iter().position(|(_, other)| other == id)
+    }
+
+    /// Iterate over the elements in order
+    pub fn iter(&self) -> impl Iterator<Item = (&ElementId, &JsonValue)> {
+        self.order
+            .iter()
+            .map(|(_, id)| (id, &self.elements[id].value.value))
+    }
+
+    /// Get the payloads in order
+    pub fn values(&self) -> Vec<JsonValue> {
+        self.iter().map(|(_, value)| value.clone()).collect()
+    }
+
+    /// Get the number of elements in the list
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Check if the list is empty
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Merge another list's state into this one
+    ///
+    /// Payloads and positions merge by Last-Write-Wins, deletes always win.
+    /// This operation is commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &List) {
+        self.clock = self.clock.max(other.clock);
+
+        for (id, remote) in &other.elements {
+            let Some(local) = self.elements.get_mut(id) else {
+                if !remote.deleted {
+                    self.order.insert((remote.position.clone(), id.clone()));
+                }
+                self.elements.insert(id.clone(), remote.clone());
+                continue;
+            };
+
+            self.order.remove(&(local.position.clone(), id.clone()));
+            local.value = local.value.merge(&remote.value);
+            if remote.moved.is_newer_than(&local.moved) {
+                local.position = remote.position.clone();
+                local.moved = remote.moved.clone();
+            }
+            local.deleted |= remote.deleted;
+            if !local.deleted {
+                self.order.insert((local.position.clone(), id.clone()));
+            }
+        }
+    }
+
+    /// Advance the Lamport clock and get a timestamp for a local change
+    fn tick(&mut self) -> Timestamp {
+        self.clock += 1;
+        Timestamp::new(self.clock, self.replica_id.clone())
+    }
+
+    /// Generate a position at the given index, skipping `moving`
+    fn position_at(&self, index: usize, moving: Option<&ElementId>) -> Result<FractionalIndex> {
+        let others: Vec<&FractionalIndex> = self
+            .order
+            .iter()
+            .filter(|(_, id)| Some(id) != moving)
+            .map(|(position, _)| position)
+            .collect();
+        if index > others.len() {
+            return Err(SyncError::InvalidOperation(format!(
+                "List index {} out of bounds (len {})",
+                index,
+                others.len()
+            )));
+        }
+
+        let left = index.checked_sub(1).map(|i| others[i]);
+        let right = others.get(index).copied();
+        Ok(FractionalIndex::between_unique(
+            left,
+            right,
+            &self.replica_id,
+        ))
+    }
+}
+
+impl From<ListState> for List {
+    fn from(state: ListState) -> Self {
+        let mut list = List::new(state.replica_id);
+        list.clock = state.clock;
+        for element in state.elements {
+            if !element.deleted {
+                list.order
+                    .insert((element.position.clone(), element.id.clone()));
+            }
+            list.elements.insert(element.id.clone(), element);
+        }
+        list
+    }
+}
+
+impl From<List> for ListState {
+    fn from(list: List) -> Self {
+        let mut elements: Vec<Element> = list.elements.into_values().collect();
+        elements.sort_by(|a, b| a.id.cmp(&b.id));
+        Self {
+            replica_id: list.replica_id,
+            clock: list.clock,
+            elements,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_insert_and_read_in_order() {
+        let mut list = List::new("replica1".to_string());
+        list.push(json!("b"));
+        list.insert(0, json!("a")).unwrap();
+        list.push(json!("d"));
+        list.insert(2, json!("c")).unwrap();
+
+        assert_eq!(
+            list.values(),
+            vec![json!("a"), json!("b"), json!("c"), json!("d")]
+        );
+        assert!(list.insert(9, json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_delete_and_set() {
+        let mut list = List::new("replica1".to_string());
+        let a = list.push(json!({ "done": false }));
+        let b = list.push(json!("b"));
+
+        assert!(list.set(&a, json!({ "done": true })));
+        assert!(list.delete(&b));
+        assert!(!list.delete(&b));
+        assert!(!list.set(&b, json!("c")));
+
+        assert_eq!(list.values(), vec![json!({ "done": true })]);
+        assert_eq!(list.get(&b), None);
+    }
+
+    #[test]
+    fn test_move() {
+        let mut list = List::new("replica1".to_string());
+        let a = list.push(json!("a"));
+        list.push(json!("b"));
+        list.push(json!("c"));
+
+        list.move_to(&a, 2).unwrap();
+        assert_eq!(list.values(), vec![json!("b"), json!("c"), json!("a")]);
+        assert_eq!(list.index_of(&a), Some(2));
+
+        list.move_to(&a, 1).unwrap();
+        assert_eq!(list.values(), vec![json!("b"), json!("a"), json!("c")]);
+        assert!(list.move_to(&a, 3).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_inserts_converge() {
+        let mut list1 = List::new("replica1".to_string());
+        list1.push(json!("a"));
+        list1.push(json!("z"));
+        let mut list2 = list1.clone();
+        list2.replica_id = "replica2".to_string();
+
+        list1.insert(1, json!("from1")).unwrap();
+        list2.insert(1, json!("from2")).unwrap();
+
+        list1.merge(&list2);
+        list2.merge(&list1);
+
+        assert_eq!(list1.len(), 4);
+        assert_eq!(list1.values(), list2.values());
+    }
+
+    #[test]
+    fn test_concurrent_moves_pick_one_location() {
+        let mut list1 = List::new("replica1".to_string());
+        let a = list1.push(json!("a"));
+        list1.push(json!("b"));
+        list1.push(json!("c"));
+        let mut list2 = List::new("replica2".to_string());
+        list2.merge(&list1);
+
+        list1.move_to(&a, 2).unwrap();
+        list2.move_to(&a, 1).unwrap();
+
+        list1.merge(&list2);
+        list2.merge(&list1);
+
+        assert_eq!(list1.values(), list2.values());
+        assert_eq!(list1.len(), 3);
+        // Equal clocks: the higher replica ID wins
+        assert_eq!(list1.index_of(&a), Some(1));
+    }
+
+    #[test]
+    fn test_delete_wins_over_concurrent_move() {
+        let mut list1 = List::new("replica1".to_string());
+        let a = list1.push(json!("a"));
+        list1.push(json!("b"));
+        let mut list2 = List::new("replica2".to_string());
+        list2.merge(&list1);
+
+        list1.delete(&a);
+        list2.move_to(&a, 1).unwrap();
+
+        list1.merge(&list2);
+        list2.merge(&list1);
+
+        assert_eq!(list1.values(), vec![json!("b")]);
+        assert_eq!(list1.elements, list2.elements);
+    }
+
+    #[test]
+    fn test_serialization_rebuilds_order() {
+        let mut list = List::new("replica1".to_string());
+        let a = list.push(json!("a"));
+        list.push(json!({ "nested": [1, 2] }));
+        list.delete(&a);
+
+        let json = serde_json::to_string(&list).unwrap();
+        let decoded: List = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, list);
+        assert_eq!(decoded.values(), vec![json!({ "nested": [1, 2] })]);
+    }
+}
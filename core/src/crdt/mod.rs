@@ -6,9 +6,14 @@
 //! # CRDTs Implemented (feature-gated)
 //!
 //! - **PN-Counter:** Positive-Negative Counter (`feature = "counters"`)
+//! - **Resettable Counter:** Counter with observed reset (`feature = "counters"`)
+//! - **Bounded Counter:** Non-negative counter with per-replica rights (`feature = "counters"`)
 //! - **OR-Set:** Observed-Remove Set (`feature = "sets"`)
 //! - **Fractional Index:** Position-based ordering (`feature = "fractional-index"`)
+//! - **List:** Ordered list with move, on fractional indexing (`feature = "fractional-index"`)
+//! - **Tree:** Movable tree without cycles (`feature = "fractional-index"`)
 //! - **Text CRDT:** YATA-style collaborative text (`feature = "text-crdt"`)
+//! - **MV-Register:** Multi-Value Register keeping concurrent writes (`feature = "registers"`)
 //!
 //! # Usage
 //!
@@ -27,24 +32,54 @@
 #[cfg(feature = "counters")]
 pub mod pn_counter;
 
+#[cfg(feature = "counters")]
+pub mod resettable_counter;
+
+#[cfg(feature = "counters")]
+pub mod bounded_counter;
+
 #[cfg(feature = "sets")]
 pub mod or_set;
 
 #[cfg(feature = "fractional-index")]
 pub mod fractional_index;
 
+#[cfg(feature = "fractional-index")]
+pub mod list;
+
+#[cfg(feature = "fractional-index")]
+pub mod tree;
+
 #[cfg(feature = "text-crdt")]
 pub mod text;
 
+#[cfg(feature = "registers")]
+pub mod mv_register;
+
 // Re-exports (only if features enabled)
 #[cfg(feature = "counters")]
 pub use pn_counter::PNCounter;
 
+#[cfg(feature = "counters")]
+pub use resettable_counter::ResettableCounter;
+
+#[cfg(feature = "counters")]
+pub use bounded_counter::BoundedCounter;
+
 #[cfg(feature = "sets")]
 pub use or_set::ORSet;
 
 #[cfg(feature = "fractional-index")]
 pub use fractional_index::FractionalIndex;
 
+#[cfg(feature = "fractional-index")]
+pub use list::List;
+
+#[cfg(feature = "fractional-index")]
+pub use tree::Tree;
+
 #[cfg(feature = "text-crdt")]
 pub use text::Text;
+
+#[cfg(feature = "registers")]
+pub use mv_register::MVRegister;
@@ -0,0 +1,233 @@
+//! MV-Register: Multi-Value Register CRDT
+//!
+//! A Last-Write-Wins field silently drops one of two concurrent writes. A
+//! multi-value register keeps both instead: every value written
+//! concurrently survives the merge as a sibling, until a later write that
+//! has seen them all supersedes them.
+//!
+//! # Dots and causal context
+//!
+//! Each write is tagged with a dot, the writing replica plus its write
+//! counter (a `Timestamp`), and the register keeps a `VectorClock` of every
+//! dot it has seen. A sibling is dropped in a merge only if the other side
+//! has seen its dot and no longer holds it, i.e. a later write replaced it.
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::crdt::MVRegister;
+//! use serde_json::json;
+//!
+//! let mut title1 = MVRegister::new("replica1".to_string());
+//! let mut title2 = MVRegister::new("replica2".to_string());
+//!
+//! // Concurrent offline edits
+//! title1.write(json!("Roadmap"));
+//! title2.write(json!("Road map 2025"));
+//! title1.merge(&title2);
+//!
+//! assert!(title1.is_conflicted());
+//! assert_eq!(title1.values().len(), 2);
+//!
+//! // Resolving supersedes both siblings everywhere
+//! title1.resolve(json!("Roadmap 2025"));
+//! title2.merge(&title1);
+//! assert_eq!(title2.values(), vec![&json!("Roadmap 2025")]);
+//! ```
+
+use crate::sync::{Timestamp, VectorClock};
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+
+/// Multi-Value Register CRDT
+///
+/// Holds one value, or several concurrently written siblings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MVRegister<T> {
+    /// Replica identifier
+    replica_id: ClientID,
+
+    /// Current siblings with their dots, sorted by dot
+    siblings: Vec<(Timestamp, T)>,
+
+    /// Every dot this replica has seen
+    context: VectorClock,
+}
+
+impl<T: Clone> MVRegister<T> {
+    /// Create an empty register for the given replica
+    pub fn new(replica_id: ClientID) -> Self {
+        Self {
+            replica_id,
+            siblings: Vec::new(),
+            context: VectorClock::new(),
+        }
+    }
+
+    /// Write a value
+    ///
+    /// The write has seen every current sibling, so it replaces them all.
+    /// Returns the write's dot.
+    pub fn write(&mut self, value: T) -> Timestamp {
+        self.context.tick(&self.replica_id);
+        let dot = Timestamp::new(self.context.get(&self.replica_id), self.replica_id.clone());
+        self.siblings = vec![(dot.clone(), value)];
+        dot
+    }
+
+    /// Resolve a conflict by writing the value that replaces all siblings
+    ///
+    /// Same as `write`; named for call sites that settle a conflict.
+    pub fn resolve(&mut self, value: T) -> Timestamp {
+        self.write(value)
+    }
+
+    /// Get the values of all siblings, in dot order
+    ///
+    /// Empty if nothing was written yet.
+    pub fn values(&self) -> Vec<&T> {
+        self.siblings.iter().map(|(_, value)| value).collect()
+    }
+
+    /// Iterate over the siblings with the dots of their writes
+    pub fn siblings(&self) -> impl Iterator<Item = (&Timestamp, &T)> {
+        self.siblings.iter().map(|(dot, value)| (dot, value))
+    }
+
+    /// Get the value if there is exactly one
+    pub fn get(&self) -> Option<&T> {
+        match self.siblings.as_slice() {
+            [(_, value)] => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Check if concurrent writes are waiting to be resolved
+    pub fn is_conflicted(&self) -> bool {
+        self.siblings.len() > 1
+    }
+
+    /// Get every dot this replica has seen
+    pub fn context(&self) -> &VectorClock {
+        &self.context
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Merge another register's state into this one
+    ///
+    /// A sibling survives if both sides hold it, or if one side holds it and
+    /// the other hasn't seen it yet.
+    /// This operation is commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &MVRegister<T>) {
+        let mut siblings: Vec<(Timestamp, T)> = self
+            .siblings
+            .iter()
+            .filter(|(dot, _)| other.holds(dot) || !other.has_seen(dot))
+            .cloned()
+            .collect();
+        siblings.extend(
+            other
+                .siblings
+                .iter()
+                .filter(|(dot, _)| !self.holds(dot) && !self.has_seen(dot))
+                .cloned(),
+        );
+        siblings.sort_by(|a, b| a.0.cmp(&b.0));
+
+        self.siblings = siblings;
+        self.context.merge(&other.context);
+    }
+
+    /// Check if a dot is in the context
+    fn has_seen(&self, dot: &Timestamp) -> bool {
+        dot.clock <= self.context.get(&dot.client_id)
+    }
+
+    /// Check if a dot is one of the current siblings
+    fn holds(&self, dot: &Timestamp) -> bool {
+        self.siblings.iter().any(|(held, _)| held == dot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_replaces_value() {
+        let mut register = MVRegister::new("replica1".to_string());
+        assert!(register.values().is_empty());
+        assert_eq!(register.get(), None);
+
+        register.write("a");
+        register.write("b");
+
+        assert_eq!(register.get(), Some(&"b"));
+        assert!(!register.is_conflicted());
+    }
+
+    #[test]
+    fn test_concurrent_writes_become_siblings() {
+        let mut register1 = MVRegister::new("replica1".to_string());
+        let mut register2 = MVRegister::new("replica2".to_string());
+
+        register1.write("a");
+        register2.write("b");
+        register1.merge(&register2);
+        register2.merge(&register1);
+
+        assert_eq!(register1.values(), vec![&"a", &"b"]);
+        assert_eq!(register1.values(), register2.values());
+        assert_eq!(register1.get(), None);
+    }
+
+    #[test]
+    fn test_later_write_supersedes_siblings() {
+        let mut register1 = MVRegister::new("replica1".to_string());
+        let mut register2 = MVRegister::new("replica2".to_string());
+        let mut register3 = MVRegister::new("replica3".to_string());
+
+        register1.write("a");
+        register2.write("b");
+        register3.merge(&register1);
+        register3.merge(&register2);
+        register3.resolve("c");
+
+        // Stale siblings don't come back from the replicas that wrote them
+        register3.merge(&register1);
+        register3.merge(&register2);
+        assert_eq!(register3.get(), Some(&"c"));
+
+        register1.merge(&register3);
+        assert_eq!(register1.get(), Some(&"c"));
+    }
+
+    #[test]
+    fn test_write_concurrent_with_resolve_survives() {
+        let mut register1 = MVRegister::new("replica1".to_string());
+        let mut register2 = MVRegister::new("replica2".to_string());
+        register1.write("a");
+        register2.write("b");
+        register1.merge(&register2);
+
+        register1.resolve("resolved");
+        register2.write("newer");
+        register1.merge(&register2);
+
+        assert_eq!(register1.values(), vec![&"resolved", &"newer"]);
+    }
+
+    #[test]
+    fn test_siblings_expose_dots() {
+        let mut register = MVRegister::new("replica1".to_string());
+        let dot = register.write(42);
+
+        let siblings: Vec<_> = register.siblings().collect();
+        assert_eq!(siblings, vec![(&dot, &42)]);
+        assert_eq!(dot, Timestamp::new(1, "replica1".to_string()));
+    }
+}
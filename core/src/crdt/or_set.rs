@@ -9,6 +9,25 @@
 //! - **Add wins:** Concurrent add and remove → element stays in set
 //! - **Unique tags:** Each add gets a unique identifier
 //!
+//! # Dots and causal context
+//!
+//! A tag is a dot: the adding replica plus a counter that the replica
+//! increments for every add. No wall clock is involved, so tags are
+//! deterministic and work on every platform.
+//!
+//! Besides the live dots of each element, the set keeps a causal context:
+//! every dot it has ever seen, stored as a version vector plus the few dots
+//! that arrived out of order. A dot that is in the context but not live was
+//! removed, so removes leave no tombstones behind and the set needs no
+//! garbage collection (see `crate::sync::stability`).
+//!
+//! # Delta-states
+//!
+//! `add`, `remove` and `clear` return a delta: a set holding only the dots
+//! the operation created and the dots it observed. Deltas merge like full
+//! states, so they can be joined into a buffer and shipped instead of the
+//! whole set.
+//!
 //! # Example
 //!
 //! ```
@@ -26,29 +45,136 @@
 //!
 //! assert!(set1.contains(&"apple".to_string()));
 //! assert!(set1.contains(&"banana".to_string()));
+//!
+//! // Ship only what changed
+//! let mut buffer = set1.add("cherry".to_string());
+//! buffer.merge(&set1.remove(&"apple".to_string()));
+//! set2.merge(&buffer);
+//!
+//! assert!(set2.contains(&"cherry".to_string()));
+//! assert!(!set2.contains(&"apple".to_string()));
 //! ```
 
+use crate::sync::VectorClock;
 use crate::ClientID;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
-use std::time::{SystemTime, UNIX_EPOCH};
 
-/// Unique identifier for an element in the set
-///
-/// Combines replica ID and timestamp to ensure global uniqueness
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
-struct UniqueTag {
-    replica_id: ClientID,
-    timestamp: u64,
-    sequence: u64, // For same-timestamp operations
+/// Unique tag of an add: (replica, per-replica counter)
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Dot {
+    /// Replica that made the add
+    pub replica_id: ClientID,
+
+    /// Position of the add among the replica's adds (starting at 1)
+    pub counter: u64,
 }
 
-impl UniqueTag {
-    fn new(replica_id: ClientID, timestamp: u64, sequence: u64) -> Self {
+impl Dot {
+    /// Create a new dot
+    pub fn new(replica_id: ClientID, counter: u64) -> Self {
         Self {
             replica_id,
-            timestamp,
-            sequence,
+            counter,
+        }
+    }
+}
+
+impl std::fmt::Display for Dot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.replica_id, self.counter)
+    }
+}
+
+/// Every dot a replica has seen
+///
+/// Dots numbered 1..=n for a replica are folded into a version vector;
+/// only dots past a gap are stored individually, until the gap fills.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CausalContext {
+    /// Contiguous dots per replica
+    version: VectorClock,
+
+    /// Dots seen out of order
+    cloud: HashSet<Dot>,
+}
+
+impl CausalContext {
+    /// Create an empty context
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if a dot has been seen
+    pub fn contains(&self, dot: &Dot) -> bool {
+        dot.counter <= self.version.get(&dot.replica_id) || self.cloud.contains(dot)
+    }
+
+    /// Get the next dot for a replica's own add
+    pub fn next_dot(&self, replica_id: &ClientID) -> Dot {
+        Dot::new(replica_id.clone(), self.version.get(replica_id) + 1)
+    }
+
+    /// Record a dot as seen
+    pub fn insert(&mut self, dot: Dot) {
+        if !self.contains(&dot) {
+            self.cloud.insert(dot);
+            self.compact();
+        }
+    }
+
+    /// Record dots 1..=counter of a replica as seen
+    pub fn insert_range(&mut self, replica_id: &ClientID, counter: u64) {
+        if counter > self.version.get(replica_id) {
+            self.version.update(replica_id, counter);
+            self.cloud
+                .retain(|dot| dot.replica_id != *replica_id || dot.counter > counter);
+            self.compact();
+        }
+    }
+
+    /// Merge another context into this one
+    pub fn merge(&mut self, other: &CausalContext) {
+        self.version.merge(&other.version);
+        self.cloud.extend(
+            other
+                .cloud
+                .iter()
+                .filter(|dot| dot.counter > self.version.get(&dot.replica_id))
+                .cloned(),
+        );
+        self.compact();
+    }
+
+    /// Get the contiguous part of the context
+    pub fn version(&self) -> &VectorClock {
+        &self.version
+    }
+
+    /// Iterate over the dots seen out of order
+    pub fn cloud(&self) -> impl Iterator<Item = &Dot> {
+        self.cloud.iter()
+    }
+
+    /// Fold dots that continue the version vector into it
+    fn compact(&mut self) {
+        loop {
+            let next: Vec<Dot> = self
+                .cloud
+                .iter()
+                .filter(|dot| dot.counter <= self.version.get(&dot.replica_id) + 1)
+                .cloned()
+                .collect();
+            if next.is_empty() {
+                return;
+            }
+
+            for dot in next {
+                self.cloud.remove(&dot);
+                if dot.counter > self.version.get(&dot.replica_id) {
+                    self.version.update(&dot.replica_id, dot.counter);
+                }
+            }
         }
     }
 }
@@ -56,7 +182,8 @@ impl UniqueTag {
 /// Observed-Remove Set CRDT
 ///
 /// Maintains a set of elements where each add operation is tagged uniquely.
-/// Removes are tracked separately to handle concurrent operations correctly.
+/// Removes drop an element's tags; the causal context remembers them, so a
+/// merge can tell a removed tag from one it hasn't seen yet.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ORSet<T>
 where
@@ -65,16 +192,12 @@ where
     /// Replica identifier
     replica_id: ClientID,
 
-    /// Elements with their unique tags
-    /// Maps element → set of tags that added it
-    elements: HashMap<T, HashSet<UniqueTag>>,
-
-    /// Tags that have been removed
-    /// An element is in the set if it has at least one tag not in this set
-    removed_tags: HashSet<UniqueTag>,
+    /// Elements with their live tags
+    /// Maps element → set of tags that added it (never empty)
+    elements: HashMap<T, HashSet<Dot>>,
 
-    /// Sequence counter for this replica (for same-timestamp operations)
-    sequence: u64,
+    /// Every tag this replica has seen, live or removed
+    context: CausalContext,
 }
 
 impl<T> ORSet<T>
@@ -86,96 +209,137 @@ where
         Self {
             replica_id,
             elements: HashMap::new(),
-            removed_tags: HashSet::new(),
-            sequence: 0,
+            context: CausalContext::new(),
         }
     }
 
-    /// Add an element to the set
+    /// Rebuild a set from its live tags and causal context
     ///
-    /// Creates a unique tag for this add operation.
-    pub fn add(&mut self, element: T) {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_micros() as u64;
+    /// Live tags missing from the context are added to it.
+    pub fn from_parts(
+        replica_id: ClientID,
+        mut elements: HashMap<T, HashSet<Dot>>,
+        mut context: CausalContext,
+    ) -> Self {
+        elements.retain(|_, tags| !tags.is_empty());
+        for dot in elements.values().flatten() {
+            context.insert(dot.clone());
+        }
 
-        self.sequence += 1;
-        let tag = UniqueTag::new(self.replica_id.clone(), timestamp, self.sequence);
+        Self {
+            replica_id,
+            elements,
+            context,
+        }
+    }
 
-        self.elements.entry(element).or_default().insert(tag);
+    /// Add an element to the set
+    ///
+    /// Creates a unique tag for this add operation. The element's older
+    /// tags are all observed now, so the new tag replaces them.
+    ///
+    /// Returns the delta-state: the new tag, with the replaced ones in its
+    /// context.
+    pub fn add(&mut self, element: T) -> ORSet<T> {
+        let dot = self.context.next_dot(&self.replica_id);
+        self.context.insert(dot.clone());
+
+        let mut delta = self.observed_delta(self.elements.get(&element).into_iter().flatten());
+        delta.context.insert(dot.clone());
+        delta
+            .elements
+            .insert(element.clone(), HashSet::from([dot.clone()]));
+
+        self.elements.insert(element, HashSet::from([dot]));
+        delta
     }
 
     /// Remove an element from the set
     ///
-    /// Marks all current tags for this element as removed.
+    /// Drops all tags observed for this element. Tags added concurrently
+    /// elsewhere aren't in the context yet, so they survive a merge.
     /// If the element is added again later, it will get a new tag.
-    pub fn remove(&mut self, element: &T) {
-        if let Some(tags) = self.elements.get(element) {
-            // Mark all tags for this element as removed
-            for tag in tags {
-                self.removed_tags.insert(tag.clone());
-            }
-        }
+    ///
+    /// Returns the delta-state: the removed tags, as context only.
+    pub fn remove(&mut self, element: &T) -> ORSet<T> {
+        let delta = self.observed_delta(self.elements.get(element).into_iter().flatten());
+        self.elements.remove(element);
+        delta
     }
 
     /// Check if an element is in the set
-    ///
-    /// An element is in the set if it has at least one tag that hasn't been removed.
     pub fn contains(&self, element: &T) -> bool {
-        if let Some(tags) = self.elements.get(element) {
-            // Element is in set if any tag is not removed
-            tags.iter().any(|tag| !self.removed_tags.contains(tag))
-        } else {
-            false
-        }
+        self.elements.contains_key(element)
     }
 
     /// Get all elements currently in the set
     pub fn iter(&self) -> impl Iterator<Item = &T> {
-        self.elements
-            .iter()
-            .filter(|(_, tags)| {
-                // Include element if any tag is not removed
-                tags.iter().any(|tag| !self.removed_tags.contains(tag))
-            })
-            .map(|(element, _)| element)
+        self.elements.keys()
     }
 
     /// Get the number of elements in the set
     pub fn len(&self) -> usize {
-        self.iter().count()
+        self.elements.len()
     }
 
     /// Check if the set is empty
     pub fn is_empty(&self) -> bool {
-        self.len() == 0
+        self.elements.is_empty()
     }
 
     /// Merge another OR-Set's state into this one
     ///
-    /// Takes the union of all elements and removed tags.
+    /// A tag stays live if both sides have it, or if one side has it and
+    /// the other hasn't seen it yet. A tag one side has seen but no longer
+    /// has was removed there, and the remove wins over the stale copy.
     pub fn merge(&mut self, other: &ORSet<T>) {
-        // Merge elements (union of tags)
+        // Local tags the other side has removed
+        for (element, tags) in self.elements.iter_mut() {
+            let remote = other.elements.get(element);
+            tags.retain(|dot| {
+                remote.is_some_and(|remote| remote.contains(dot)) || !other.context.contains(dot)
+            });
+        }
+
+        // Remote tags this side hasn't seen
         for (element, tags) in &other.elements {
+            let unseen = tags.iter().filter(|dot| !self.context.contains(dot));
             self.elements
                 .entry(element.clone())
                 .or_default()
-                .extend(tags.clone());
+                .extend(unseen.cloned());
         }
 
-        // Merge removed tags (union)
-        self.removed_tags.extend(other.removed_tags.clone());
+        self.elements.retain(|_, tags| !tags.is_empty());
+        self.context.merge(&other.context);
     }
 
     /// Clear all elements from the set
-    pub fn clear(&mut self) {
-        // Mark all current tags as removed
-        for tags in self.elements.values() {
-            for tag in tags {
-                self.removed_tags.insert(tag.clone());
-            }
+    ///
+    /// Returns the delta-state: every removed tag, as context only.
+    pub fn clear(&mut self) -> ORSet<T> {
+        let delta = self.observed_delta(self.elements.values().flatten());
+        self.elements.clear();
+        delta
+    }
+
+    /// Get the tags currently adding an element
+    pub fn tags(&self, element: &T) -> impl Iterator<Item = &Dot> {
+        self.elements.get(element).into_iter().flatten()
+    }
+
+    /// Get every tag this replica has seen
+    pub fn context(&self) -> &CausalContext {
+        &self.context
+    }
+
+    /// Delta with no live tags whose context holds the given tags
+    fn observed_delta<'a>(&self, dots: impl Iterator<Item = &'a Dot>) -> ORSet<T> {
+        let mut delta = ORSet::new(self.replica_id.clone());
+        for dot in dots {
+            delta.context.insert(dot.clone());
         }
+        delta
     }
 }
 
@@ -331,6 +495,49 @@ mod tests {
         assert!(!set.contains(&"banana".to_string()));
     }
 
+    #[test]
+    fn test_tags_are_deterministic_dots() {
+        let mut set = ORSet::new("replica1".to_string());
+        set.add("apple".to_string());
+        set.add("banana".to_string());
+        set.add("apple".to_string());
+
+        let tags: Vec<&Dot> = set.tags(&"apple".to_string()).collect();
+        assert_eq!(tags, vec![&Dot::new("replica1".to_string(), 3)]);
+        assert_eq!(set.context().version().get(&"replica1".to_string()), 3);
+    }
+
+    #[test]
+    fn test_remove_leaves_no_tombstone() {
+        let mut set1 = ORSet::new("replica1".to_string());
+        set1.add("apple".to_string());
+        let stale = set1.clone();
+
+        set1.remove(&"apple".to_string());
+        assert_eq!(set1.context().cloud().count(), 0);
+
+        // A stale copy can't bring the element back
+        set1.merge(&stale);
+        assert!(!set1.contains(&"apple".to_string()));
+
+        let mut stale = stale;
+        stale.merge(&set1);
+        assert!(!stale.contains(&"apple".to_string()));
+    }
+
+    #[test]
+    fn test_context_compacts_out_of_order_dots() {
+        let mut context = CausalContext::new();
+        context.insert(Dot::new("r".to_string(), 2));
+        context.insert(Dot::new("r".to_string(), 3));
+        assert_eq!(context.cloud().count(), 2);
+        assert!(!context.contains(&Dot::new("r".to_string(), 1)));
+
+        context.insert(Dot::new("r".to_string(), 1));
+        assert_eq!(context.cloud().count(), 0);
+        assert_eq!(context.version().get(&"r".to_string()), 3);
+    }
+
     #[test]
     fn test_iter() {
         let mut set = ORSet::new("replica1".to_string());
@@ -343,4 +550,68 @@ mod tests {
 
         assert_eq!(items, vec!["apple", "banana", "cherry"]);
     }
+
+    #[test]
+    fn test_add_delta_holds_only_new_dot() {
+        let mut set = ORSet::new("replica1".to_string());
+        set.add("apple".to_string());
+        set.add("banana".to_string());
+
+        let delta = set.add("apple".to_string());
+        assert_eq!(delta.len(), 1);
+        assert_eq!(
+            delta.tags(&"apple".to_string()).collect::<Vec<_>>(),
+            vec![&Dot::new("replica1".to_string(), 3)]
+        );
+        // The replaced dot is observed, the unrelated one isn't
+        assert!(delta
+            .context()
+            .contains(&Dot::new("replica1".to_string(), 1)));
+        assert!(!delta
+            .context()
+            .contains(&Dot::new("replica1".to_string(), 2)));
+    }
+
+    #[test]
+    fn test_delta_buffer_converges_with_full_merge() {
+        let mut set1 = ORSet::new("replica1".to_string());
+        let mut set2 = ORSet::new("replica2".to_string());
+        set1.add("apple".to_string());
+        set2.merge(&set1);
+        set2.add("banana".to_string());
+
+        let mut buffer = set1.add("cherry".to_string());
+        buffer.merge(&set1.remove(&"apple".to_string()));
+        buffer.merge(&set1.add("date".to_string()));
+        buffer.merge(&set1.remove(&"date".to_string()));
+
+        let mut full = set2.clone();
+        full.merge(&set1);
+
+        set2.merge(&buffer);
+        assert_eq!(set2.elements, full.elements);
+        assert_eq!(set2.context, full.context);
+
+        let mut items: Vec<_> = set2.iter().cloned().collect();
+        items.sort();
+        assert_eq!(items, vec!["banana", "cherry"]);
+    }
+
+    #[test]
+    fn test_remove_delta_keeps_concurrent_add() {
+        let mut set1 = ORSet::new("replica1".to_string());
+        let mut set2 = ORSet::new("replica2".to_string());
+        set1.add("apple".to_string());
+        set2.merge(&set1);
+
+        let remove = set1.remove(&"apple".to_string());
+        let add = set2.add("apple".to_string());
+
+        set1.merge(&add);
+        set2.merge(&remove);
+
+        assert!(set1.contains(&"apple".to_string()));
+        assert!(set2.contains(&"apple".to_string()));
+        assert_eq!(set1.elements, set2.elements);
+    }
 }
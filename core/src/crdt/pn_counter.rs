@@ -18,15 +18,35 @@
 //! let mut counter2 = PNCounter::new("replica2".to_string());
 //!
 //! // Both replicas increment
-//! counter1.increment(5);
-//! counter2.increment(3);
+//! counter1.increment(5).unwrap();
+//! counter2.increment(3).unwrap();
 //!
 //! // Merge states
 //! counter1.merge(&counter2);
 //!
 //! assert_eq!(counter1.value(), 8);
 //! ```
+//!
+//! # Delta-states
+//!
+//! `increment` and `decrement` return a delta: a counter holding only the
+//! entry that changed. Deltas merge like full states, so they can be joined
+//! into a buffer and shipped instead of the whole counter.
+//!
+//! ```
+//! use synckit_core::crdt::PNCounter;
+//!
+//! let mut counter1 = PNCounter::new("replica1".to_string());
+//! let mut counter2 = PNCounter::new("replica2".to_string());
+//!
+//! let mut buffer = counter1.increment(5).unwrap();
+//! buffer.merge(&counter1.decrement(2).unwrap());
+//!
+//! counter2.merge(&buffer);
+//! assert_eq!(counter2.value(), 3);
+//! ```
 
+use crate::error::{Result, SyncError};
 use crate::ClientID;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -68,60 +88,119 @@ impl PNCounter {
     ///
     /// # Arguments
     ///
-    /// * `amount` - Amount to increment (must be positive)
+    /// * `amount` - Amount to increment (must be non-negative)
     ///
-    /// # Panics
-    ///
-    /// Panics if amount is negative. Use `decrement()` for negative values.
-    pub fn increment(&mut self, amount: i64) {
-        assert!(amount >= 0, "Increment amount must be non-negative");
+    /// Returns the delta-state of the increment, or
+    /// `SyncError::InvalidOperation` if amount is negative or the replica's
+    /// total would overflow; the counter is then left unchanged.
+    pub fn increment(&mut self, amount: i64) -> Result<PNCounter> {
+        if amount < 0 {
+            return Err(SyncError::InvalidOperation(format!(
+                "Increment amount must be non-negative: {}",
+                amount
+            )));
+        }
 
-        let current = self.positive.get(&self.replica_id).unwrap_or(&0);
-        self.positive
-            .insert(self.replica_id.clone(), current + amount);
+        let count = Self::bump(&mut self.positive, &self.replica_id, amount)?;
+        let mut delta = self.empty_delta();
+        delta.positive.insert(self.replica_id.clone(), count);
+        Ok(delta)
     }
 
     /// Decrement the counter by the given amount
     ///
     /// # Arguments
     ///
-    /// * `amount` - Amount to decrement (must be positive)
+    /// * `amount` - Amount to decrement (must be non-negative)
     ///
-    /// # Panics
-    ///
-    /// Panics if amount is negative. Use `increment()` for positive values.
-    pub fn decrement(&mut self, amount: i64) {
-        assert!(amount >= 0, "Decrement amount must be non-negative");
+    /// Returns the delta-state of the decrement, or
+    /// `SyncError::InvalidOperation` if amount is negative or the replica's
+    /// total would overflow; the counter is then left unchanged.
+    pub fn decrement(&mut self, amount: i64) -> Result<PNCounter> {
+        if amount < 0 {
+            return Err(SyncError::InvalidOperation(format!(
+                "Decrement amount must be non-negative: {}",
+                amount
+            )));
+        }
 
-        let current = self.negative.get(&self.replica_id).unwrap_or(&0);
-        self.negative
-            .insert(self.replica_id.clone(), current + amount);
+        let count = Self::bump(&mut self.negative, &self.replica_id, amount)?;
+        let mut delta = self.empty_delta();
+        delta.negative.insert(self.replica_id.clone(), count);
+        Ok(delta)
+    }
+
+    /// Same as `increment`
+    #[deprecated(note = "`increment` returns a `Result` now")]
+    pub fn try_increment(&mut self, amount: i64) -> Result<PNCounter> {
+        self.increment(amount)
+    }
+
+    /// Same as `decrement`
+    #[deprecated(note = "`decrement` returns a `Result` now")]
+    pub fn try_decrement(&mut self, amount: i64) -> Result<PNCounter> {
+        self.decrement(amount)
+    }
+
+    /// Add a signed amount: positive increments, negative decrements
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` on overflow.
+    pub fn add(&mut self, amount: i64) -> Result<PNCounter> {
+        if amount >= 0 {
+            self.increment(amount)
+        } else {
+            let amount = amount.checked_neg().ok_or_else(|| {
+                SyncError::InvalidOperation(format!("Counter amount out of range: {}", amount))
+            })?;
+            self.decrement(amount)
+        }
     }
 
     /// Get the current counter value
     ///
-    /// Returns the sum of all positive counters minus the sum of all negative counters.
+    /// Returns the sum of all positive counters minus the sum of all negative counters,
+    /// saturating at the bounds of `i64`.
     pub fn value(&self) -> i64 {
-        let positive_sum: i64 = self.positive.values().sum();
-        let negative_sum: i64 = self.negative.values().sum();
-        positive_sum - negative_sum
+        let positive_sum: i128 = self.positive.values().map(|&count| count as i128).sum();
+        let negative_sum: i128 = self.negative.values().map(|&count| count as i128).sum();
+        (positive_sum - negative_sum).clamp(i64::MIN as i128, i64::MAX as i128) as i64
     }
 
     /// Merge another PN-Counter's state into this one
     ///
     /// Takes the component-wise maximum of all counters.
     /// This operation is commutative, associative, and idempotent.
+    /// Negative totals can't come from a valid replica and are ignored.
     pub fn merge(&mut self, other: &PNCounter) {
-        // Merge positive counters (take maximum)
-        for (replica, &count) in &other.positive {
-            let current = self.positive.get(replica).unwrap_or(&0);
-            self.positive.insert(replica.clone(), (*current).max(count));
-        }
+        Self::merge_totals(&mut self.positive, &other.positive);
+        Self::merge_totals(&mut self.negative, &other.negative);
+    }
 
-        // Merge negative counters (take maximum)
-        for (replica, &count) in &other.negative {
-            let current = self.negative.get(replica).unwrap_or(&0);
-            self.negative.insert(replica.clone(), (*current).max(count));
+    /// Raise a replica's total by amount, checking for overflow
+    fn bump(
+        totals: &mut HashMap<ClientID, i64>,
+        replica_id: &ClientID,
+        amount: i64,
+    ) -> Result<i64> {
+        let current = totals.get(replica_id).copied().unwrap_or(0);
+        let count = current.checked_add(amount).ok_or_else(|| {
+            SyncError::InvalidOperation(format!(
+                "Counter total overflows: {} + {}",
+                current, amount
+            ))
+        })?;
+        totals.insert(replica_id.clone(), count);
+        Ok(count)
+    }
+
+    /// Take the maximum of each replica's total
+    fn merge_totals(totals: &mut HashMap<ClientID, i64>, other: &HashMap<ClientID, i64>) {
+        for (replica, &count) in other {
+            if count < 0 {
+                continue;
+            }
+            let current = totals.entry(replica.clone()).or_insert(0);
+            *current = (*current).max(count);
         }
     }
 
@@ -130,10 +209,42 @@ impl PNCounter {
         &self.replica_id
     }
 
+    /// Iterate over the increment totals per replica
+    pub fn increments(&self) -> impl Iterator<Item = (&ClientID, i64)> {
+        self.positive
+            .iter()
+            .map(|(replica, &count)| (replica, count))
+    }
+
+    /// Iterate over the decrement totals per replica
+    pub fn decrements(&self) -> impl Iterator<Item = (&ClientID, i64)> {
+        self.negative
+            .iter()
+            .map(|(replica, &count)| (replica, count))
+    }
+
+    /// Rebuild a counter from its per-replica totals
+    pub fn from_parts(
+        replica_id: ClientID,
+        positive: HashMap<ClientID, i64>,
+        negative: HashMap<ClientID, i64>,
+    ) -> Self {
+        Self {
+            replica_id,
+            positive,
+            negative,
+        }
+    }
+
+    /// Counter with no entries, the base of a delta-state
+    fn empty_delta(&self) -> PNCounter {
+        Self::from_parts(self.replica_id.clone(), HashMap::new(), HashMap::new())
+    }
+
     /// Reset the counter to zero
     ///
     /// Note: This is a local operation and won't affect other replicas.
-    /// For true distributed reset, all replicas must coordinate.
+    /// For a reset that converges, use `ResettableCounter`.
     pub fn reset(&mut self) {
         self.positive.clear();
         self.negative.clear();
@@ -156,25 +267,25 @@ mod tests {
     #[test]
     fn test_increment() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.increment(5);
+        counter.increment(5).unwrap();
         assert_eq!(counter.value(), 5);
 
-        counter.increment(3);
+        counter.increment(3).unwrap();
         assert_eq!(counter.value(), 8);
     }
 
     #[test]
     fn test_decrement() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.increment(10);
-        counter.decrement(3);
+        counter.increment(10).unwrap();
+        counter.decrement(3).unwrap();
         assert_eq!(counter.value(), 7);
     }
 
     #[test]
     fn test_negative_value() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.decrement(5);
+        counter.decrement(5).unwrap();
         assert_eq!(counter.value(), -5);
     }
 
@@ -183,8 +294,8 @@ mod tests {
         let mut counter1 = PNCounter::new("replica1".to_string());
         let mut counter2 = PNCounter::new("replica1".to_string());
 
-        counter1.increment(5);
-        counter2.increment(3);
+        counter1.increment(5).unwrap();
+        counter2.increment(3).unwrap();
 
         counter1.merge(&counter2);
 
@@ -197,8 +308,8 @@ mod tests {
         let mut counter1 = PNCounter::new("replica1".to_string());
         let mut counter2 = PNCounter::new("replica2".to_string());
 
-        counter1.increment(5);
-        counter2.increment(3);
+        counter1.increment(5).unwrap();
+        counter2.increment(3).unwrap();
 
         counter1.merge(&counter2);
 
@@ -211,11 +322,11 @@ mod tests {
         let mut counter1 = PNCounter::new("replica1".to_string());
         let mut counter2 = PNCounter::new("replica2".to_string());
 
-        counter1.increment(10);
-        counter1.decrement(2);
+        counter1.increment(10).unwrap();
+        counter1.decrement(2).unwrap();
 
-        counter2.increment(5);
-        counter2.decrement(3);
+        counter2.increment(5).unwrap();
+        counter2.decrement(3).unwrap();
 
         counter1.merge(&counter2);
 
@@ -228,7 +339,7 @@ mod tests {
         let mut counter1 = PNCounter::new("replica1".to_string());
         let counter2 = PNCounter::new("replica2".to_string());
 
-        counter1.increment(5);
+        counter1.increment(5).unwrap();
 
         counter1.merge(&counter2);
         let value1 = counter1.value();
@@ -246,12 +357,12 @@ mod tests {
         let mut counter1b = counter1a.clone();
         let counter2 = {
             let mut c = PNCounter::new("replica2".to_string());
-            c.increment(5);
+            c.increment(5).unwrap();
             c
         };
         let counter3 = {
             let mut c = PNCounter::new("replica3".to_string());
-            c.increment(3);
+            c.increment(3).unwrap();
             c
         };
 
@@ -269,8 +380,8 @@ mod tests {
     #[test]
     fn test_reset() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.increment(10);
-        counter.decrement(3);
+        counter.increment(10).unwrap();
+        counter.decrement(3).unwrap();
 
         assert_eq!(counter.value(), 7);
 
@@ -279,16 +390,127 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "Increment amount must be non-negative")]
-    fn test_increment_negative_panics() {
+    fn test_deltas_only_hold_changed_entry() {
+        let mut counter = PNCounter::new("replica1".to_string());
+        counter.increment(4).unwrap();
+
+        let delta = counter.increment(3).unwrap();
+        assert_eq!(
+            delta.increments().collect::<Vec<_>>(),
+            vec![(&"replica1".to_string(), 7)]
+        );
+        assert_eq!(delta.decrements().count(), 0);
+    }
+
+    #[test]
+    fn test_delta_buffer_converges_with_full_merge() {
+        let mut counter1 = PNCounter::new("replica1".to_string());
+        let mut counter2 = PNCounter::new("replica2".to_string());
+        counter2.increment(10).unwrap();
+
+        let mut buffer = counter1.increment(5).unwrap();
+        buffer.merge(&counter1.decrement(2).unwrap());
+        buffer.merge(&counter1.increment(1).unwrap());
+
+        let mut full = counter2.clone();
+        full.merge(&counter1);
+
+        counter2.merge(&buffer);
+        assert_eq!(counter2.value(), 14);
+        assert_eq!(counter2.value(), full.value());
+
+        // Delivering the same delta again changes nothing
+        counter2.merge(&buffer);
+        assert_eq!(counter2.value(), 14);
+    }
+
+    #[test]
+    fn test_try_operations_reject_bad_amounts() {
+        let mut counter = PNCounter::new("replica1".to_string());
+        counter.increment(5).unwrap();
+
+        assert!(matches!(
+            counter.increment(-1),
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            counter.decrement(-1),
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            counter.increment(i64::MAX),
+            Err(SyncError::InvalidOperation(_))
+        ));
+
+        // Failed operations leave the counter alone
+        assert_eq!(counter.value(), 5);
+    }
+
+    #[test]
+    fn test_add_signed_amounts() {
+        let mut counter = PNCounter::new("replica1".to_string());
+        counter.add(10).unwrap();
+        let delta = counter.add(-4).unwrap();
+
+        assert_eq!(counter.value(), 6);
+        assert_eq!(delta.value(), -4);
+        assert!(counter.add(i64::MIN).is_err());
+        assert_eq!(counter.value(), 6);
+    }
+
+    #[test]
+    fn test_value_saturates() {
+        let mut counter1 = PNCounter::new("replica1".to_string());
+        let mut counter2 = PNCounter::new("replica2".to_string());
+        counter1.increment(i64::MAX).unwrap();
+        counter2.increment(i64::MAX).unwrap();
+
+        counter1.merge(&counter2);
+        assert_eq!(counter1.value(), i64::MAX);
+
+        counter1.decrement(i64::MAX).unwrap();
+        assert_eq!(counter1.value(), i64::MAX);
+    }
+
+    #[test]
+    fn test_merge_ignores_negative_totals() {
+        let mut counter = PNCounter::new("replica1".to_string());
+        counter.increment(3).unwrap();
+
+        let malformed = PNCounter::from_parts(
+            "replica2".to_string(),
+            HashMap::from([("replica2".to_string(), -100)]),
+            HashMap::new(),
+        );
+        counter.merge(&malformed);
+        assert_eq!(counter.value(), 3);
+    }
+
+    #[test]
+    fn test_increment_negative_fails() {
+        let mut counter = PNCounter::new("replica1".to_string());
+        let error = counter.increment(-5).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Increment amount must be non-negative"));
+    }
+
+    #[test]
+    fn test_decrement_negative_fails() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.increment(-5);
+        let error = counter.decrement(-5).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Decrement amount must be non-negative"));
     }
 
     #[test]
-    #[should_panic(expected = "Decrement amount must be non-negative")]
-    fn test_decrement_negative_panics() {
+    #[allow(deprecated)]
+    fn test_try_variants_match() {
         let mut counter = PNCounter::new("replica1".to_string());
-        counter.decrement(-5);
+        counter.try_increment(5).unwrap();
+        counter.try_decrement(2).unwrap();
+        assert_eq!(counter.value(), 3);
+        assert!(counter.try_increment(-1).is_err());
     }
 }
@@ -0,0 +1,266 @@
+//! Resettable Counter: PN-Counter with a convergent reset
+//!
+//! `PNCounter::reset` is local-only: the next merge brings the old totals
+//! back. This counter makes reset part of the replicated state instead.
+//!
+//! # Observed reset
+//!
+//! Increments and decrements are kept as per-replica totals, i.e. two
+//! version vectors. A reset records the totals it has observed in a second
+//! pair of version vectors, the reset baseline. The value only counts what
+//! lies above the baseline, so:
+//!
+//! - everything the reset observed is cleared on every replica
+//! - increments made concurrently with the reset aren't covered by the
+//!   baseline and survive
+//!
+//! All four vectors merge by pointwise maximum, so the counter is a
+//! state-based CRDT like `PNCounter`, and mutators return delta-states the
+//! same way.
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::crdt::ResettableCounter;
+//!
+//! let mut counter1 = ResettableCounter::new("replica1".to_string());
+//! let mut counter2 = ResettableCounter::new("replica2".to_string());
+//!
+//! counter1.increment(5).unwrap();
+//! counter2.merge(&counter1);
+//!
+//! // Reset on one replica while the other keeps counting
+//! counter2.reset();
+//! counter1.increment(2).unwrap();
+//!
+//! counter1.merge(&counter2);
+//! counter2.merge(&counter1);
+//!
+//! assert_eq!(counter1.value(), 2);
+//! assert_eq!(counter2.value(), 2);
+//! ```
+
+use crate::error::{Result, SyncError};
+use crate::sync::VectorClock;
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+
+/// Counter CRDT whose reset converges across replicas
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResettableCounter {
+    /// Replica identifier
+    replica_id: ClientID,
+
+    /// Increment totals per replica
+    increments: VectorClock,
+
+    /// Decrement totals per replica
+    decrements: VectorClock,
+
+    /// Increment totals observed by resets
+    reset_increments: VectorClock,
+
+    /// Decrement totals observed by resets
+    reset_decrements: VectorClock,
+}
+
+impl ResettableCounter {
+    /// Create a new counter for the given replica
+    pub fn new(replica_id: ClientID) -> Self {
+        Self {
+            replica_id,
+            increments: VectorClock::new(),
+            decrements: VectorClock::new(),
+            reset_increments: VectorClock::new(),
+            reset_decrements: VectorClock::new(),
+        }
+    }
+
+    /// Increment the counter by the given amount
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` if the
+    /// replica's total would overflow.
+    pub fn increment(&mut self, amount: u64) -> Result<ResettableCounter> {
+        let total = Self::bump(&mut self.increments, &self.replica_id, amount)?;
+        let mut delta = Self::new(self.replica_id.clone());
+        delta.increments.update(&self.replica_id, total);
+        Ok(delta)
+    }
+
+    /// Decrement the counter by the given amount
+    ///
+    /// Returns the delta-state, or `SyncError::InvalidOperation` if the
+    /// replica's total would overflow.
+    pub fn decrement(&mut self, amount: u64) -> Result<ResettableCounter> {
+        let total = Self::bump(&mut self.decrements, &self.replica_id, amount)?;
+        let mut delta = Self::new(self.replica_id.clone());
+        delta.decrements.update(&self.replica_id, total);
+        Ok(delta)
+    }
+
+    /// Add a signed amount: positive increments, negative decrements
+    pub fn add(&mut self, amount: i64) -> Result<ResettableCounter> {
+        if amount >= 0 {
+            self.increment(amount.unsigned_abs())
+        } else {
+            self.decrement(amount.unsigned_abs())
+        }
+    }
+
+    /// Reset the counter to zero on every replica
+    ///
+    /// Clears every increment and decrement this replica has seen. Ones
+    /// made concurrently elsewhere survive the merge.
+    ///
+    /// Returns the delta-state of the reset.
+    pub fn reset(&mut self) -> ResettableCounter {
+        self.reset_increments.merge(&self.increments);
+        self.reset_decrements.merge(&self.decrements);
+
+        let mut delta = Self::new(self.replica_id.clone());
+        delta.reset_increments = self.reset_increments.clone();
+        delta.reset_decrements = self.reset_decrements.clone();
+        delta
+    }
+
+    /// Get the current counter value
+    ///
+    /// Sums what every replica counted since the last reset that observed
+    /// it, saturating at the bounds of `i64`.
+    pub fn value(&self) -> i64 {
+        let positive = Self::since_reset(&self.increments, &self.reset_increments);
+        let negative = Self::since_reset(&self.decrements, &self.reset_decrements);
+        (positive - negative).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+    }
+
+    /// Merge another counter's state into this one
+    ///
+    /// Takes the pointwise maximum of the totals and of the reset baselines.
+    /// This operation is commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &ResettableCounter) {
+        self.increments.merge(&other.increments);
+        self.decrements.merge(&other.decrements);
+        self.reset_increments.merge(&other.reset_increments);
+        self.reset_decrements.merge(&other.reset_decrements);
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Raise a replica's total by amount, checking for overflow
+    fn bump(totals: &mut VectorClock, replica_id: &ClientID, amount: u64) -> Result<u64> {
+        let current = totals.get(replica_id);
+        let total = current.checked_add(amount).ok_or_else(|| {
+            SyncError::InvalidOperation(format!(
+                "Counter total overflows: {} + {}",
+                current, amount
+            ))
+        })?;
+        totals.update(replica_id, total);
+        Ok(total)
+    }
+
+    /// Sum of each replica's total above its reset baseline
+    fn since_reset(totals: &VectorClock, baseline: &VectorClock) -> i128 {
+        totals
+            .clocks()
+            .iter()
+            .map(|(replica, &total)| total.saturating_sub(baseline.get(replica)) as i128)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_increment_and_decrement() {
+        let mut counter = ResettableCounter::new("replica1".to_string());
+        counter.increment(10).unwrap();
+        counter.decrement(3).unwrap();
+        counter.add(-2).unwrap();
+
+        assert_eq!(counter.value(), 5);
+    }
+
+    #[test]
+    fn test_reset_survives_merge() {
+        let mut counter1 = ResettableCounter::new("replica1".to_string());
+        let mut counter2 = ResettableCounter::new("replica2".to_string());
+        counter1.increment(5).unwrap();
+        counter2.decrement(2).unwrap();
+        counter1.merge(&counter2);
+        counter2.merge(&counter1);
+
+        counter1.reset();
+        assert_eq!(counter1.value(), 0);
+
+        // The old totals don't come back
+        counter1.merge(&counter2);
+        counter2.merge(&counter1);
+        assert_eq!(counter1.value(), 0);
+        assert_eq!(counter2.value(), 0);
+    }
+
+    #[test]
+    fn test_concurrent_increment_survives_reset() {
+        let mut counter1 = ResettableCounter::new("replica1".to_string());
+        let mut counter2 = ResettableCounter::new("replica2".to_string());
+        counter1.increment(5).unwrap();
+        counter2.merge(&counter1);
+
+        let reset = counter1.reset();
+        counter2.increment(3).unwrap();
+        counter2.decrement(1).unwrap();
+
+        counter1.merge(&counter2);
+        counter2.merge(&reset);
+
+        assert_eq!(counter1.value(), 2);
+        assert_eq!(counter2.value(), 2);
+        assert_eq!(counter1.increments, counter2.increments);
+    }
+
+    #[test]
+    fn test_counting_after_reset() {
+        let mut counter = ResettableCounter::new("replica1".to_string());
+        counter.increment(7).unwrap();
+        counter.reset();
+        counter.increment(4).unwrap();
+
+        assert_eq!(counter.value(), 4);
+    }
+
+    #[test]
+    fn test_deltas_converge_with_full_merge() {
+        let mut counter1 = ResettableCounter::new("replica1".to_string());
+        let mut counter2 = ResettableCounter::new("replica2".to_string());
+        counter2.increment(1).unwrap();
+
+        let mut buffer = counter1.increment(5).unwrap();
+        buffer.merge(&counter1.reset());
+        buffer.merge(&counter1.decrement(2).unwrap());
+
+        let mut full = counter2.clone();
+        full.merge(&counter1);
+        counter2.merge(&buffer);
+
+        assert_eq!(counter2, full);
+        assert_eq!(counter2.value(), -1);
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let mut counter = ResettableCounter::new("replica1".to_string());
+        counter.increment(u64::MAX).unwrap();
+
+        assert!(matches!(
+            counter.increment(1),
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert_eq!(counter.value(), i64::MAX);
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Each item in the text CRDT has a unique ID composed of:
 //! - Client ID: Identifies the replica that created the item
-//! - Clock: Lamport timestamp for ordering
+//! - Clock: Sequence number of the character among the client's insertions
 
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -17,7 +17,7 @@ pub struct ItemId {
     /// Client that created this item
     pub client: u64,
 
-    /// Lamport clock at creation time
+    /// Sequence number among the client's characters (0, 1, 2, ...)
     pub clock: u64,
 }
 
@@ -28,6 +28,10 @@ impl ItemId {
     }
 
     /// Check if this is a root item (clock 0)
+    #[deprecated(
+        note = "there is no root item: clock 0 is every client's first character; \
+                an item inserted at the start has `Item::left == None`"
+    )]
     pub fn is_root(&self) -> bool {
         self.clock == 0
     }
@@ -41,7 +45,7 @@ impl PartialOrd for ItemId {
 
 impl Ord for ItemId {
     fn cmp(&self, other: &Self) -> Ordering {
-        // First compare by clock (Lamport timestamp)
+        // First compare by clock
         match self.clock.cmp(&other.clock) {
             Ordering::Equal => {
                 // If clocks are equal, use client ID for deterministic ordering
@@ -96,6 +100,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(deprecated)]
     fn test_root_item() {
         let root = ItemId::new(0, 0);
         let normal = ItemId::new(1, 5);
@@ -1,10 +1,19 @@
 //! Item: The fundamental building block of the Text CRDT
 //!
 //! Each item represents a character (or block of characters) with:
-//! - Unique ID
+//! - Unique ID (of its first character; the others follow consecutively)
 //! - Content
 //! - Left/right origins for conflict resolution
 //! - Deleted flag (tombstone)
+//!
+//! Garbage collection may drop the content of a deleted item. The item
+//! keeps its ID range and origins (other items and marks refer to them),
+//! and only remembers how many characters it held.
+//!
+//! Inside a block, each character's left origin is the character before
+//! it and all characters share the block's right origin. That makes a
+//! block equivalent to the same characters inserted one by one, so it can
+//! be split and merged freely.
 
 use super::id::ItemId;
 use serde::{Deserialize, Serialize};
@@ -17,18 +26,18 @@ use serde::{Deserialize, Serialize};
 ///
 /// This enables deterministic conflict resolution when multiple
 /// clients insert at the same position concurrently.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Item {
-    /// Unique identifier for this item
+    /// Unique identifier for this item (its first character)
     pub id: ItemId,
 
     /// The actual content (single character or merged block)
     pub content: String,
 
-    /// Item this was inserted after (None for first item)
+    /// Character the first character was inserted after (None for first item)
     pub left: Option<ItemId>,
 
-    /// Item this was inserted before (None for append)
+    /// Character this was inserted before (None for append)
     pub right: Option<ItemId>,
 
     /// Whether this item has been deleted
@@ -36,6 +45,16 @@ pub struct Item {
 
     /// Client that created this item (redundant with id.client but convenient)
     pub client: u64,
+
+    /// Number of characters whose content was garbage collected
+    ///
+    /// Non-zero only for deleted items, whose `content` is then empty.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub collected: usize,
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
 }
 
 impl Item {
@@ -48,6 +67,25 @@ impl Item {
             left,
             right,
             deleted: false,
+            collected: 0,
+        }
+    }
+
+    /// Create a deleted item whose content was garbage collected
+    pub fn new_collected(
+        id: ItemId,
+        len: usize,
+        left: Option<ItemId>,
+        right: Option<ItemId>,
+    ) -> Self {
+        Self {
+            client: id.client,
+            id,
+            content: String::new(),
+            left,
+            right,
+            deleted: true,
+            collected: len,
         }
     }
 
@@ -56,14 +94,43 @@ impl Item {
         Self::new(id, ch.to_string(), left, right)
     }
 
-    /// Get the length of this item's content
+    /// Get the length of this item's content (in chars)
     pub fn len(&self) -> usize {
-        self.content.len()
+        if self.is_collected() {
+            self.collected
+        } else {
+            self.content.chars().count()
+        }
     }
 
     /// Check if this item is empty
     pub fn is_empty(&self) -> bool {
-        self.content.is_empty()
+        self.len() == 0
+    }
+
+    /// Check if this item's content was garbage collected
+    pub fn is_collected(&self) -> bool {
+        self.collected > 0
+    }
+
+    /// Clock just past this item's last character
+    pub fn end_clock(&self) -> u64 {
+        self.id.clock + self.len() as u64
+    }
+
+    /// Get the ID of the character at a char offset
+    pub fn id_at(&self, offset: usize) -> ItemId {
+        ItemId::new(self.id.client, self.id.clock + offset as u64)
+    }
+
+    /// Get the ID of the last character
+    pub fn last_id(&self) -> ItemId {
+        self.id_at(self.len().saturating_sub(1))
+    }
+
+    /// Check if a character belongs to this item
+    pub fn contains(&self, id: ItemId) -> bool {
+        id.client == self.id.client && id.clock >= self.id.clock && id.clock < self.end_clock()
     }
 
     /// Mark this item as deleted
@@ -71,18 +138,77 @@ impl Item {
         self.deleted = true;
     }
 
+    /// Drop the content of a deleted item, keeping its length
+    pub fn collect(&mut self) {
+        if self.deleted && !self.is_collected() {
+            self.collected = self.len();
+            self.content = String::new();
+        }
+    }
+
+    /// Split this item at a char offset, returning the right part
+    ///
+    /// This item keeps the first `offset` characters. The right part starts
+    /// at the character with ID `id_at(offset)`, whose left origin is the
+    /// last character kept here.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is not within `1..len()`.
+    pub fn split(&mut self, offset: usize) -> Item {
+        assert!(
+            offset > 0 && offset < self.len(),
+            "Split offset {} out of range for item of length {}",
+            offset,
+            self.len()
+        );
+
+        if self.is_collected() {
+            let right_len = self.collected - offset;
+            self.collected = offset;
+            return Item::new_collected(
+                self.id_at(offset),
+                right_len,
+                Some(self.id_at(offset - 1)),
+                self.right,
+            );
+        }
+
+        let byte_offset = self
+            .content
+            .char_indices()
+            .nth(offset)
+            .map(|(index, _)| index)
+            .expect("Offset checked above");
+
+        Item {
+            id: self.id_at(offset),
+            content: self.content.split_off(byte_offset),
+            left: Some(self.id_at(offset - 1)),
+            right: self.right,
+            deleted: self.deleted,
+            client: self.client,
+            collected: 0,
+        }
+    }
+
     /// Check if this item can be merged with another
     ///
     /// Items can be merged if:
     /// - Same client
     /// - Sequential IDs
-    /// - Same deletion status
-    /// - Adjacent in the list
+    /// - Other was inserted right after our last character
+    /// - Same right origin
+    /// - Same deletion status (and both or neither garbage collected)
+    ///
+    /// The caller must also check that they are adjacent in the list.
     pub fn can_merge_with(&self, other: &Item) -> bool {
         self.client == other.client
             && self.deleted == other.deleted
-            && self.id.clock + 1 == other.id.clock
-            && self.right == Some(other.id)
+            && self.is_collected() == other.is_collected()
+            && self.end_clock() == other.id.clock
+            && other.left == Some(self.last_id())
+            && self.right == other.right
     }
 
     /// Merge another item into this one
@@ -90,7 +216,7 @@ impl Item {
     /// Assumes can_merge_with returned true
     pub fn merge(&mut self, other: &Item) {
         self.content.push_str(&other.content);
-        self.right = other.right;
+        self.collected += other.collected;
     }
 }
 
@@ -126,15 +252,23 @@ mod tests {
         let id2 = ItemId::new(1, 11);
         let id3 = ItemId::new(2, 11);
 
-        let item1 = Item::new_char(id1, 'a', None, Some(id2));
+        let item1 = Item::new_char(id1, 'a', None, None);
         let item2 = Item::new_char(id2, 'b', Some(id1), None);
-        let item3 = Item::new_char(id3, 'c', None, None);
+        let item3 = Item::new_char(id3, 'c', Some(id2), None);
 
         // Sequential items from same client
         assert!(item1.can_merge_with(&item2));
 
         // Different clients
         assert!(!item2.can_merge_with(&item3));
+
+        // Different right origins
+        let item4 = Item::new_char(id2, 'b', Some(id1), Some(id3));
+        assert!(!item1.can_merge_with(&item4));
+
+        // Not inserted after the previous character
+        let item5 = Item::new_char(id2, 'b', None, None);
+        assert!(!item1.can_merge_with(&item5));
     }
 
     #[test]
@@ -143,12 +277,68 @@ mod tests {
         let id2 = ItemId::new(1, 11);
         let id3 = ItemId::new(1, 12);
 
-        let mut item1 = Item::new_char(id1, 'a', None, Some(id2));
+        let mut item1 = Item::new_char(id1, 'a', None, Some(id3));
         let item2 = Item::new_char(id2, 'b', Some(id1), Some(id3));
 
+        assert!(item1.can_merge_with(&item2));
         item1.merge(&item2);
 
         assert_eq!(item1.content, "ab");
+        assert_eq!(item1.len(), 2);
+        assert_eq!(item1.last_id(), id2);
         assert_eq!(item1.right, Some(id3));
     }
+
+    #[test]
+    fn test_item_split() {
+        let id = ItemId::new(1, 10);
+        let origin = ItemId::new(2, 3);
+        let mut item = Item::new(id, "héllo".to_string(), Some(origin), None);
+
+        let right = item.split(2);
+
+        assert_eq!(item.content, "hé");
+        assert_eq!(item.left, Some(origin));
+        assert_eq!(right.content, "llo");
+        assert_eq!(right.id, ItemId::new(1, 12));
+        assert_eq!(right.left, Some(ItemId::new(1, 11)));
+        assert!(item.contains(ItemId::new(1, 11)));
+        assert!(!item.contains(ItemId::new(1, 12)));
+
+        // Splitting and merging back restores the original
+        assert!(item.can_merge_with(&right));
+        item.merge(&right);
+        assert_eq!(item.content, "héllo");
+    }
+
+    #[test]
+    fn test_collect_keeps_length() {
+        let id = ItemId::new(1, 10);
+        let mut item = Item::new(id, "héllo".to_string(), None, None);
+        item.collect();
+        assert!(!item.is_collected()); // Not deleted
+
+        item.delete();
+        item.collect();
+        assert!(item.is_collected());
+        assert_eq!(item.content, "");
+        assert_eq!(item.len(), 5);
+
+        let right = item.split(2);
+        assert_eq!((item.len(), right.len()), (2, 3));
+        assert!(right.deleted && right.is_collected());
+
+        // Collected and uncollected parts don't merge
+        let mut live = Item::new(
+            ItemId::new(1, 15),
+            "!".to_string(),
+            Some(ItemId::new(1, 14)),
+            None,
+        );
+        live.delete();
+        assert!(!right.can_merge_with(&live));
+
+        item.merge(&right);
+        assert_eq!(item.len(), 5);
+    }
 }
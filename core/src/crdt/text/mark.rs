@@ -0,0 +1,181 @@
+//! Formatting marks: rich-text attributes on ranges of a Text
+//!
+//! A mark sets one attribute (`bold`, `link`, ...) on a range of characters.
+//! Its boundaries are anchored to character IDs rather than positions, so
+//! the range follows concurrent edits:
+//! - A boundary sits just before or just after a character (or at either
+//!   end of the document)
+//! - `Expand` decides whether text typed at an edge of the range joins it
+//!
+//! Marks are never removed. Clearing an attribute adds a mark with a `null`
+//! value, and for every character and attribute the mark with the highest
+//! `MarkId` wins, so concurrent formatting converges on every replica.
+
+use super::id::ItemId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+
+/// Formatting attributes of a run of text (attribute name → value)
+pub type Attributes = BTreeMap<String, JsonValue>;
+
+/// Unique, totally ordered identifier for a mark
+///
+/// Ordered by Lamport clock, then client ID: a mark made after seeing
+/// another one always wins over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct MarkId {
+    /// Lamport clock of the replica that created the mark
+    pub lamport: u64,
+
+    /// Client that created the mark
+    pub client: u64,
+}
+
+impl MarkId {
+    /// Create a new mark ID
+    pub fn new(lamport: u64, client: u64) -> Self {
+        Self { lamport, client }
+    }
+}
+
+/// Boundary of a mark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Anchor {
+    /// Start of the document
+    Start,
+
+    /// Just before a character
+    Before(ItemId),
+
+    /// Just after a character
+    After(ItemId),
+
+    /// End of the document
+    End,
+}
+
+impl Anchor {
+    /// Character the boundary is attached to, if any
+    pub fn id(&self) -> Option<ItemId> {
+        match self {
+            Anchor::Before(id) | Anchor::After(id) => Some(*id),
+            Anchor::Start | Anchor::End => None,
+        }
+    }
+}
+
+/// Whether text inserted at the edges of a mark gets the mark too
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Expand {
+    /// Neither edge grows (e.g. links)
+    None,
+
+    /// Text typed just before the range joins it
+    Left,
+
+    /// Text typed just after the range joins it (e.g. bold, italic)
+    #[default]
+    Right,
+
+    /// Both edges grow
+    Both,
+}
+
+impl Expand {
+    /// Check if the left edge grows
+    pub fn left(self) -> bool {
+        matches!(self, Expand::Left | Expand::Both)
+    }
+
+    /// Check if the right edge grows
+    pub fn right(self) -> bool {
+        matches!(self, Expand::Right | Expand::Both)
+    }
+}
+
+/// An attribute set on a range of characters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mark {
+    /// Unique identifier (decides which mark wins)
+    pub id: MarkId,
+
+    /// Attribute name
+    pub name: String,
+
+    /// Attribute value (`null` clears the attribute)
+    pub value: JsonValue,
+
+    /// Boundary before the first covered character
+    pub start: Anchor,
+
+    /// Boundary after the last covered character
+    pub end: Anchor,
+}
+
+impl Mark {
+    /// Characters the mark's boundaries are attached to
+    pub fn anchor_ids(&self) -> impl Iterator<Item = ItemId> {
+        self.start.id().into_iter().chain(self.end.id())
+    }
+}
+
+/// A run of text with uniform formatting (Quill/ProseMirror delta style)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextRun {
+    /// The run's text
+    pub insert: String,
+
+    /// Attributes set on the whole run
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: Attributes,
+}
+
+impl TextRun {
+    /// Create a run of unformatted text
+    pub fn new(insert: impl Into<String>) -> Self {
+        Self {
+            insert: insert.into(),
+            attributes: Attributes::new(),
+        }
+    }
+
+    /// Set an attribute on the run
+    pub fn with_attribute(mut self, name: impl Into<String>, value: JsonValue) -> Self {
+        self.attributes.insert(name.into(), value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mark_id_order() {
+        assert!(MarkId::new(2, 1) > MarkId::new(1, 9));
+        assert!(MarkId::new(1, 2) > MarkId::new(1, 1));
+    }
+
+    #[test]
+    fn test_text_run_json() {
+        let runs = vec![
+            TextRun::new("Hello ").with_attribute("bold", json!(true)),
+            TextRun::new("world"),
+        ];
+
+        let json = serde_json::to_value(&runs).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                {"insert": "Hello ", "attributes": {"bold": true}},
+                {"insert": "world"},
+            ])
+        );
+
+        let back: Vec<TextRun> = serde_json::from_value(json).unwrap();
+        assert_eq!(back, runs);
+    }
+}
@@ -5,10 +5,17 @@
 //!
 //! # Features
 //!
-//! - **High performance**: O(1) for sequential typing, O(log n) for random inserts
+//! - **High performance**: O(log n) inserts and deletes at any position
 //! - **Block merging**: Automatically merges adjacent insertions for efficiency
 //! - **Deterministic**: Concurrent operations always converge to the same state
-//! - **Tombstones**: Deleted items preserved for correct merging
+//! - **Tombstones**: Deleted items preserved for correct merging; their
+//!   content is garbage collected once stable (`Text::collect_garbage`)
+//! - **Rich text**: Formatting marks anchored to characters, exported and
+//!   imported as delta-style runs (see `TextRun`)
+//! - **Relative positions**: Cursors anchored to characters survive
+//!   concurrent edits (see `RelativePosition`)
+//! - **Unicode positions**: Indices count chars, UTF-16 code units or
+//!   grapheme clusters (see `PositionUnit`)
 //!
 //! # Example
 //!
@@ -24,6 +31,49 @@
 //! assert_eq!(text.to_string(), "Hello");
 //! ```
 //!
+//! # Positions from JavaScript
+//!
+//! ```
+//! use synckit_core::crdt::text::{PositionUnit, Text};
+//!
+//! // JS string indices count UTF-16 code units
+//! let mut text = Text::with_unit(1, PositionUnit::Utf16);
+//! text.insert(0, "👋 Hi");
+//! assert_eq!(text.len(), 5);
+//!
+//! text.insert(3, "!"); // After "👋 "
+//! assert_eq!(text.to_string(), "👋 !Hi");
+//! ```
+//!
+//! # Formatting
+//!
+//! ```
+//! use serde_json::json;
+//! use synckit_core::crdt::text::{Expand, Text, TextRun};
+//!
+//! let mut text = Text::new(1);
+//! text.insert(0, "Hello World");
+//! text.format(0, 5, "bold", json!(true), Expand::Right);
+//!
+//! assert_eq!(text.to_runs(), vec![
+//!     TextRun::new("Hello").with_attribute("bold", json!(true)),
+//!     TextRun::new(" World"),
+//! ]);
+//! ```
+//!
+//! # Cursors
+//!
+//! ```
+//! use synckit_core::crdt::text::{Assoc, Text};
+//!
+//! let mut text = Text::new(1);
+//! text.insert(0, "Hello World");
+//! let cursor = text.relative_position(6, Assoc::After); // Before "World"
+//!
+//! text.insert(0, ">> ");
+//! assert_eq!(text.resolve_position(&cursor), Some(9));
+//! ```
+//!
 //! # Concurrent Editing
 //!
 //! ```
@@ -49,9 +99,18 @@
 
 mod id;
 mod item;
+mod mark;
+mod position;
+mod sequence;
 #[allow(clippy::module_inception)]
 mod text;
+mod unit;
+mod update;
 
 pub use id::ItemId;
 pub use item::Item;
+pub use mark::{Anchor, Attributes, Expand, Mark, MarkId, TextRun};
+pub use position::{Assoc, RelativePosition};
 pub use text::Text;
+pub use unit::PositionUnit;
+pub use update::{IdRange, StateVector, TextUpdate};
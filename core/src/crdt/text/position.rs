@@ -0,0 +1,89 @@
+//! Relative positions: cursors and selections that follow concurrent edits
+//!
+//! An index into a Text shifts whenever someone edits before it. A
+//! `RelativePosition` instead remembers the character next to the position
+//! and which side of it the position sticks to, so it resolves to the right
+//! index after any concurrent inserts and deletes, on every replica.
+//!
+//! A selection is a pair of relative positions (anchor and head).
+
+use super::id::ItemId;
+use serde::{Deserialize, Serialize};
+
+/// Which neighbouring character a position sticks to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Assoc {
+    /// Stick to the character before: text inserted at the position ends
+    /// up after it
+    Before,
+
+    /// Stick to the character after: text inserted at the position ends
+    /// up before it
+    #[default]
+    After,
+}
+
+/// A position in a Text anchored to a character
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RelativePosition {
+    /// Character the position sticks to (None for the start or end of the
+    /// text, depending on `assoc`)
+    pub item: Option<ItemId>,
+
+    /// Side of the position the character is on
+    pub assoc: Assoc,
+}
+
+impl RelativePosition {
+    /// Position just before a character
+    pub fn before(id: ItemId) -> Self {
+        Self {
+            item: Some(id),
+            assoc: Assoc::After,
+        }
+    }
+
+    /// Position just after a character
+    pub fn after(id: ItemId) -> Self {
+        Self {
+            item: Some(id),
+            assoc: Assoc::Before,
+        }
+    }
+
+    /// Start of the text
+    pub fn start() -> Self {
+        Self {
+            item: None,
+            assoc: Assoc::Before,
+        }
+    }
+
+    /// End of the text
+    pub fn end() -> Self {
+        Self {
+            item: None,
+            assoc: Assoc::After,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip() {
+        let position = RelativePosition::after(ItemId::new(3, 7));
+
+        let json = serde_json::to_value(position).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"item": {"client": 3, "clock": 7}, "assoc": "before"})
+        );
+
+        let back: RelativePosition = serde_json::from_value(json).unwrap();
+        assert_eq!(back, position);
+    }
+}
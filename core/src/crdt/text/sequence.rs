@@ -0,0 +1,557 @@
+//! Sequence: the document order of a Text's blocks
+//!
+//! An implicit treap (a randomized balanced binary tree ordered by
+//! position) whose nodes also sum up the blocks' lengths, in chars as well
+//! as in the other position units. That gives O(log n) expected time for:
+//! - Finding the block at a visible offset in any unit
+//! - Getting a block's index or the lengths before it
+//! - Inserting and removing blocks anywhere
+//!
+//! Nodes live in an arena and keep parent links, so a block can be located
+//! from its ID without searching.
+
+use super::id::ItemId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+/// Lengths of a block, or sums of them over several blocks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct Lengths {
+    /// Characters, including deleted ones
+    pub len: usize,
+
+    /// Visible characters
+    pub visible: usize,
+
+    /// UTF-16 code units of the visible characters
+    pub utf16: usize,
+
+    /// Grapheme clusters starting at a visible character of the block
+    ///
+    /// Depends on the text before the block, so the owner keeps it up to
+    /// date as neighbours change.
+    pub graphemes: usize,
+}
+
+impl Lengths {
+    /// Add another block's lengths
+    pub fn add(&mut self, other: &Lengths) {
+        self.len += other.len;
+        self.visible += other.visible;
+        self.utf16 += other.utf16;
+        self.graphemes += other.graphemes;
+    }
+}
+
+/// A block in the tree
+#[derive(Debug, Clone)]
+struct Node {
+    /// ID of the block's first character
+    id: ItemId,
+
+    /// Lengths of the block
+    lengths: Lengths,
+
+    /// Heap priority (higher is closer to the root)
+    priority: u64,
+
+    parent: Option<usize>,
+    left: Option<usize>,
+    right: Option<usize>,
+
+    /// Number of blocks in the subtree
+    count: usize,
+
+    /// Lengths summed over the subtree
+    sum: Lengths,
+}
+
+/// Block order of a Text, indexed by position and length
+#[derive(Debug, Clone, Default)]
+pub(super) struct Sequence {
+    /// Node arena
+    nodes: Vec<Node>,
+
+    /// Unused arena slots
+    free: Vec<usize>,
+
+    /// Root of the tree
+    root: Option<usize>,
+
+    /// Node of each block
+    handles: HashMap<ItemId, usize>,
+}
+
+impl Sequence {
+    /// Number of blocks
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.count(self.root)
+    }
+
+    /// Lengths summed over all blocks
+    pub fn lengths(&self) -> Lengths {
+        self.sum(self.root)
+    }
+
+    /// Lengths of a block
+    pub fn block_lengths(&self, id: ItemId) -> Lengths {
+        self.nodes[self.handle(id)].lengths
+    }
+
+    /// Get the first block
+    pub fn first(&self) -> Option<ItemId> {
+        let mut node = self.root?;
+        while let Some(left) = self.nodes[node].left {
+            node = left;
+        }
+        Some(self.nodes[node].id)
+    }
+
+    /// Get the block at an index
+    #[cfg(test)]
+    pub fn get(&self, index: usize) -> Option<ItemId> {
+        let mut node = self.root?;
+        let mut index = index;
+        loop {
+            let left_count = self.count(self.nodes[node].left);
+            if index < left_count {
+                node = self.nodes[node].left?;
+            } else if index == left_count {
+                return Some(self.nodes[node].id);
+            } else {
+                index -= left_count + 1;
+                node = self.nodes[node].right?;
+            }
+        }
+    }
+
+    /// Find the block holding a visible character offset
+    ///
+    /// Returns the block and the offset within it
+    pub fn find_visible(&self, offset: usize) -> Option<(ItemId, usize)> {
+        self.find(offset, |lengths| lengths.visible)
+    }
+
+    /// Find the block holding an offset counted by one of the lengths
+    ///
+    /// Returns the block and the offset within it
+    pub fn find(
+        &self,
+        offset: usize,
+        measure: impl Fn(&Lengths) -> usize,
+    ) -> Option<(ItemId, usize)> {
+        let mut node = self.root?;
+        let mut offset = offset;
+        loop {
+            let left = measure(&self.sum(self.nodes[node].left));
+            if offset < left {
+                node = self.nodes[node].left?;
+                continue;
+            }
+            offset -= left;
+
+            let own = measure(&self.nodes[node].lengths);
+            if offset < own {
+                return Some((self.nodes[node].id, offset));
+            }
+            offset -= own;
+            node = self.nodes[node].right?;
+        }
+    }
+
+    /// Get the index of a block
+    pub fn index_of(&self, id: ItemId) -> usize {
+        let mut node = self.handle(id);
+        let mut index = self.count(self.nodes[node].left);
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                index += self.count(self.nodes[parent].left) + 1;
+            }
+            node = parent;
+        }
+        index
+    }
+
+    /// Lengths summed over the blocks before a block
+    pub fn before(&self, id: ItemId) -> Lengths {
+        let mut node = self.handle(id);
+        let mut total = self.sum(self.nodes[node].left);
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                total.add(&self.sum(self.nodes[parent].left));
+                total.add(&self.nodes[parent].lengths);
+            }
+            node = parent;
+        }
+        total
+    }
+
+    /// Number of visible characters before a block
+    pub fn visible_before(&self, id: ItemId) -> usize {
+        self.before(id).visible
+    }
+
+    /// Get the block after a block
+    pub fn next(&self, id: ItemId) -> Option<ItemId> {
+        let mut node = self.handle(id);
+        if let Some(mut right) = self.nodes[node].right {
+            while let Some(left) = self.nodes[right].left {
+                right = left;
+            }
+            return Some(self.nodes[right].id);
+        }
+
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].left == Some(node) {
+                return Some(self.nodes[parent].id);
+            }
+            node = parent;
+        }
+        None
+    }
+
+    /// Get the block before a block
+    pub fn prev(&self, id: ItemId) -> Option<ItemId> {
+        let mut node = self.handle(id);
+        if let Some(mut left) = self.nodes[node].left {
+            while let Some(right) = self.nodes[left].right {
+                left = right;
+            }
+            return Some(self.nodes[left].id);
+        }
+
+        while let Some(parent) = self.nodes[node].parent {
+            if self.nodes[parent].right == Some(node) {
+                return Some(self.nodes[parent].id);
+            }
+            node = parent;
+        }
+        None
+    }
+
+    /// Iterate over the blocks in order
+    pub fn iter(&self) -> impl Iterator<Item = ItemId> + '_ {
+        std::iter::successors(self.first(), |&id| self.next(id))
+    }
+
+    /// Iterate over the blocks in order, starting at a block
+    pub fn iter_from(&self, id: ItemId) -> impl Iterator<Item = ItemId> + '_ {
+        std::iter::successors(Some(id), |&id| self.next(id))
+    }
+
+    /// Insert a block at an index
+    pub fn insert(&mut self, index: usize, id: ItemId, lengths: Lengths) {
+        let node = self.alloc(id, lengths);
+        self.handles.insert(id, node);
+
+        let (left, right) = self.split(self.root, index);
+        let merged = self.merge(left, Some(node));
+        self.root = self.merge(merged, right);
+        self.set_root_parent();
+    }
+
+    /// Remove a block
+    pub fn remove(&mut self, id: ItemId) {
+        let index = self.index_of(id);
+        let node = self.handles.remove(&id).expect("Block must be in sequence");
+
+        let (left, rest) = self.split(self.root, index);
+        let (_, right) = self.split(rest, 1);
+        self.root = self.merge(left, right);
+        self.set_root_parent();
+        self.free.push(node);
+    }
+
+    /// Update a block's lengths
+    pub fn set_lengths(&mut self, id: ItemId, lengths: Lengths) {
+        let mut node = self.handle(id);
+        self.nodes[node].lengths = lengths;
+
+        loop {
+            self.update(node);
+            match self.nodes[node].parent {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+    }
+
+    /// Get the node of a block
+    fn handle(&self, id: ItemId) -> usize {
+        *self.handles.get(&id).expect("Block must be in sequence")
+    }
+
+    /// Number of blocks in a subtree
+    fn count(&self, node: Option<usize>) -> usize {
+        node.map_or(0, |node| self.nodes[node].count)
+    }
+
+    /// Lengths summed over a subtree
+    fn sum(&self, node: Option<usize>) -> Lengths {
+        node.map_or(Lengths::default(), |node| self.nodes[node].sum)
+    }
+
+    /// Create a detached node
+    fn alloc(&mut self, id: ItemId, lengths: Lengths) -> usize {
+        let node = Node {
+            id,
+            lengths,
+            priority: priority(id),
+            parent: None,
+            left: None,
+            right: None,
+            count: 1,
+            sum: lengths,
+        };
+
+        match self.free.pop() {
+            Some(slot) => {
+                self.nodes[slot] = node;
+                slot
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Recompute a node's subtree sums from its children
+    fn update(&mut self, node: usize) {
+        let (mut count, mut sum) = (1, self.nodes[node].lengths);
+        for child in [self.nodes[node].left, self.nodes[node].right]
+            .into_iter()
+            .flatten()
+        {
+            count += self.nodes[child].count;
+            sum.add(&self.nodes[child].sum);
+        }
+
+        let node = &mut self.nodes[node];
+        node.count = count;
+        node.sum = sum;
+    }
+
+    fn set_left(&mut self, node: usize, child: Option<usize>) {
+        self.nodes[node].left = child;
+        if let Some(child) = child {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn set_right(&mut self, node: usize, child: Option<usize>) {
+        self.nodes[node].right = child;
+        if let Some(child) = child {
+            self.nodes[child].parent = Some(node);
+        }
+    }
+
+    fn set_root_parent(&mut self) {
+        if let Some(root) = self.root {
+            self.nodes[root].parent = None;
+        }
+    }
+
+    /// Split a subtree into its first `k` blocks and the rest
+    fn split(&mut self, tree: Option<usize>, k: usize) -> (Option<usize>, Option<usize>) {
+        let Some(node) = tree else {
+            return (None, None);
+        };
+
+        let left_count = self.count(self.nodes[node].left);
+        if k <= left_count {
+            let (first, rest) = self.split(self.nodes[node].left, k);
+            self.set_left(node, rest);
+            self.update(node);
+            if let Some(first) = first {
+                self.nodes[first].parent = None;
+            }
+            (first, Some(node))
+        } else {
+            let (first, rest) = self.split(self.nodes[node].right, k - left_count - 1);
+            self.set_right(node, first);
+            self.update(node);
+            if let Some(rest) = rest {
+                self.nodes[rest].parent = None;
+            }
+            (Some(node), rest)
+        }
+    }
+
+    /// Concatenate two subtrees
+    fn merge(&mut self, first: Option<usize>, second: Option<usize>) -> Option<usize> {
+        match (first, second) {
+            (None, tree) | (tree, None) => tree,
+            (Some(a), Some(b)) => {
+                if self.nodes[a].priority > self.nodes[b].priority {
+                    let right = self.merge(self.nodes[a].right, Some(b));
+                    self.set_right(a, right);
+                    self.update(a);
+                    Some(a)
+                } else {
+                    let left = self.merge(Some(a), self.nodes[b].left);
+                    self.set_left(b, left);
+                    self.update(b);
+                    Some(b)
+                }
+            }
+        }
+    }
+}
+
+/// Deterministic pseudo-random priority for a block (SplitMix64)
+fn priority(id: ItemId) -> u64 {
+    let mut x = id
+        .client
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        .wrapping_add(id.clock);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// Serialized form of a block in the sequence
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    id: ItemId,
+    #[serde(flatten)]
+    lengths: Lengths,
+}
+
+impl Serialize for Sequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter().map(|id| Entry {
+            id,
+            lengths: self.block_lengths(id),
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for Sequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = Vec::<Entry>::deserialize(deserializer)?;
+        let mut sequence = Sequence::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            sequence.insert(index, entry.id, entry.lengths);
+        }
+        Ok(sequence)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id(clock: u64) -> ItemId {
+        ItemId::new(1, clock)
+    }
+
+    fn lengths(len: usize, visible: usize, utf16: usize, graphemes: usize) -> Lengths {
+        Lengths {
+            len,
+            visible,
+            utf16,
+            graphemes,
+        }
+    }
+
+    /// Reference model: (id, lengths) in order
+    fn check(sequence: &Sequence, model: &[(ItemId, Lengths)]) {
+        assert_eq!(sequence.len(), model.len());
+        assert_eq!(
+            sequence.iter().collect::<Vec<_>>(),
+            model.iter().map(|e| e.0).collect::<Vec<_>>()
+        );
+
+        let mut total = Lengths::default();
+        for (index, &(block, own)) in model.iter().enumerate() {
+            assert_eq!(sequence.get(index), Some(block));
+            assert_eq!(sequence.index_of(block), index);
+            assert_eq!(sequence.before(block), total);
+            assert_eq!(sequence.block_lengths(block), own);
+            for offset in 0..own.visible {
+                assert_eq!(
+                    sequence.find_visible(total.visible + offset),
+                    Some((block, offset))
+                );
+            }
+            for offset in 0..own.utf16 {
+                assert_eq!(
+                    sequence.find(total.utf16 + offset, |l| l.utf16),
+                    Some((block, offset))
+                );
+            }
+            assert_eq!(
+                sequence.prev(block),
+                index.checked_sub(1).map(|i| model[i].0)
+            );
+            assert_eq!(sequence.next(block), model.get(index + 1).map(|e| e.0));
+            total.add(&own);
+        }
+        assert_eq!(sequence.lengths(), total);
+        assert_eq!(sequence.find_visible(total.visible), None);
+        assert_eq!(sequence.find(total.utf16, |l| l.utf16), None);
+    }
+
+    #[test]
+    fn test_matches_vec_model() {
+        let mut sequence = Sequence::default();
+        let mut model = Vec::new();
+
+        // Deterministic mix of inserts at varying positions
+        let mut seed = 7u64;
+        for clock in 0..200 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let index = (seed >> 33) as usize % (model.len() + 1);
+            let len = 1 + (seed % 5) as usize;
+            let visible = if seed.is_multiple_of(3) { 0 } else { len };
+            let own = lengths(len, visible, visible * 2, visible / 2);
+
+            sequence.insert(index, id(clock), own);
+            model.insert(index, (id(clock), own));
+        }
+        check(&sequence, &model);
+
+        // Remove every third block and change some lengths
+        for clock in (0..200).step_by(3) {
+            sequence.remove(id(clock));
+            model.retain(|e| e.0 != id(clock));
+        }
+        for clock in (1..200u64)
+            .step_by(7)
+            .filter(|clock| !clock.is_multiple_of(3))
+        {
+            sequence.set_lengths(id(clock), lengths(9, 4, 5, 3));
+            let entry = model.iter_mut().find(|e| e.0 == id(clock)).unwrap();
+            entry.1 = lengths(9, 4, 5, 3);
+        }
+        check(&sequence, &model);
+
+        // Slots are reused
+        sequence.insert(0, id(1000), lengths(1, 1, 1, 1));
+        model.insert(0, (id(1000), lengths(1, 1, 1, 1)));
+        check(&sequence, &model);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut sequence = Sequence::default();
+        sequence.insert(0, id(0), lengths(3, 3, 4, 2));
+        sequence.insert(1, id(3), lengths(2, 0, 0, 0));
+        sequence.insert(1, id(5), lengths(1, 1, 1, 1));
+
+        let json = serde_json::to_string(&sequence).unwrap();
+        let back: Sequence = serde_json::from_str(&json).unwrap();
+        check(
+            &back,
+            &[
+                (id(0), lengths(3, 3, 4, 2)),
+                (id(5), lengths(1, 1, 1, 1)),
+                (id(3), lengths(2, 0, 0, 0)),
+            ],
+        );
+    }
+}
@@ -5,14 +5,46 @@
 //!
 //! Key features:
 //! - Block-based structure for efficiency
-//! - Sequential insertion optimization (O(1) for typical typing)
+//! - Balanced position index: O(log n) to find, insert or delete at an index
 //! - Deterministic conflict resolution
 //! - Tombstones for deletion handling
+//!
+//! Every character has its own `ItemId`; each client numbers its characters
+//! 0, 1, 2, ... A block stores a run of characters with consecutive IDs and
+//! is addressed by the ID of its first character. Origins always point at
+//! single characters, so a block is split whenever an edit or a remote
+//! origin lands inside it, and adjacent blocks are merged back when they
+//! form one run again.
+//!
+//! Block order lives in a `Sequence`, a balanced tree that also sums up
+//! block lengths, so positions map to blocks (and back) in O(log n).
+//!
+//! Positions passed to and returned from index-based APIs are counted in
+//! the text's `PositionUnit` and converted to char offsets internally.
+//! The sequence keeps UTF-16 and grapheme counts next to the char counts,
+//! so conversions only look at the block holding the position. A block's
+//! grapheme count depends on the text before it, so it is recounted when
+//! the block or, for joining characters, its predecessors change.
+//!
+//! Formatting is stored as marks anchored to characters (see `mark`) and
+//! resolved into runs of uniformly formatted text on export.
+//!
+//! Replicas can sync incrementally: local edits are recorded as a
+//! `TextUpdate` (see `take_update`), and `update_since` encodes whatever a
+//! peer's `StateVector` is missing. Remote updates may arrive out of order;
+//! blocks whose origins are unknown are buffered until they can be placed.
 
 use super::id::ItemId;
 use super::item::Item;
+use super::mark::{Anchor, Attributes, Expand, Mark, MarkId, TextRun};
+use super::position::{Assoc, RelativePosition};
+use super::sequence::{Lengths, Sequence};
+use super::unit::PositionUnit;
+use super::update::{IdRange, StateVector, TextUpdate};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use unicode_segmentation::UnicodeSegmentation;
 
 /// Text CRDT document
 ///
@@ -23,27 +55,86 @@ pub struct Text {
     /// Client ID for this replica
     client_id: u64,
 
-    /// Lamport clock for generating unique IDs
+    /// Clock for this client's next character
     clock: u64,
 
-    /// Store of all items by their ID
+    /// Store of all blocks by the ID of their first character
     items: HashMap<ItemId, Item>,
 
-    /// Ordered list of item IDs (the actual sequence)
-    sequence: Vec<ItemId>,
+    /// Block IDs in document order, indexed by position
+    sequence: Sequence,
+
+    /// Block start clocks per client (to find the block holding a character)
+    starts: HashMap<u64, BTreeSet<u64>>,
+
+    /// Unit for positions in index-based APIs (replica-local)
+    #[serde(default)]
+    unit: PositionUnit,
+
+    /// Remote blocks waiting for their origins or earlier characters
+    #[serde(default)]
+    pending_inserts: Vec<Item>,
+
+    /// Remote deletions of characters we haven't received yet
+    #[serde(default)]
+    pending_deletes: Vec<IdRange>,
+
+    /// Formatting marks, sorted by ID
+    #[serde(default)]
+    marks: Vec<Mark>,
+
+    /// Lamport clock for new marks
+    #[serde(default)]
+    mark_clock: u64,
+
+    /// Remote marks attached to characters we haven't received yet
+    #[serde(default)]
+    pending_marks: Vec<Mark>,
+
+    /// Local edits not yet taken with `take_update`
+    #[serde(skip)]
+    local_update: TextUpdate,
+
+    /// Characters whose blocks changed since the last `merge_blocks`
+    #[serde(skip)]
+    dirty: Vec<ItemId>,
 }
 
 impl Text {
     /// Create a new text document
     pub fn new(client_id: u64) -> Self {
+        Self::with_unit(client_id, PositionUnit::default())
+    }
+
+    /// Create a new text document counting positions in the given unit
+    pub fn with_unit(client_id: u64, unit: PositionUnit) -> Self {
         Self {
             client_id,
             clock: 0,
             items: HashMap::new(),
-            sequence: Vec::new(),
+            sequence: Sequence::default(),
+            starts: HashMap::new(),
+            unit,
+            pending_inserts: Vec::new(),
+            pending_deletes: Vec::new(),
+            marks: Vec::new(),
+            mark_clock: 0,
+            pending_marks: Vec::new(),
+            local_update: TextUpdate::new(),
+            dirty: Vec::new(),
         }
     }
 
+    /// Get the unit for positions in index-based APIs
+    pub fn unit(&self) -> PositionUnit {
+        self.unit
+    }
+
+    /// Change the unit for positions in index-based APIs
+    pub fn set_unit(&mut self, unit: PositionUnit) {
+        self.unit = unit;
+    }
+
     /// Get the current client ID
     pub fn client_id(&self) -> u64 {
         self.client_id
@@ -54,300 +145,1014 @@ impl Text {
         self.clock
     }
 
-    /// Generate next item ID
-    fn next_id(&mut self) -> ItemId {
-        let id = ItemId::new(self.client_id, self.clock);
-        self.clock += 1;
-        id
-    }
-
     /// Insert text at the given position
     ///
-    /// Returns the IDs of created items
+    /// Returns the IDs of the inserted characters
     pub fn insert(&mut self, position: usize, text: &str) -> Vec<ItemId> {
-        let mut created_ids = Vec::new();
-
-        // Find left and right origins
-        let (left_origin, right_origin) = self.get_origins_at_position(position);
-
-        // Create items for each character
-        // All characters from this insert share the same left/right origins
-        for ch in text.chars() {
-            let id = self.next_id();
-            let item = Item::new_char(id, ch, left_origin, right_origin);
+        let position = self.to_char_offset(position, false);
+        self.insert_chars(position, text)
+    }
 
-            self.items.insert(id, item);
-            created_ids.push(id);
+    /// Insert text at a char offset
+    fn insert_chars(&mut self, position: usize, text: &str) -> Vec<ItemId> {
+        let length = text.chars().count() as u64;
+        if length == 0 {
+            return Vec::new();
         }
 
-        // Integrate the new items into the sequence
-        self.integrate_items(&created_ids);
+        // Left origin is the visible character before the position, right
+        // origin whatever directly follows it (even if deleted)
+        let left = position
+            .checked_sub(1)
+            .and_then(|offset| self.visible_char_id(offset));
+        let right = match left {
+            Some(left) => {
+                self.split_at(ItemId::new(left.client, left.clock + 1));
+                self.sequence
+                    .next(self.block_of(left).expect("Left origin must exist"))
+            }
+            None => self.sequence.first(),
+        };
+
+        let id = ItemId::new(self.client_id, self.clock);
+        self.clock += length;
 
-        // Try to merge adjacent blocks from same client
+        let item = Item::new(id, text.to_string(), left, right);
+        self.local_update.push_insert(item.clone());
+        self.integrate(item);
         self.merge_blocks();
 
-        created_ids
+        (0..length)
+            .map(|offset| ItemId::new(id.client, id.clock + offset))
+            .collect()
     }
 
     /// Delete text at the given position
     ///
-    /// Marks items as deleted (tombstones) rather than removing them
+    /// Marks characters as deleted (tombstones) rather than removing them.
+    /// Blocks that are only partly covered are split first, so exactly the
+    /// requested characters are deleted.
+    ///
+    /// A range boundary inside a UTF-16 surrogate pair is widened to cover
+    /// the whole character.
+    ///
+    /// Returns the IDs of the deleted characters
     pub fn delete(&mut self, position: usize, length: usize) -> Vec<ItemId> {
-        let mut deleted_ids = Vec::new();
+        let end = self.to_char_offset(position.saturating_add(length), true);
+        let position = self.to_char_offset(position, false);
+
+        // Collect the character ranges first; deleting splits blocks
+        let mut ranges = Vec::new();
+        if let Some((first, offset)) = self.sequence.find_visible(position) {
+            let mut visible_pos = position - offset;
+            for id in self.sequence.iter_from(first) {
+                if visible_pos >= end {
+                    break;
+                }
 
-        let mut visible_pos = 0;
-        for &id in &self.sequence {
-            if let Some(item) = self.items.get_mut(&id) {
+                let item = &self.items[&id];
                 if item.deleted {
                     continue;
                 }
 
-                let item_len = item.content.len();
-
-                // Check if this item overlaps with deletion range
-                if visible_pos < position + length && visible_pos + item_len > position {
-                    item.delete();
-                    deleted_ids.push(id);
+                let item_len = item.len();
+                let from = position.max(visible_pos);
+                let to = end.min(visible_pos + item_len);
+                if from < to {
+                    ranges.push((item.id_at(from - visible_pos), to - from));
                 }
 
                 visible_pos += item_len;
+            }
+        }
 
-                if visible_pos >= position + length {
-                    break;
+        let mut deleted_ids = Vec::new();
+        for (start, length) in ranges {
+            self.delete_range(start, length);
+            self.local_update
+                .push_delete(IdRange::new(start, length as u64));
+            deleted_ids
+                .extend((0..length as u64).map(|i| ItemId::new(start.client, start.clock + i)));
+        }
+
+        self.merge_blocks();
+
+        deleted_ids
+    }
+
+    /// Get a relative position for an index
+    ///
+    /// The position sticks to the character after the index (`Assoc::After`)
+    /// or before it (`Assoc::Before`).
+    pub fn relative_position(&self, index: usize, assoc: Assoc) -> RelativePosition {
+        let offset = self.to_char_offset(index, false);
+        let item = match assoc {
+            Assoc::After => self.visible_char_id(offset),
+            Assoc::Before => offset
+                .checked_sub(1)
+                .and_then(|offset| self.visible_char_id(offset)),
+        };
+        RelativePosition { item, assoc }
+    }
+
+    /// Resolve a relative position to the current index
+    ///
+    /// If the character it sticks to was deleted, resolves to where the
+    /// character used to be. Returns None if the character hasn't been
+    /// received yet.
+    pub fn resolve_position(&self, position: &RelativePosition) -> Option<usize> {
+        let offset = match (position.item, position.assoc) {
+            (None, Assoc::Before) => 0,
+            (None, Assoc::After) => self.char_len(),
+            (Some(id), assoc) => {
+                let block = self.block_of(id)?;
+                let offset = self.char_offset_of(block) + self.visible_len_before(block, id);
+                let visible = !self.items[&block].deleted;
+                match assoc {
+                    Assoc::Before if visible => offset + 1,
+                    _ => offset,
                 }
             }
+        };
+
+        Some(self.position_of(offset))
+    }
+
+    /// Delete characters by ID
+    ///
+    /// Characters that are already deleted or unknown are skipped.
+    ///
+    /// Returns the IDs of the characters deleted now
+    pub fn delete_ids(&mut self, ids: &[ItemId]) -> Vec<ItemId> {
+        let deleted_ids: Vec<ItemId> = ids
+            .iter()
+            .copied()
+            .filter(|&id| {
+                self.block_of(id)
+                    .is_some_and(|block| !self.items[&block].deleted)
+            })
+            .collect();
+
+        let mut runs: Vec<IdRange> = Vec::new();
+        for &id in &deleted_ids {
+            match runs.last_mut() {
+                Some(run) if run.start.client == id.client && run.end_clock() == id.clock => {
+                    run.len += 1;
+                }
+                _ => runs.push(IdRange::new(id, 1)),
+            }
+        }
+
+        for run in runs {
+            self.delete_range(run.start, run.len as usize);
+            self.local_update.push_delete(run);
         }
+        self.merge_blocks();
 
         deleted_ids
     }
 
-    /// Get the length of the text (excluding deleted items)
-    pub fn len(&self) -> usize {
-        self.sequence
+    /// Insert copies of characters where the originals are (or were)
+    ///
+    /// Used to bring back deleted text: each copy is placed at the current
+    /// position of its original, so concurrent edits around it are kept.
+    /// Unknown IDs and characters whose content was garbage collected are
+    /// skipped, as are repeated IDs.
+    ///
+    /// Returns (original, copy) for each character brought back, in
+    /// document order
+    pub fn reinsert(&mut self, ids: &[ItemId]) -> Vec<(ItemId, ItemId)> {
+        // Index in the document, char offset and content of each original
+        let mut originals: Vec<(usize, usize, ItemId, char)> = ids
             .iter()
-            .filter_map(|id| self.items.get(id))
-            .filter(|item| !item.deleted)
-            .map(|item| item.content.len())
-            .sum()
+            .filter_map(|&id| {
+                let block = self.block_of(id)?;
+                let item = &self.items[&block];
+                if item.is_collected() {
+                    return None;
+                }
+                let ch = item
+                    .content
+                    .chars()
+                    .nth((id.clock - block.clock) as usize)
+                    .expect("Character is in its block");
+                let offset = self.char_offset_of(block) + self.visible_len_before(block, id);
+                Some((self.char_index(id), offset, id, ch))
+            })
+            .collect();
+        originals.sort_unstable_by_key(|&(index, ..)| index);
+        originals.dedup_by_key(|&mut (index, ..)| index);
+
+        // Group into runs that go to the same offset
+        let mut runs: Vec<(usize, String, Vec<ItemId>)> = Vec::new();
+        for (_, offset, id, ch) in originals {
+            match runs.last_mut() {
+                Some((run_offset, content, run_ids)) if *run_offset == offset => {
+                    content.push(ch);
+                    run_ids.push(id);
+                }
+                _ => runs.push((offset, ch.to_string(), vec![id])),
+            }
+        }
+
+        // Insert from the back so earlier offsets stay valid
+        let mut pairs: Vec<Vec<(ItemId, ItemId)>> = runs
+            .into_iter()
+            .rev()
+            .map(|(offset, content, run_ids)| {
+                let copies = self.insert_chars(offset, &content);
+                run_ids.into_iter().zip(copies).collect()
+            })
+            .collect();
+        pairs.reverse();
+        pairs.into_iter().flatten().collect()
     }
 
-    /// Check if the text is empty
-    pub fn is_empty(&self) -> bool {
-        self.len() == 0
+    /// Number of visible chars before a block
+    fn char_offset_of(&self, block: ItemId) -> usize {
+        self.sequence.visible_before(block)
+    }
+
+    /// Number of visible chars in a block before one of its characters
+    fn visible_len_before(&self, block: ItemId, id: ItemId) -> usize {
+        if self.items[&block].deleted {
+            0
+        } else {
+            (id.clock - block.clock) as usize
+        }
     }
 
-    /// Get left and right origins for insertion at position
-    fn get_origins_at_position(&self, position: usize) -> (Option<ItemId>, Option<ItemId>) {
-        if position == 0 {
-            // Insert at beginning
-            return (None, self.sequence.first().copied());
+    /// Set an attribute on a range of text
+    ///
+    /// `expand` decides whether text typed at the edges of the range later
+    /// gets the attribute too. A `null` value clears the attribute.
+    ///
+    /// Returns the new mark's ID, or None if the range is empty
+    pub fn format(
+        &mut self,
+        position: usize,
+        length: usize,
+        name: &str,
+        value: JsonValue,
+        expand: Expand,
+    ) -> Option<MarkId> {
+        let end = self.to_char_offset(position.saturating_add(length), true);
+        let position = self.to_char_offset(position, false);
+        if position >= end {
+            return None;
         }
 
-        let mut visible_pos = 0;
+        let start = if expand.left() {
+            position
+                .checked_sub(1)
+                .and_then(|offset| self.visible_char_id(offset))
+                .map_or(Anchor::Start, Anchor::After)
+        } else {
+            Anchor::Before(self.visible_char_id(position)?)
+        };
+        let end = if expand.right() {
+            self.visible_char_id(end)
+                .map_or(Anchor::End, Anchor::Before)
+        } else {
+            Anchor::After(self.visible_char_id(end - 1)?)
+        };
 
-        for (i, &id) in self.sequence.iter().enumerate() {
-            if let Some(item) = self.items.get(&id) {
-                if item.deleted {
-                    continue;
-                }
+        self.mark_clock += 1;
+        let mark = Mark {
+            id: MarkId::new(self.mark_clock, self.client_id),
+            name: name.to_string(),
+            value,
+            start,
+            end,
+        };
+        let id = mark.id;
+        self.local_update.marks.push(mark.clone());
+        self.add_mark(mark);
 
-                let item_len = item.content.len();
-
-                if visible_pos + item_len >= position {
-                    // Found the insertion point - left origin is this item
-                    let left_origin = Some(id);
-
-                    // Find right origin (next visible item after current)
-                    let mut right_origin = None;
-                    for &next_id in &self.sequence[i + 1..] {
-                        if let Some(next_item) = self.items.get(&next_id) {
-                            if !next_item.deleted {
-                                right_origin = Some(next_id);
-                                break;
-                            }
-                        }
-                    }
+        Some(id)
+    }
 
-                    return (left_origin, right_origin);
+    /// Clear an attribute on a range of text
+    ///
+    /// Same as `format` with a `null` value.
+    pub fn unformat(
+        &mut self,
+        position: usize,
+        length: usize,
+        name: &str,
+        expand: Expand,
+    ) -> Option<MarkId> {
+        self.format(position, length, name, JsonValue::Null, expand)
+    }
+
+    /// Get all formatting marks, sorted by ID
+    pub fn marks(&self) -> &[Mark] {
+        &self.marks
+    }
+
+    /// Export the visible text as runs of uniformly formatted text
+    ///
+    /// Adjacent characters with the same attributes share a run; cleared
+    /// attributes are left out.
+    pub fn to_runs(&self) -> Vec<TextRun> {
+        let spans = self.mark_spans();
+        let boundaries: BTreeSet<usize> = spans.iter().flat_map(|&(lo, hi, _)| [lo, hi]).collect();
+
+        let mut runs: Vec<TextRun> = Vec::new();
+        let mut attributes = Attributes::new();
+        let mut next_change = 0;
+        let mut index = 0;
+        for id in self.sequence.iter() {
+            let item = &self.items[&id];
+            if item.deleted {
+                index += item.len();
+                continue;
+            }
+
+            for ch in item.content.chars() {
+                if index >= next_change {
+                    attributes = resolve_attributes(&spans, index);
+                    next_change = boundaries
+                        .range(index + 1..)
+                        .next()
+                        .copied()
+                        .unwrap_or(usize::MAX);
                 }
 
-                visible_pos += item_len;
+                match runs.last_mut() {
+                    Some(run) if run.attributes == attributes => run.insert.push(ch),
+                    _ => runs.push(TextRun {
+                        insert: ch.to_string(),
+                        attributes: attributes.clone(),
+                    }),
+                }
+                index += 1;
             }
         }
 
-        // Insert at end
-        (self.sequence.last().copied(), None)
+        runs
     }
 
-    /// Integrate items into the sequence using YATA algorithm
+    /// Insert delta-style runs of formatted text at a position
     ///
-    /// This is the core conflict resolution algorithm. It finds the correct
-    /// position for new items based on their left and right origins.
-    fn integrate_items(&mut self, item_ids: &[ItemId]) {
-        for &item_id in item_ids {
-            self.integrate_item(item_id);
+    /// Each run ends up with exactly its own attributes: any it would pick
+    /// up from an expanding neighbour are cleared. New marks use `expand`.
+    ///
+    /// Returns the IDs of the inserted characters
+    pub fn insert_runs(
+        &mut self,
+        position: usize,
+        runs: &[TextRun],
+        expand: Expand,
+    ) -> Vec<ItemId> {
+        let mut position = position.min(self.len());
+        let mut ids = Vec::new();
+
+        for run in runs {
+            let run_ids = self.insert(position, &run.insert);
+            let Some(&first) = run_ids.first() else {
+                continue;
+            };
+            let length = self.unit.len(&run.insert);
+
+            let current = self.attributes_of(first);
+            let wanted: Attributes = run
+                .attributes
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+
+            for name in current.keys().filter(|name| !wanted.contains_key(*name)) {
+                self.format(position, length, name, JsonValue::Null, expand);
+            }
+            for (name, value) in &wanted {
+                if current.get(name) != Some(value) {
+                    self.format(position, length, name, value.clone(), expand);
+                }
+            }
+
+            position += length;
+            ids.extend(run_ids);
         }
+
+        ids
     }
 
-    /// Integrate a single item into the sequence
-    fn integrate_item(&mut self, item_id: ItemId) {
-        let item = self.items.get(&item_id).expect("Item must exist");
-        let left = item.left;
-        let right = item.right;
+    /// Get the attributes of a character
+    fn attributes_of(&self, id: ItemId) -> Attributes {
+        resolve_attributes(&self.mark_spans(), self.char_index(id))
+    }
 
-        // Find insertion position using YATA algorithm
-        let insert_pos = if let Some(left_id) = left {
-            // Find left origin in sequence
-            if let Some(left_pos) = self.sequence.iter().position(|&id| id == left_id) {
-                // Scan forward from left origin
-                let mut pos = left_pos + 1;
+    /// Index (counting deleted characters) of a character
+    fn char_index(&self, id: ItemId) -> usize {
+        let block = self.block_of(id).expect("Character must exist");
+        self.sequence.before(block).len + (id.clock - block.clock) as usize
+    }
 
-                while pos < self.sequence.len() {
-                    let current_id = self.sequence[pos];
+    /// Character index range covered by each non-empty mark, in ID order
+    fn mark_spans(&self) -> Vec<(usize, usize, &Mark)> {
+        let total = self.sequence.lengths().len;
 
-                    // Stop if we reach the right origin
-                    if Some(current_id) == right {
-                        break;
-                    }
+        let gap = |anchor: Anchor| match anchor {
+            Anchor::Start => 0,
+            Anchor::Before(id) => self.char_index(id),
+            Anchor::After(id) => self.char_index(id) + 1,
+            Anchor::End => total,
+        };
 
-                    if let Some(current_item) = self.items.get(&current_id) {
-                        // YATA conflict resolution:
-                        // - Items with same left origin: order by ID (deterministic)
-                        // - Items with different left but originated between our left and right:
-                        //   continue scanning to find the right position
-
-                        if current_item.left == left {
-                            // Same left origin: compare IDs
-                            if item_id < current_id {
-                                // Our item should come before this one
-                                break;
-                            } else {
-                                // Continue scanning
-                                pos += 1;
-                            }
-                        } else {
-                            // Different left origin
-                            // Check if current item originated between our left and right
-                            if right.is_some() {
-                                // If current's left comes after our left, we go before it
-                                if let Some(current_left) = current_item.left {
-                                    if current_left > left_id {
-                                        break;
-                                    }
-                                }
-                            }
-                            pos += 1;
-                        }
-                    } else {
-                        break;
+        self.marks
+            .iter()
+            .map(|mark| (gap(mark.start), gap(mark.end), mark))
+            .filter(|&(lo, hi, _)| lo < hi)
+            .collect()
+    }
+
+    /// Add an integrated mark, ignoring duplicates
+    fn add_mark(&mut self, mark: Mark) {
+        self.mark_clock = self.mark_clock.max(mark.id.lamport);
+        if let Err(index) = self.marks.binary_search_by_key(&mark.id, |m| m.id) {
+            self.marks.insert(index, mark);
+        }
+    }
+
+    /// Get the length of the text (excluding deleted items)
+    ///
+    /// Counted in the text's position unit.
+    pub fn len(&self) -> usize {
+        let lengths = self.sequence.lengths();
+        match self.unit {
+            PositionUnit::Char => lengths.visible,
+            PositionUnit::Utf16 => lengths.utf16,
+            PositionUnit::Grapheme => lengths.graphemes,
+        }
+    }
+
+    /// Check if the text is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of visible chars
+    fn char_len(&self) -> usize {
+        self.sequence.lengths().visible
+    }
+
+    /// Convert a position in the text's unit to a char offset
+    fn to_char_offset(&self, position: usize, round_up: bool) -> usize {
+        let found = match self.unit {
+            PositionUnit::Char => return position.min(self.char_len()),
+            PositionUnit::Utf16 => self.sequence.find(position, |l| l.utf16),
+            PositionUnit::Grapheme => self.sequence.find(position, |l| l.graphemes),
+        };
+        let Some((block, inner)) = found else {
+            return self.char_len();
+        };
+
+        let before = self.sequence.visible_before(block);
+        match self.unit {
+            PositionUnit::Grapheme => before + self.grapheme_starts(block)[inner],
+            unit => before + unit.to_char_offset(&self.items[&block].content, inner, round_up),
+        }
+    }
+
+    /// Convert a char offset to a position in the text's unit
+    fn position_of(&self, offset: usize) -> usize {
+        if self.unit == PositionUnit::Char {
+            return offset.min(self.char_len());
+        }
+        let Some((block, inner)) = self.sequence.find_visible(offset) else {
+            return self.len();
+        };
+
+        let before = self.sequence.before(block);
+        match self.unit {
+            PositionUnit::Grapheme => {
+                // Every cluster started so far has ended, except the one
+                // holding the offset (the text always starts a cluster)
+                let starts = self.grapheme_starts(block);
+                before.graphemes + starts.partition_point(|&start| start <= inner) - 1
+            }
+            unit => before.utf16 + unit.from_char_offset(&self.items[&block].content, inner),
+        }
+    }
+
+    /// Char offsets within a visible block where grapheme clusters start
+    fn grapheme_starts(&self, block: ItemId) -> Vec<usize> {
+        // Boundaries only look back past joining characters, so the
+        // context ends with the first character that doesn't join
+        let mut context = Vec::new();
+        let mut prev = self.sequence.prev(block);
+        'blocks: while let Some(id) = prev {
+            let item = &self.items[&id];
+            if !item.deleted {
+                for ch in item.content.chars().rev() {
+                    context.push(ch);
+                    if !joins_context(ch) {
+                        break 'blocks;
                     }
                 }
+            }
+            prev = self.sequence.prev(id);
+        }
 
-                pos
-            } else {
-                // Left origin not found, append
-                self.sequence.len()
+        let prefix: String = context.into_iter().rev().collect();
+        let text = prefix.clone() + &self.items[&block].content;
+        let mut chars = 0;
+        let mut bytes = prefix.len();
+        let mut starts = Vec::new();
+        for (index, _) in text.grapheme_indices(true) {
+            if index < prefix.len() {
+                continue;
             }
-        } else {
-            // No left origin, insert at beginning
-            // But scan to find items that also have no left origin
-            let mut pos = 0;
-            while pos < self.sequence.len() {
-                let current_id = self.sequence[pos];
-
-                // Stop if we reach the right origin
-                if Some(current_id) == right {
+            chars += text[bytes..index].chars().count();
+            bytes = index;
+            starts.push(chars);
+        }
+        starts
+    }
+
+    /// Recount the grapheme clusters of changed blocks
+    ///
+    /// Blocks after a changed block are recounted too while their count
+    /// may depend on it.
+    fn recount_graphemes(&mut self, changed: &[ItemId]) {
+        let changed: HashSet<ItemId> = changed.iter().filter_map(|&id| self.block_of(id)).collect();
+        let mut done = HashSet::new();
+
+        for &start in &changed {
+            let mut next = Some(start);
+            while let Some(block) = next {
+                if !done.insert(block) {
                     break;
                 }
 
-                if let Some(current_item) = self.items.get(&current_id) {
-                    if current_item.left.is_none() {
-                        // Both have no left origin: order by ID
-                        if item_id < current_id {
-                            break;
-                        } else {
-                            pos += 1;
-                        }
-                    } else {
-                        // Current has a left origin, we go before it
-                        break;
-                    }
+                let item = &self.items[&block];
+                let mut lengths = self.sequence.block_lengths(block);
+                lengths.graphemes = if item.deleted {
+                    0
                 } else {
+                    self.grapheme_starts(block).len()
+                };
+                self.sequence.set_lengths(block, lengths);
+
+                let item = &self.items[&block];
+                let propagates = changed.contains(&block)
+                    || item.deleted
+                    || item.content.chars().next_back().is_some_and(joins_context);
+                next = self.sequence.next(block).filter(|_| propagates);
+            }
+        }
+    }
+
+    /// Get the ID of the visible character at a char offset
+    fn visible_char_id(&self, offset: usize) -> Option<ItemId> {
+        let (block, offset) = self.sequence.find_visible(offset)?;
+        Some(self.items[&block].id_at(offset))
+    }
+
+    /// Check if a character has been integrated
+    pub fn contains(&self, id: ItemId) -> bool {
+        self.block_of(id).is_some()
+    }
+
+    /// Find the start ID of the block holding a character
+    fn block_of(&self, id: ItemId) -> Option<ItemId> {
+        let start = *self
+            .starts
+            .get(&id.client)?
+            .range(..=id.clock)
+            .next_back()?;
+        let block = ItemId::new(id.client, start);
+        self.items[&block].contains(id).then_some(block)
+    }
+
+    /// Clock of the next character expected from a client
+    fn next_clock(&self, client: u64) -> u64 {
+        self.starts
+            .get(&client)
+            .and_then(|starts| starts.last())
+            .map_or(0, |&start| {
+                self.items[&ItemId::new(client, start)].end_clock()
+            })
+    }
+
+    /// Add a block to the sequence right after another one (or first)
+    fn insert_block(&mut self, after: Option<ItemId>, item: Item) {
+        self.starts
+            .entry(item.id.client)
+            .or_default()
+            .insert(item.id.clock);
+        let index = after.map_or(0, |block| self.sequence.index_of(block) + 1);
+        self.sequence.insert(index, item.id, lengths_of(&item));
+        self.dirty.push(item.id);
+        self.items.insert(item.id, item);
+    }
+
+    /// Make sure a character starts a block, splitting the block holding it
+    fn split_at(&mut self, id: ItemId) {
+        let Some(block) = self.block_of(id) else {
+            return;
+        };
+        if block == id {
+            return;
+        }
+
+        let left = self.items.get_mut(&block).expect("Block must exist");
+        let right = left.split((id.clock - block.clock) as usize);
+        self.sequence.set_lengths(block, lengths_of(left));
+        self.dirty.push(block);
+        self.insert_block(Some(block), right);
+    }
+
+    /// Mark a run of a client's characters as deleted
+    fn delete_range(&mut self, start: ItemId, length: usize) {
+        let end = start.clock + length as u64;
+        self.split_at(start);
+        self.split_at(ItemId::new(start.client, end));
+
+        let Some(starts) = self.starts.get(&start.client) else {
+            return;
+        };
+        let blocks: Vec<ItemId> = starts
+            .range(start.clock..end)
+            .map(|&clock| ItemId::new(start.client, clock))
+            .collect();
+
+        for block in blocks {
+            if let Some(item) = self.items.get_mut(&block) {
+                item.delete();
+                self.sequence.set_lengths(block, lengths_of(item));
+                self.dirty.push(block);
+            }
+        }
+    }
+
+    /// Check if both origins of an item have been integrated
+    fn has_origins(&self, item: &Item) -> bool {
+        item.left.is_none_or(|id| self.contains(id))
+            && item.right.is_none_or(|id| self.contains(id))
+    }
+
+    /// Integrate a block into the sequence using the YATA algorithm
+    ///
+    /// This is the core conflict resolution algorithm. Starting after the
+    /// left origin, it scans the items between the origins and places the
+    /// new block so that every replica picks the same position:
+    /// - Concurrent items with the same origins are ordered by client ID
+    /// - Items whose origin lies inside the scanned range stay attached to it
+    ///
+    /// Both origins must already be integrated.
+    fn integrate(&mut self, item: Item) {
+        // Origins must be block boundaries
+        if let Some(right) = item.right {
+            self.split_at(right);
+        }
+        if let Some(left) = item.left {
+            self.split_at(ItemId::new(left.client, left.clock + 1));
+        }
+
+        let mut left_block = item
+            .left
+            .map(|left| self.block_of(left).expect("Left origin must exist"));
+
+        let mut conflicting = HashSet::new();
+        let mut before_origin = HashSet::new();
+
+        let mut next = match left_block {
+            Some(block) => self.sequence.next(block),
+            None => self.sequence.first(),
+        };
+        while let Some(current_id) = next {
+            if Some(current_id) == item.right {
+                break;
+            }
+
+            let current = &self.items[&current_id];
+            before_origin.insert(current_id);
+            conflicting.insert(current_id);
+
+            if current.left == item.left {
+                // Same left origin: lower client ID goes first
+                if current.client < item.client {
+                    left_block = Some(current_id);
+                    conflicting.clear();
+                } else if current.right == item.right {
                     break;
                 }
+            } else if let Some(origin_block) = current
+                .left
+                .and_then(|origin| self.block_of(origin))
+                .filter(|origin_block| before_origin.contains(origin_block))
+            {
+                // Current was inserted after an item we passed: keep it
+                // attached to that item unless it's part of a conflict
+                if !conflicting.contains(&origin_block) {
+                    left_block = Some(current_id);
+                    conflicting.clear();
+                }
+            } else {
+                break;
             }
-            pos
-        };
 
-        self.sequence.insert(insert_pos, item_id);
+            next = self.sequence.next(current_id);
+        }
+
+        self.insert_block(left_block, item);
     }
 
     /// Merge adjacent blocks from the same client
     ///
     /// This is an important optimization that keeps memory usage low
-    /// for sequential typing patterns.
+    /// for sequential typing patterns. Only blocks touched since the last
+    /// call are looked at, so the cost follows the edit, not the document.
     fn merge_blocks(&mut self) {
-        let mut i = 0;
-        while i + 1 < self.sequence.len() {
-            let id1 = self.sequence[i];
-            let id2 = self.sequence[i + 1];
-
-            // Check if items can be merged
-            let can_merge = {
-                if let (Some(item1), Some(item2)) = (self.items.get(&id1), self.items.get(&id2)) {
-                    item1.can_merge_with(item2)
-                } else {
-                    false
-                }
+        let dirty = std::mem::take(&mut self.dirty);
+        self.recount_graphemes(&dirty);
+
+        for id in dirty {
+            let Some(mut block) = self.block_of(id) else {
+                continue;
             };
 
-            if can_merge {
-                // Perform merge
-                let item2 = self.items.remove(&id2).unwrap();
-                self.items.get_mut(&id1).unwrap().merge(&item2);
-                self.sequence.remove(i + 1);
-                // Don't increment i, check if we can merge with next item
-            } else {
-                i += 1;
+            while let Some(prev) = self.sequence.prev(block) {
+                if !self.items[&prev].can_merge_with(&self.items[&block]) {
+                    break;
+                }
+                self.merge_pair(prev, block);
+                block = prev;
+            }
+            while let Some(next) = self.sequence.next(block) {
+                if !self.items[&block].can_merge_with(&self.items[&next]) {
+                    break;
+                }
+                self.merge_pair(block, next);
             }
         }
     }
 
-    /// Merge this text document with another
+    /// Merge a block into the block before it
+    fn merge_pair(&mut self, first: ItemId, second: ItemId) {
+        let item2 = self.items.remove(&second).expect("Block must exist");
+        if let Some(starts) = self.starts.get_mut(&second.client) {
+            starts.remove(&second.clock);
+        }
+        // Both grapheme counts were taken in context, so they add up too
+        let mut lengths = self.sequence.block_lengths(first);
+        lengths.add(&self.sequence.block_lengths(second));
+        self.sequence.remove(second);
+        self.sequence.set_lengths(first, lengths);
+
+        let item1 = self.items.get_mut(&first).expect("Block must exist");
+        item1.merge(&item2);
+    }
+
+    /// Get the number of characters integrated from each client
+    pub fn state_vector(&self) -> StateVector {
+        let mut state = StateVector::new();
+        for &client in self.starts.keys() {
+            state.set(client, self.next_clock(client));
+        }
+        state
+    }
+
+    /// Take the local edits made since the last call
     ///
-    /// Integrates all items from the other document that we don't have yet
-    pub fn merge(&mut self, other: &Text) {
-        // Update clock to be at least as large as other's clock
-        if other.clock > self.clock {
-            self.clock = other.clock;
-        }
-
-        // Collect new items and update existing items
-        let mut new_items = Vec::new();
-        for (&id, other_item) in &other.items {
-            if let Some(my_item) = self.items.get_mut(&id) {
-                // Item exists: update deletion status
-                if other_item.deleted && !my_item.deleted {
-                    my_item.deleted = true;
+    /// Consecutive typing is coalesced, so the update grows with the edit,
+    /// not with the document.
+    pub fn take_update(&mut self) -> TextUpdate {
+        std::mem::take(&mut self.local_update)
+    }
+
+    /// Encode everything a replica with the given state vector is missing
+    ///
+    /// Contains all characters beyond the state vector plus the full sets of
+    /// deletions and marks (neither advances the state vector).
+    pub fn update_since(&self, state: &StateVector) -> TextUpdate {
+        let mut update = TextUpdate::new();
+
+        let mut clients: Vec<u64> = self.starts.keys().copied().collect();
+        clients.sort_unstable();
+
+        for &client in &clients {
+            let known = state.get(client);
+            for &start in &self.starts[&client] {
+                let block = &self.items[&ItemId::new(client, start)];
+                if block.end_clock() <= known {
+                    continue;
+                }
+
+                // Deletes are sent separately, except for collected content
+                let mut item = block.clone();
+                item.deleted = item.is_collected();
+                if item.id.clock < known {
+                    item = item.split((known - item.id.clock) as usize);
+                }
+                update.push_insert(item);
+            }
+        }
+
+        for &client in &clients {
+            for &start in &self.starts[&client] {
+                let block = &self.items[&ItemId::new(client, start)];
+                if block.deleted {
+                    update.push_delete(IdRange::new(block.id, block.len() as u64));
                 }
-            } else {
-                // New item: add it
-                self.items.insert(id, other_item.clone());
-                new_items.push(id);
             }
         }
 
-        // Integrate new items
-        self.integrate_items(&new_items);
+        update.marks = self.marks.clone();
+
+        update
+    }
+
+    /// Apply an update from another replica
+    ///
+    /// Updates can be applied in any order and more than once. Blocks whose
+    /// origins or earlier characters are missing, and deletions of unknown
+    /// characters, are kept until a later update fills the gap.
+    pub fn apply_update(&mut self, update: &TextUpdate) {
+        self.pending_inserts.extend(update.inserts.iter().cloned());
+        self.pending_deletes.extend(update.deletes.iter().copied());
+
+        self.pending_marks.extend(update.marks.iter().cloned());
+
+        self.integrate_pending();
+        self.apply_pending_deletes();
+        self.integrate_pending_marks();
+
+        // Never reuse IDs of our own characters the other side knew about
+        self.clock = self.clock.max(self.next_clock(self.client_id));
 
         // Merge blocks for optimization
         self.merge_blocks();
     }
+
+    /// Drop the content of deleted characters every known replica has
+    ///
+    /// `stable` is the state vector all peers have acknowledged (see
+    /// `crate::sync::StabilityTracker` and `state_vector`). Those peers
+    /// never need the content again; replicas joining later receive the
+    /// characters as deleted, without content.
+    ///
+    /// IDs, origins and lengths are kept, so concurrent inserts, marks and
+    /// relative positions anchored to collected characters keep working.
+    /// Collected characters can no longer be brought back by `reinsert`
+    /// (and so by undo).
+    ///
+    /// Returns the number of characters whose content was dropped.
+    pub fn collect_garbage(&mut self, stable: &StateVector) -> usize {
+        let mut collected = 0;
+        for item in self.items.values_mut() {
+            if item.deleted && !item.is_collected() && item.end_clock() <= stable.get(item.client) {
+                item.collect();
+                collected += item.len();
+                self.dirty.push(item.id);
+            }
+        }
+
+        // Neighbouring blocks may have been collected together
+        self.merge_blocks();
+        collected
+    }
+
+    /// Check if remote changes are waiting for missing characters
+    pub fn has_pending(&self) -> bool {
+        !self.pending_inserts.is_empty()
+            || !self.pending_deletes.is_empty()
+            || !self.pending_marks.is_empty()
+    }
+
+    /// Integrate buffered blocks whose origins are in place
+    fn integrate_pending(&mut self) {
+        // Each client's blocks, in clock order
+        let mut by_client: BTreeMap<u64, Vec<Item>> = BTreeMap::new();
+        for item in std::mem::take(&mut self.pending_inserts) {
+            by_client.entry(item.client).or_default().push(item);
+        }
+        let mut queues: Vec<VecDeque<Item>> = by_client
+            .into_values()
+            .map(|mut items| {
+                items.sort_by_key(|item| item.id.clock);
+                items.into()
+            })
+            .collect();
+
+        // Integrate whatever can be placed until nothing changes
+        loop {
+            let mut progress = false;
+            for queue in &mut queues {
+                while let Some(front) = queue.front_mut() {
+                    let known = self.next_clock(front.client);
+                    if front.end_clock() <= known {
+                        // Already integrated
+                        queue.pop_front();
+                        continue;
+                    }
+                    if front.id.clock > known {
+                        // Earlier characters from this client are missing
+                        break;
+                    }
+                    if front.id.clock < known {
+                        *front = front.split((known - front.id.clock) as usize);
+                    }
+                    if !self.has_origins(front) {
+                        break;
+                    }
+
+                    let item = queue.pop_front().expect("Queue is not empty");
+                    self.integrate(item);
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        self.pending_inserts = queues.into_iter().flatten().collect();
+    }
+
+    /// Apply buffered deletions to the characters we have
+    fn apply_pending_deletes(&mut self) {
+        for range in std::mem::take(&mut self.pending_deletes) {
+            let known = self.next_clock(range.start.client);
+            let end = range.end_clock();
+
+            if range.start.clock < known {
+                let length = end.min(known) - range.start.clock;
+                self.delete_range(range.start, length as usize);
+            }
+            if end > known {
+                let start = range.start.clock.max(known);
+                self.pending_deletes.push(IdRange::new(
+                    ItemId::new(range.start.client, start),
+                    end - start,
+                ));
+            }
+        }
+    }
+
+    /// Add buffered marks whose anchors are in place
+    fn integrate_pending_marks(&mut self) {
+        for mark in std::mem::take(&mut self.pending_marks) {
+            if mark.anchor_ids().all(|id| self.contains(id)) {
+                self.add_mark(mark);
+            } else {
+                self.pending_marks.push(mark);
+            }
+        }
+    }
+
+    /// Merge this text document with another
+    ///
+    /// Equivalent to applying `other.update_since(&self.state_vector())`.
+    pub fn merge(&mut self, other: &Text) {
+        self.apply_update(&other.update_since(&self.state_vector()));
+    }
+}
+
+/// Resolve the attributes at a character index
+///
+/// For each attribute the covering mark with the highest ID wins.
+fn resolve_attributes(spans: &[(usize, usize, &Mark)], index: usize) -> Attributes {
+    let mut attributes = Attributes::new();
+    for &(lo, hi, mark) in spans {
+        if lo <= index && index < hi {
+            attributes.insert(mark.name.clone(), mark.value.clone());
+        }
+    }
+    attributes.retain(|_, value| !value.is_null());
+    attributes
+}
+
+/// Number of visible characters in a block
+/// Lengths of a block, leaving its grapheme count to `recount_graphemes`
+fn lengths_of(item: &Item) -> Lengths {
+    let (visible, utf16) = if item.deleted {
+        (0, 0)
+    } else {
+        (item.len(), item.content.encode_utf16().count())
+    };
+    Lengths {
+        len: item.len(),
+        visible,
+        utf16,
+        graphemes: 0,
+    }
+}
+
+/// Check if grapheme boundaries after a character depend on the ones before
+///
+/// True for regional indicators (flags pair up) and characters that attach
+/// to a preceding base, such as combining marks and zero-width joiners.
+fn joins_context(ch: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch) || format!("a{ch}").graphemes(true).nth(1).is_none()
 }
 
 impl std::fmt::Display for Text {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for &id in &self.sequence {
+        for id in self.sequence.iter() {
             if let Some(item) = self.items.get(&id) {
                 if !item.deleted {
                     write!(f, "{}", item.content)?;
@@ -427,7 +1232,7 @@ mod tests {
         assert_eq!(text.to_string(), "Hello");
 
         // Check that blocks were merged (optimization)
-        assert!(text.sequence.len() <= 5);
+        assert_eq!(text.sequence.len(), 1);
     }
 
     #[test]
@@ -457,12 +1262,12 @@ mod tests {
         let mut text1 = Text::new(1);
         let mut text2 = Text::new(2);
 
-        text1.insert(0, "Hello");
+        text1.insert(0, "Héllo");
         text2.merge(&text1);
 
         // Concurrent insertions at different positions
-        text1.insert(0, "A"); // "AHello"
-        text2.insert(5, "B"); // "HelloB"
+        text1.insert(0, "Ä"); // "ÄHéllo"
+        text2.insert(5, "😀"); // "Héllo😀"
 
         // Merge
         text1.merge(&text2);
@@ -470,7 +1275,7 @@ mod tests {
 
         // Should converge
         assert_eq!(text1.to_string(), text2.to_string());
-        assert_eq!(text1.to_string(), "AHelloB");
+        assert_eq!(text1.to_string(), "ÄHéllo😀");
     }
 
     #[test]
@@ -479,12 +1284,12 @@ mod tests {
         let mut text1 = Text::new(1);
         let mut text2 = Text::new(2);
 
-        text1.insert(0, "Hello World");
+        text1.insert(0, "Grüße Wörld");
         text2.merge(&text1);
 
         // Concurrent deletions
-        text1.delete(0, 5); // Delete "Hello"
-        text2.delete(6, 5); // Delete "World"
+        text1.delete(0, 5); // Delete "Grüße"
+        text2.delete(6, 5); // Delete "Wörld"
 
         // Merge
         text1.merge(&text2);
@@ -503,14 +1308,14 @@ mod tests {
         let mut text3 = Text::new(3);
 
         // Initial state
-        text1.insert(0, "abc");
+        text1.insert(0, "日本語");
         text2.merge(&text1);
         text3.merge(&text1);
 
         // Concurrent operations
-        text1.insert(1, "X"); // "aXbc"
-        text2.insert(2, "Y"); // "abYc"
-        text3.delete(0, 1); // "bc"
+        text1.insert(1, "🎉"); // "日🎉本語"
+        text2.insert(2, "é"); // "日本é語"
+        text3.delete(0, 1); // "本語"
 
         // Merge all states
         text1.merge(&text2);
@@ -546,4 +1351,558 @@ mod tests {
         assert_eq!(text.to_string(), "");
         assert!(text.is_empty());
     }
+
+    #[test]
+    fn test_multibyte_positions_count_chars() {
+        let mut text = Text::new(1);
+
+        text.insert(0, "héllo");
+        assert_eq!(text.len(), 5);
+
+        text.insert(2, "X");
+        assert_eq!(text.to_string(), "héXllo");
+
+        text.delete(1, 2); // Delete "éX"
+        assert_eq!(text.to_string(), "hllo");
+    }
+
+    #[test]
+    fn test_utf16_positions() {
+        let mut text = Text::with_unit(1, PositionUnit::Utf16);
+
+        text.insert(0, "a😀b");
+        assert_eq!(text.len(), 4);
+
+        // JS index 3 is after the emoji's surrogate pair
+        text.insert(3, "c");
+        assert_eq!(text.to_string(), "a😀cb");
+
+        // A range ending inside the surrogate pair covers the whole emoji
+        text.delete(1, 1);
+        assert_eq!(text.to_string(), "acb");
+        assert_eq!(text.len(), 3);
+    }
+
+    #[test]
+    fn test_grapheme_positions() {
+        let mut text = Text::with_unit(1, PositionUnit::Grapheme);
+
+        // "e" + combining acute accent is one grapheme
+        text.insert(0, "ne\u{301}e");
+        assert_eq!(text.len(), 3);
+
+        text.insert(2, "!");
+        assert_eq!(text.to_string(), "ne\u{301}!e");
+
+        text.delete(1, 1);
+        assert_eq!(text.to_string(), "n!e");
+    }
+
+    #[test]
+    fn test_concurrent_edits_with_different_units() {
+        // A browser client (UTF-16) and a native client (chars) converge
+        let mut web = Text::with_unit(1, PositionUnit::Utf16);
+        let mut native = Text::new(2);
+
+        web.insert(0, "👋🌍");
+        native.merge(&web);
+
+        web.insert(2, "-"); // After 👋 (two UTF-16 units)
+        native.insert(2, "!"); // After 🌍 (two chars)
+
+        web.merge(&native);
+        native.merge(&web);
+
+        assert_eq!(web.to_string(), native.to_string());
+        assert_eq!(web.to_string(), "👋-🌍!");
+        assert_eq!(web.len(), 6);
+        assert_eq!(native.len(), 4);
+    }
+
+    #[test]
+    fn test_unit_lengths_match_string() {
+        // Pieces that join with their neighbours: combining marks, flags
+        // (pairs of regional indicators), ZWJ sequences and astral chars
+        const PIECES: [&str; 8] = ["a", "\u{301}", "🇫", "🇷", "\u{200D}", "👩", "é", "xy"];
+
+        fn check(text: &mut Text) {
+            let string = text.to_string();
+            for unit in [
+                PositionUnit::Char,
+                PositionUnit::Utf16,
+                PositionUnit::Grapheme,
+            ] {
+                text.set_unit(unit);
+                let len = unit.len(&string);
+                assert_eq!(text.len(), len, "{unit:?} length of {string:?}");
+                for position in 0..=len + 1 {
+                    for round_up in [false, true] {
+                        assert_eq!(
+                            text.to_char_offset(position, round_up),
+                            unit.to_char_offset(&string, position, round_up),
+                            "{unit:?} position {position} in {string:?}"
+                        );
+                    }
+                }
+                for offset in 0..=string.chars().count() {
+                    assert_eq!(
+                        text.position_of(offset),
+                        unit.from_char_offset(&string, offset),
+                        "{unit:?} offset {offset} in {string:?}"
+                    );
+                }
+            }
+        }
+
+        let mut replicas = [Text::new(1), Text::new(2)];
+        let mut seed = 11u64;
+        for step in 0..300 {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let text = &mut replicas[step % 2];
+            text.set_unit(PositionUnit::Char);
+            let len = text.len();
+            let position = (seed >> 33) as usize % (len + 1);
+            if len > 0 && seed.is_multiple_of(4) {
+                text.delete(position.min(len - 1), 1 + (seed >> 40) as usize % 3);
+            } else {
+                text.insert(position, PIECES[(seed >> 20) as usize % PIECES.len()]);
+            }
+            check(text);
+
+            if step % 10 == 9 {
+                let [a, b] = &mut replicas;
+                a.merge(b);
+                b.merge(a);
+                check(a);
+                check(b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reinsert_pairs_originals_in_document_order() {
+        let mut text = Text::new(1);
+        text.insert(0, "abcdef");
+        let deleted = text.delete(1, 4); // "bcde"
+        assert_eq!(text.to_string(), "af");
+
+        // Out of order, repeated and unknown IDs
+        let ids = [
+            ItemId::new(1, 4),
+            ItemId::new(9, 0),
+            ItemId::new(1, 1),
+            ItemId::new(1, 3),
+            ItemId::new(1, 1),
+        ];
+        let pairs = text.reinsert(&ids);
+        assert_eq!(text.to_string(), "abdef");
+
+        let originals: Vec<ItemId> = pairs.iter().map(|&(original, _)| original).collect();
+        assert_eq!(originals, vec![deleted[0], deleted[2], deleted[3]]);
+        let char_of = |id: ItemId| {
+            let block = text.block_of(id).unwrap();
+            text.items[&block]
+                .content
+                .chars()
+                .nth((id.clock - block.clock) as usize)
+        };
+        for (original, copy) in pairs {
+            assert_eq!(char_of(original), char_of(copy));
+        }
+    }
+
+    #[test]
+    fn test_delete_inside_block() {
+        let mut text = Text::new(1);
+        for (i, ch) in ["H", "e", "l", "l", "o"].iter().enumerate() {
+            text.insert(i, ch);
+        }
+        assert_eq!(text.sequence.len(), 1);
+
+        let deleted = text.delete(1, 1);
+
+        assert_eq!(deleted, vec![ItemId::new(1, 1)]);
+        assert_eq!(text.to_string(), "Hllo");
+        assert_eq!(text.len(), 4);
+    }
+
+    #[test]
+    fn test_insert_inside_block() {
+        let mut text = Text::new(1);
+        text.insert(0, "Helo");
+        text.insert(3, "l");
+
+        assert_eq!(text.to_string(), "Hello");
+
+        // The split block and the new character can't be merged back
+        assert_eq!(text.sequence.len(), 3);
+    }
+
+    #[test]
+    fn test_concurrent_edits_inside_block() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+
+        text1.insert(0, "Hello");
+        text2.merge(&text1);
+
+        text1.delete(1, 3); // "Ho"
+        text2.insert(2, "X"); // "HeXllo"
+
+        text1.merge(&text2);
+        text2.merge(&text1);
+
+        assert_eq!(text1.to_string(), text2.to_string());
+        assert_eq!(text1.to_string(), "HXo");
+    }
+
+    #[test]
+    fn test_merge_with_partially_known_block() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+
+        text1.insert(0, "Hel");
+        text2.merge(&text1);
+
+        // Client 1 keeps typing; its block grows past what client 2 knows
+        text1.insert(3, "lo");
+        text2.insert(3, "!");
+
+        text2.merge(&text1);
+        text1.merge(&text2);
+
+        assert_eq!(text1.to_string(), text2.to_string());
+        assert_eq!(text1.to_string(), "Hello!");
+    }
+
+    #[test]
+    fn test_merged_ids_are_not_reused() {
+        let mut text1 = Text::new(1);
+        text1.insert(0, "abc");
+
+        // A fresh replica for the same client catches up first
+        let mut text1_again = Text::new(1);
+        text1_again.merge(&text1);
+        let ids = text1_again.insert(3, "d");
+
+        assert_eq!(ids, vec![ItemId::new(1, 3)]);
+        assert_eq!(text1_again.to_string(), "abcd");
+    }
+
+    #[test]
+    fn test_take_update_is_compact() {
+        let mut text1 = Text::new(1);
+        text1.insert(0, "Hello World");
+        text1.take_update();
+
+        // Typing one character at a time yields a single block
+        for (i, ch) in ["!", "!", "!"].iter().enumerate() {
+            text1.insert(11 + i, ch);
+        }
+        text1.delete(0, 6);
+
+        let update = text1.take_update();
+        assert_eq!(update.inserts.len(), 1);
+        assert_eq!(update.inserts[0].content, "!!!");
+        assert_eq!(update.deletes, vec![IdRange::new(ItemId::new(1, 0), 6)]);
+        assert!(text1.take_update().is_empty());
+    }
+
+    #[test]
+    fn test_apply_local_updates() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+
+        text1.insert(0, "Hello");
+        text2.apply_update(&text1.take_update());
+
+        text1.delete(1, 3);
+        text2.insert(5, " World");
+        text2.apply_update(&text1.take_update());
+        text1.apply_update(&text2.take_update());
+
+        assert_eq!(text1.to_string(), "Ho World");
+        assert_eq!(text1.to_string(), text2.to_string());
+    }
+
+    #[test]
+    fn test_out_of_order_updates_are_buffered() {
+        let mut text1 = Text::new(1);
+        text1.insert(0, "ab");
+        let first = text1.take_update();
+        text1.insert(1, "X");
+        text1.delete(0, 1);
+        let second = text1.take_update();
+
+        let mut text2 = Text::new(2);
+        text2.apply_update(&second);
+        assert_eq!(text2.to_string(), "");
+        assert!(text2.has_pending());
+
+        text2.apply_update(&first);
+        assert!(!text2.has_pending());
+        assert_eq!(text2.to_string(), "Xb");
+
+        // Duplicates are ignored
+        text2.apply_update(&first);
+        text2.apply_update(&second);
+        assert_eq!(text2.to_string(), "Xb");
+    }
+
+    #[test]
+    fn test_update_since_state_vector() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+
+        text1.insert(0, "Hello");
+        text2.merge(&text1);
+        text1.insert(5, "!");
+        text1.delete(0, 1);
+
+        let state = text2.state_vector();
+        assert_eq!(state.get(1), 5);
+
+        // Only the new character travels, plus the delete set
+        let update = text1.update_since(&state);
+        assert_eq!(update.inserts.len(), 1);
+        assert_eq!(update.inserts[0].content, "!");
+        assert_eq!(update.deletes, vec![IdRange::new(ItemId::new(1, 0), 1)]);
+
+        text2.apply_update(&update);
+        assert_eq!(text2.to_string(), "ello!");
+        assert!(text1.update_since(&text2.state_vector()).inserts.is_empty());
+    }
+
+    fn bold(text: &str) -> TextRun {
+        TextRun::new(text).with_attribute("bold", serde_json::json!(true))
+    }
+
+    #[test]
+    fn test_format_expands_right() {
+        let mut text = Text::new(1);
+        text.insert(0, "Hello World");
+        text.format(0, 5, "bold", serde_json::json!(true), Expand::Right);
+
+        // Typing at the end of the range extends it, at the start doesn't
+        text.insert(5, "!");
+        text.insert(0, ">");
+
+        assert_eq!(
+            text.to_runs(),
+            vec![TextRun::new(">"), bold("Hello!"), TextRun::new(" World")]
+        );
+    }
+
+    #[test]
+    fn test_format_expand_none_and_left() {
+        let mut text = Text::new(1);
+        text.insert(0, "abc");
+        text.format(
+            1,
+            1,
+            "link",
+            serde_json::json!("https://example.com"),
+            Expand::None,
+        );
+        text.format(1, 1, "italic", serde_json::json!(true), Expand::Left);
+
+        text.insert(2, "R");
+        text.insert(1, "L");
+
+        let link = serde_json::json!("https://example.com");
+        assert_eq!(
+            text.to_runs(),
+            vec![
+                TextRun::new("a"),
+                TextRun::new("L").with_attribute("italic", serde_json::json!(true)),
+                TextRun::new("b")
+                    .with_attribute("italic", serde_json::json!(true))
+                    .with_attribute("link", link),
+                TextRun::new("Rc"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unformat_inside_range() {
+        let mut text = Text::new(1);
+        text.insert(0, "Hello");
+        text.format(0, 5, "bold", serde_json::json!(true), Expand::Right);
+        text.unformat(1, 3, "bold", Expand::None);
+
+        assert_eq!(
+            text.to_runs(),
+            vec![bold("H"), TextRun::new("ell"), bold("o")]
+        );
+    }
+
+    #[test]
+    fn test_concurrent_formatting_converges() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+        text1.insert(0, "Hello World");
+        text2.merge(&text1);
+
+        // Overlapping concurrent marks, plus an insert inside the range
+        text1.format(0, 5, "bold", serde_json::json!(true), Expand::Right);
+        text2.unformat(3, 5, "bold", Expand::Right);
+        text2.insert(2, "X");
+
+        text1.merge(&text2);
+        text2.merge(&text1);
+
+        assert_eq!(text1.to_runs(), text2.to_runs());
+        // Same Lamport clock: the higher client ID wins
+        assert_eq!(
+            text1.to_runs(),
+            vec![bold("HeXl"), TextRun::new("lo World")]
+        );
+    }
+
+    #[test]
+    fn test_marks_wait_for_their_characters() {
+        let mut text1 = Text::new(1);
+        text1.insert(0, "ab");
+        let first = text1.take_update();
+        text1.format(0, 2, "bold", serde_json::json!(true), Expand::None);
+        let second = text1.take_update();
+
+        let mut text2 = Text::new(2);
+        text2.apply_update(&second);
+        assert!(text2.has_pending());
+
+        text2.apply_update(&first);
+        assert!(!text2.has_pending());
+        assert_eq!(text2.to_runs(), vec![bold("ab")]);
+    }
+
+    #[test]
+    fn test_insert_runs_round_trip() {
+        let runs = vec![
+            bold("Hello"),
+            TextRun::new(" "),
+            TextRun::new("World").with_attribute("italic", serde_json::json!(true)),
+            TextRun::new("!"),
+        ];
+
+        let mut text = Text::new(1);
+        text.insert_runs(0, &runs, Expand::Right);
+
+        assert_eq!(text.to_string(), "Hello World!");
+        assert_eq!(text.to_runs(), runs);
+    }
+
+    #[test]
+    fn test_relative_position_follows_edits() {
+        let mut text = Text::new(1);
+        text.insert(0, "Hello World");
+
+        let after = text.relative_position(5, Assoc::After);
+        let before = text.relative_position(5, Assoc::Before);
+
+        // Edits before the cursor shift it
+        text.insert(0, "Oh, ");
+        assert_eq!(text.resolve_position(&after), Some(9));
+        assert_eq!(text.resolve_position(&before), Some(9));
+
+        // Text typed at the cursor goes to the side it doesn't stick to
+        text.insert(9, "!!");
+        assert_eq!(text.to_string(), "Oh, Hello!! World");
+        assert_eq!(text.resolve_position(&after), Some(11));
+        assert_eq!(text.resolve_position(&before), Some(9));
+
+        // Deleting the anchored characters keeps the cursor in place
+        text.delete(8, 4);
+        assert_eq!(text.to_string(), "Oh, HellWorld");
+        assert_eq!(text.resolve_position(&after), Some(8));
+        assert_eq!(text.resolve_position(&before), Some(8));
+    }
+
+    #[test]
+    fn test_relative_position_ends() {
+        let mut text = Text::new(1);
+        text.insert(0, "abc");
+
+        let start = text.relative_position(0, Assoc::Before);
+        let end = text.relative_position(3, Assoc::After);
+        assert_eq!(start, RelativePosition::start());
+        assert_eq!(end, RelativePosition::end());
+
+        text.insert(0, "x");
+        text.insert(4, "y");
+        assert_eq!(text.resolve_position(&start), Some(0));
+        assert_eq!(text.resolve_position(&end), Some(5));
+    }
+
+    #[test]
+    fn test_relative_position_across_replicas() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::with_unit(2, PositionUnit::Utf16);
+        text1.insert(0, "😀 Hi");
+        text2.merge(&text1);
+
+        // Client 2 shares its cursor (before "Hi") as presence data
+        let cursor = text2.relative_position(3, Assoc::After);
+        let json = serde_json::to_string(&cursor).unwrap();
+
+        text1.insert(0, "¡");
+        text2.insert(0, "Oh ");
+        text1.merge(&text2);
+        text2.merge(&text1);
+
+        let cursor: RelativePosition = serde_json::from_str(&json).unwrap();
+        assert_eq!(text1.resolve_position(&cursor), Some(6));
+        assert_eq!(text2.resolve_position(&cursor), Some(7));
+
+        // Unknown characters don't resolve
+        let unknown = RelativePosition::before(ItemId::new(9, 0));
+        assert_eq!(text1.resolve_position(&unknown), None);
+    }
+
+    #[test]
+    fn test_collect_garbage_keeps_structure() {
+        use crate::sync::StabilityTracker;
+
+        let mut text1 = Text::new(1);
+        text1.insert(0, "Hello World");
+        let mut text2 = Text::new(2);
+        text2.merge(&text1);
+
+        text1.delete(5, 6);
+        let cursor = text1.relative_position(5, Assoc::After); // At " World"
+
+        // Not collected until every peer has the characters
+        let mut tracker = StabilityTracker::new();
+        tracker.acknowledge("2".to_string(), &StateVector::new());
+        assert_eq!(
+            text1.collect_garbage(&tracker.stable(&text1.state_vector())),
+            0
+        );
+
+        tracker.acknowledge("2".to_string(), &text2.state_vector());
+        assert_eq!(
+            text1.collect_garbage(&tracker.stable(&text1.state_vector())),
+            6
+        );
+        assert_eq!(text1.to_string(), "Hello");
+        assert_eq!(text1.resolve_position(&cursor), Some(5));
+
+        // text2 hasn't seen the delete and types next to collected text
+        text2.insert(11, "!");
+        text2.apply_update(&text1.update_since(&text2.state_vector()));
+        text1.apply_update(&text2.update_since(&text1.state_vector()));
+        assert_eq!(text1.to_string(), "Hello!");
+        assert_eq!(text2.to_string(), text1.to_string());
+
+        // A new replica gets the collected characters without content
+        let mut text3 = Text::new(3);
+        text3.apply_update(&text1.update_since(&StateVector::new()));
+        assert_eq!(text3.to_string(), "Hello!");
+
+        // Collected characters can't be brought back
+        let deleted: Vec<ItemId> = (5..11).map(|clock| ItemId::new(1, clock)).collect();
+        assert!(text1.reinsert(&deleted).is_empty());
+    }
 }
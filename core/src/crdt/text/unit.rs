@@ -0,0 +1,149 @@
+//! Position units: how index-based Text APIs count characters
+//!
+//! Internally the Text CRDT counts Unicode scalar values (`char`s). Callers
+//! can pick the unit their editor uses instead:
+//! - `Char`: Unicode scalar values (Rust `char`, Python `str` indices)
+//! - `Utf16`: UTF-16 code units (JavaScript string indices, DOM selections)
+//! - `Grapheme`: extended grapheme clusters (user-perceived characters)
+
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Unit for positions and lengths in index-based Text APIs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PositionUnit {
+    /// Unicode scalar values
+    #[default]
+    Char,
+
+    /// UTF-16 code units
+    Utf16,
+
+    /// Extended grapheme clusters
+    Grapheme,
+}
+
+impl PositionUnit {
+    /// Length of a string in this unit
+    pub fn len(self, text: &str) -> usize {
+        match self {
+            PositionUnit::Char => text.chars().count(),
+            PositionUnit::Utf16 => text.encode_utf16().count(),
+            PositionUnit::Grapheme => text.graphemes(true).count(),
+        }
+    }
+
+    /// Convert a position in this unit to a char offset into `text`
+    ///
+    /// Positions past the end are clamped. A UTF-16 position in the middle
+    /// of a surrogate pair rounds down, or up if `round_up` is set.
+    pub fn to_char_offset(self, text: &str, position: usize, round_up: bool) -> usize {
+        match self {
+            PositionUnit::Char => position.min(text.chars().count()),
+            PositionUnit::Utf16 => {
+                let mut units = 0;
+                let mut offset = 0;
+                for ch in text.chars() {
+                    if units >= position {
+                        break;
+                    }
+                    units += ch.len_utf16();
+                    if units > position && !round_up {
+                        break;
+                    }
+                    offset += 1;
+                }
+                offset
+            }
+            PositionUnit::Grapheme => text
+                .graphemes(true)
+                .take(position)
+                .map(|grapheme| grapheme.chars().count())
+                .sum(),
+        }
+    }
+
+    /// Convert a char offset into `text` to a position in this unit
+    ///
+    /// An offset inside a grapheme cluster maps to the cluster's start.
+    pub fn from_char_offset(self, text: &str, offset: usize) -> usize {
+        match self {
+            PositionUnit::Char => offset.min(text.chars().count()),
+            PositionUnit::Utf16 => text.chars().take(offset).map(char::len_utf16).sum(),
+            PositionUnit::Grapheme => {
+                let mut chars = 0;
+                text.graphemes(true)
+                    .take_while(|grapheme| {
+                        chars += grapheme.chars().count();
+                        chars <= offset
+                    })
+                    .count()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "e" + combining acute, a flag (two regional indicators), a family emoji (ZWJ sequence)
+    const SAMPLE: &str = "ae\u{301}🇫🇷👨‍👩‍👧!";
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(PositionUnit::Char.len(SAMPLE), 11);
+        assert_eq!(PositionUnit::Utf16.len(SAMPLE), 16);
+        assert_eq!(PositionUnit::Grapheme.len(SAMPLE), 5);
+    }
+
+    #[test]
+    fn test_utf16_to_char_offset() {
+        let unit = PositionUnit::Utf16;
+
+        assert_eq!(unit.to_char_offset(SAMPLE, 0, false), 0);
+        assert_eq!(unit.to_char_offset(SAMPLE, 3, false), 3);
+
+        // Halfway through the first regional indicator
+        assert_eq!(unit.to_char_offset(SAMPLE, 4, false), 3);
+        assert_eq!(unit.to_char_offset(SAMPLE, 4, true), 4);
+
+        assert_eq!(unit.to_char_offset(SAMPLE, 7, false), 5);
+        assert_eq!(unit.to_char_offset(SAMPLE, 100, false), 11);
+    }
+
+    #[test]
+    fn test_grapheme_to_char_offset() {
+        let unit = PositionUnit::Grapheme;
+
+        assert_eq!(unit.to_char_offset(SAMPLE, 1, false), 1);
+        assert_eq!(unit.to_char_offset(SAMPLE, 2, false), 3);
+        assert_eq!(unit.to_char_offset(SAMPLE, 3, false), 5);
+        assert_eq!(unit.to_char_offset(SAMPLE, 4, false), 10);
+        assert_eq!(unit.to_char_offset(SAMPLE, 9, false), 11);
+    }
+
+    #[test]
+    fn test_from_char_offset_round_trips() {
+        for unit in [
+            PositionUnit::Char,
+            PositionUnit::Utf16,
+            PositionUnit::Grapheme,
+        ] {
+            // Char offsets of the grapheme cluster boundaries
+            for offset in [0, 1, 3, 5, 10, 11] {
+                let position = unit.from_char_offset(SAMPLE, offset);
+                assert_eq!(
+                    unit.to_char_offset(SAMPLE, position, false),
+                    offset,
+                    "{:?}",
+                    unit
+                );
+            }
+        }
+
+        // Inside a grapheme cluster
+        assert_eq!(PositionUnit::Grapheme.from_char_offset(SAMPLE, 2), 1);
+        assert_eq!(PositionUnit::Utf16.from_char_offset(SAMPLE, 4), 5);
+    }
+}
@@ -0,0 +1,204 @@
+//! Incremental updates for the Text CRDT
+//!
+//! Instead of shipping the whole document, replicas exchange `TextUpdate`s:
+//! - Local edits are recorded as they happen (`Text::take_update`)
+//! - A peer's `StateVector` says which characters it already has, and
+//!   `Text::update_since` encodes everything it is missing
+//!
+//! Formatting marks travel with updates too. They aren't covered by the
+//! state vector, so `Text::update_since` always includes all of them.
+//!
+//! Updates can be applied in any order and more than once. Characters whose
+//! origins (or earlier characters from the same client) haven't arrived yet
+//! are buffered until they do.
+
+use super::id::ItemId;
+use super::item::Item;
+use super::mark::Mark;
+use crate::sync::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Number of characters seen from each client
+///
+/// Each client numbers its characters 0, 1, 2, ..., so a single clock per
+/// client describes everything a replica has integrated.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateVector {
+    /// Next expected clock per client
+    clocks: BTreeMap<u64, u64>,
+}
+
+impl StateVector {
+    /// Create an empty state vector (nothing seen)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the next expected clock for a client
+    pub fn get(&self, client: u64) -> u64 {
+        self.clocks.get(&client).copied().unwrap_or(0)
+    }
+
+    /// Set the next expected clock for a client
+    pub fn set(&mut self, client: u64, clock: u64) {
+        if clock == 0 {
+            self.clocks.remove(&client);
+        } else {
+            self.clocks.insert(client, clock);
+        }
+    }
+
+    /// Check if a character is covered by this state vector
+    pub fn contains(&self, id: ItemId) -> bool {
+        id.clock < self.get(id.client)
+    }
+
+    /// Iterate over (client, next clock) pairs
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.clocks.iter().map(|(&client, &clock)| (client, clock))
+    }
+}
+
+impl Version for StateVector {
+    fn join(&mut self, other: &Self) {
+        for (client, clock) in other.iter() {
+            self.set(client, self.get(client).max(clock));
+        }
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut result = StateVector::new();
+        for (client, clock) in self.iter() {
+            result.set(client, clock.min(other.get(client)));
+        }
+        result
+    }
+}
+
+/// A run of consecutive characters from one client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdRange {
+    /// First character in the run
+    pub start: ItemId,
+
+    /// Number of characters
+    pub len: u64,
+}
+
+impl IdRange {
+    /// Create a new range
+    pub fn new(start: ItemId, len: u64) -> Self {
+        Self { start, len }
+    }
+
+    /// Clock just past the last character
+    pub fn end_clock(&self) -> u64 {
+        self.start.clock + self.len
+    }
+}
+
+/// Inserted blocks and deleted ranges to apply to a Text
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextUpdate {
+    /// Inserted blocks with their origins
+    pub inserts: Vec<Item>,
+
+    /// Deleted character ranges
+    pub deletes: Vec<IdRange>,
+
+    /// Formatting marks
+    #[serde(default)]
+    pub marks: Vec<Mark>,
+}
+
+impl TextUpdate {
+    /// Create an empty update
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if the update carries no changes
+    pub fn is_empty(&self) -> bool {
+        self.inserts.is_empty() && self.deletes.is_empty() && self.marks.is_empty()
+    }
+
+    /// Add an inserted block, extending the previous one if it continues it
+    pub fn push_insert(&mut self, item: Item) {
+        match self.inserts.last_mut() {
+            Some(last) if last.can_merge_with(&item) => last.merge(&item),
+            _ => self.inserts.push(item),
+        }
+    }
+
+    /// Add a deleted range, extending the previous one if it continues it
+    pub fn push_delete(&mut self, range: IdRange) {
+        match self.deletes.last_mut() {
+            Some(last)
+                if last.start.client == range.start.client
+                    && last.end_clock() == range.start.clock =>
+            {
+                last.len += range.len;
+            }
+            _ => self.deletes.push(range),
+        }
+    }
+
+    /// Append another update's changes to this one
+    pub fn extend(&mut self, other: TextUpdate) {
+        for item in other.inserts {
+            self.push_insert(item);
+        }
+        for range in other.deletes {
+            self.push_delete(range);
+        }
+        self.marks.extend(other.marks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_vector() {
+        let mut sv = StateVector::new();
+        sv.set(1, 3);
+
+        assert_eq!(sv.get(1), 3);
+        assert_eq!(sv.get(2), 0);
+        assert!(sv.contains(ItemId::new(1, 2)));
+        assert!(!sv.contains(ItemId::new(1, 3)));
+
+        sv.set(1, 0);
+        assert_eq!(sv, StateVector::new());
+    }
+
+    #[test]
+    fn test_push_extends_runs() {
+        let mut update = TextUpdate::new();
+
+        update.push_insert(Item::new(ItemId::new(1, 0), "ab".to_string(), None, None));
+        update.push_insert(Item::new(
+            ItemId::new(1, 2),
+            "c".to_string(),
+            Some(ItemId::new(1, 1)),
+            None,
+        ));
+        update.push_insert(Item::new(ItemId::new(2, 0), "x".to_string(), None, None));
+
+        update.push_delete(IdRange::new(ItemId::new(1, 0), 1));
+        update.push_delete(IdRange::new(ItemId::new(1, 1), 2));
+        update.push_delete(IdRange::new(ItemId::new(2, 0), 1));
+
+        assert_eq!(update.inserts.len(), 2);
+        assert_eq!(update.inserts[0].content, "abc");
+        assert_eq!(
+            update.deletes,
+            vec![
+                IdRange::new(ItemId::new(1, 0), 3),
+                IdRange::new(ItemId::new(2, 0), 1),
+            ]
+        );
+    }
+}
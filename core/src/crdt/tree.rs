@@ -0,0 +1,657 @@
+//! Tree: movable tree CRDT for hierarchical data
+//!
+//! Folders, outlines and other hierarchies need moves. Storing each node's
+//! parent as a plain LWW field breaks under concurrency: two replicas moving
+//! A under B and B under A produce a cycle, detached from the root.
+//!
+//! # Move log
+//!
+//! Every create, move and delete is a move operation: (timestamp, node, new
+//! parent, position). The state is the set of all moves, merged by union.
+//! The tree is computed by replaying the moves in timestamp order, skipping
+//! any move that would make a node its own ancestor. The skipped node stays
+//! where the earlier moves put it. Every replica replays the same moves in
+//! the same order, so all of them compute the same tree, and no cycle can
+//! ever appear (Kleppmann et al., "A highly-available move operation for
+//! replicated trees").
+//!
+//! - **Delete:** moves the node under a hidden trash node, with its subtree
+//! - **Sibling order:** each move carries a `FractionalIndex` position
+//! - **Payload:** each node holds a JSON value under Last-Write-Wins
+//!
+//! Timestamps come from a Lamport clock per tree. A local operation is
+//! always the newest one, so it is applied directly. A merge that brings
+//! new moves undoes the local moves newer than the earliest new one, then
+//! redoes them interleaved with the new ones, as in the paper. Its cost
+//! grows with the number of moves redone (times the tree depth for the
+//! cycle check), so merging a long-offline replica replays most of the log.
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::crdt::tree::{NodeId, Tree};
+//! use serde_json::json;
+//!
+//! let mut tree1 = Tree::new("replica1".to_string());
+//! let a = tree1.create(&NodeId::root(), 0, json!("a")).unwrap();
+//! let b = tree1.create(&NodeId::root(), 1, json!("b")).unwrap();
+//! let mut tree2 = Tree::new("replica2".to_string());
+//! tree2.merge(&tree1);
+//!
+//! // Concurrently move a under b and b under a
+//! tree1.move_node(&a, &b, 0).unwrap();
+//! tree2.move_node(&b, &a, 0).unwrap();
+//!
+//! tree1.merge(&tree2);
+//! tree2.merge(&tree1);
+//!
+//! // One of the moves is skipped: no cycle
+//! assert_eq!(tree1.children(&NodeId::root()), tree2.children(&NodeId::root()));
+//! assert_eq!(tree1.children(&NodeId::root()).len(), 1);
+//! ```
+
+use super::fractional_index::FractionalIndex;
+use crate::error::{Result, SyncError};
+use crate::sync::{LWWField, Timestamp};
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::{BTreeSet, HashMap};
+
+/// Unique ID of a tree node: (creating replica, Lamport clock)
+///
+/// The root and the trash use the empty replica ID, which replicas must
+/// not use.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct NodeId {
+    /// Replica that created the node
+    pub replica_id: ClientID,
+
+    /// Lamport clock of the create
+    pub counter: u64,
+}
+
+impl NodeId {
+    /// Create a new node ID
+    pub fn new(replica_id: ClientID, counter: u64) -> Self {
+        Self {
+            replica_id,
+            counter,
+        }
+    }
+
+    /// The root of every tree
+    pub fn root() -> Self {
+        Self::new(ClientID::new(), 0)
+    }
+
+    /// Hidden parent of deleted nodes
+    fn trash() -> Self {
+        Self::new(ClientID::new(), 1)
+    }
+
+    /// Check if this is the root or the trash
+    fn is_reserved(&self) -> bool {
+        self.replica_id.is_empty()
+    }
+}
+
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.replica_id, self.counter)
+    }
+}
+
+/// A create, move or delete
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Move {
+    /// When the move happened (unique per move)
+    timestamp: Timestamp,
+
+    /// Node being moved
+    node: NodeId,
+
+    /// New parent
+    parent: NodeId,
+
+    /// Position among the new parent's children
+    position: FractionalIndex,
+}
+
+/// Where a node sits: its parent and position among the parent's children
+type Placement = (NodeId, FractionalIndex);
+
+/// Serialized form of a tree: its move log and payloads
+#[derive(Serialize, Deserialize)]
+struct TreeState {
+    replica_id: ClientID,
+    clock: u64,
+    moves: Vec<Move>,
+    values: Vec<(NodeId, LWWField)>,
+}
+
+/// Movable tree CRDT
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "TreeState", into = "TreeState")]
+pub struct Tree {
+    /// Replica identifier
+    replica_id: ClientID,
+
+    /// Lamport clock: highest clock seen so far
+    clock: u64,
+
+    /// Every move, sorted by timestamp
+    moves: Vec<Move>,
+
+    /// Placement of each move's node before the move (to undo it)
+    previous: Vec<Option<Placement>>,
+
+    /// Node payloads
+    values: HashMap<NodeId, LWWField>,
+
+    /// Current parent and position of each node (computed from the moves)
+    parents: HashMap<NodeId, Placement>,
+
+    /// Children of each node sorted by position (ID breaks ties)
+    children: HashMap<NodeId, BTreeSet<(FractionalIndex, NodeId)>>,
+}
+
+impl Tree {
+    /// Create a tree holding only the root
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replica_id` is empty: the empty ID is reserved for the
+    /// root and the trash.
+    pub fn new(replica_id: ClientID) -> Self {
+        assert!(!replica_id.is_empty(), "Tree replica ID must not be empty");
+        Self {
+            replica_id,
+            clock: 0,
+            moves: Vec::new(),
+            previous: Vec::new(),
+            values: HashMap::new(),
+            parents: HashMap::new(),
+            children: HashMap::new(),
+        }
+    }
+
+    /// Create a node at the given index among a parent's children
+    ///
+    /// Returns the new node's ID, or `SyncError::InvalidOperation` if the
+    /// parent doesn't exist or the index is out of bounds.
+    pub fn create(&mut self, parent: &NodeId, index: usize, value: JsonValue) -> Result<NodeId> {
+        self.check_live(parent)?;
+        let position = self.position_at(parent, index, None)?;
+
+        let timestamp = self.tick();
+        let node = NodeId::new(self.replica_id.clone(), timestamp.clock);
+        self.values
+            .insert(node.clone(), LWWField::new(value, timestamp.clone()));
+        self.push_move(Move {
+            timestamp,
+            node: node.clone(),
+            parent: parent.clone(),
+            position,
+        });
+        Ok(node)
+    }
+
+    /// Move a node (with its subtree) to the given index under a new parent
+    ///
+    /// The index counts the parent's other children. Returns
+    /// `SyncError::InvalidOperation` if either node doesn't exist, the node
+    /// is the root, or the parent is inside the node's subtree.
+    pub fn move_node(&mut self, node: &NodeId, parent: &NodeId, index: usize) -> Result<()> {
+        self.check_live(node)?;
+        self.check_live(parent)?;
+        if node.is_reserved() {
+            return Err(SyncError::InvalidOperation(
+                "Cannot move the root".to_string(),
+            ));
+        }
+        if self.is_ancestor(node, parent) {
+            return Err(SyncError::InvalidOperation(format!(
+                "Cannot move {} into its own subtree",
+                node
+            )));
+        }
+
+        let position = self.position_at(parent, index, Some(node))?;
+        let timestamp = self.tick();
+        self.push_move(Move {
+            timestamp,
+            node: node.clone(),
+            parent: parent.clone(),
+            position,
+        });
+        Ok(())
+    }
+
+    /// Delete a node and its subtree
+    ///
+    /// A concurrent move of a descendant out of the subtree keeps that
+    /// descendant. Returns false if the node doesn't exist or is the root.
+    pub fn delete(&mut self, node: &NodeId) -> bool {
+        if node.is_reserved() || !self.contains(node) {
+            return false;
+        }
+
+        let timestamp = self.tick();
+        self.push_move(Move {
+            timestamp,
+            node: node.clone(),
+            parent: NodeId::trash(),
+            position: FractionalIndex::first(),
+        });
+        true
+    }
+
+    /// Replace a node's payload
+    ///
+    /// Returns false if the node doesn't exist or is the root.
+    pub fn set(&mut self, node: &NodeId, value: JsonValue) -> bool {
+        if node.is_reserved() || !self.contains(node) {
+            return false;
+        }
+
+        let timestamp = self.tick();
+        self.values
+            .insert(node.clone(), LWWField::new(value, timestamp));
+        true
+    }
+
+    /// Get a node's payload
+    pub fn get(&self, node: &NodeId) -> Option<&JsonValue> {
+        if !self.contains(node) {
+            return None;
+        }
+        self.values.get(node).map(|field| &field.value)
+    }
+
+    /// Check if a node is in the tree (reachable from the root)
+    pub fn contains(&self, node: &NodeId) -> bool {
+        self.ancestors(node).last() == Some(&NodeId::root())
+    }
+
+    /// Get a node's parent
+    pub fn parent(&self, node: &NodeId) -> Option<&NodeId> {
+        if !self.contains(node) {
+            return None;
+        }
+        self.parents.get(node).map(|(parent, _)| parent)
+    }
+
+    /// Get a node's children in order
+    pub fn children(&self, node: &NodeId) -> Vec<NodeId> {
+        if !self.contains(node) {
+            return Vec::new();
+        }
+        self.children
+            .get(node)
+            .into_iter()
+            .flatten()
+            .map(|(_, child)| child.clone())
+            .collect()
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Merge another tree's state into this one
+    ///
+    /// Takes the union of both move logs. Only the moves from the earliest
+    /// new one on are undone and redone.
+    /// This operation is commutative, associative, and idempotent.
+    pub fn merge(&mut self, other: &Tree) {
+        self.clock = self.clock.max(other.clock);
+
+        for (node, remote) in &other.values {
+            let value = match self.values.get(node) {
+                Some(local) => local.merge(remote),
+                None => remote.clone(),
+            };
+            self.values.insert(node.clone(), value);
+        }
+
+        // Both logs are sorted: find where the earliest new move goes
+        let Some((remote_start, start)) = other.moves.iter().enumerate().find_map(|(i, mv)| {
+            self.moves
+                .binary_search_by(|local| local.timestamp.cmp(&mv.timestamp))
+                .err()
+                .map(|start| (i, start))
+        }) else {
+            return;
+        };
+
+        self.undo_from(start);
+        let mut local = self.moves.split_off(start).into_iter().peekable();
+        let mut remote = other.moves[remote_start..].iter().peekable();
+        loop {
+            let next = match (local.peek(), remote.peek()) {
+                (Some(l), Some(r)) => match l.timestamp.cmp(&r.timestamp) {
+                    std::cmp::Ordering::Less => local.next(),
+                    std::cmp::Ordering::Greater => remote.next().cloned(),
+                    std::cmp::Ordering::Equal => {
+                        remote.next();
+                        local.next()
+                    }
+                },
+                (Some(_), None) => local.next(),
+                (None, Some(_)) => remote.next().cloned(),
+                (None, None) => break,
+            };
+            self.push_move(next.expect("One of the logs has moves left"));
+        }
+    }
+
+    /// Advance the Lamport clock and get a timestamp for a local change
+    fn tick(&mut self) -> Timestamp {
+        self.clock += 1;
+        Timestamp::new(self.clock, self.replica_id.clone())
+    }
+
+    /// Apply a move and append it to the log; it must be the newest
+    fn push_move(&mut self, mv: Move) {
+        self.previous.push(self.parents.get(&mv.node).cloned());
+        self.apply(&mv);
+        self.moves.push(mv);
+    }
+
+    /// Recompute the tree from the move log
+    fn replay(&mut self) {
+        self.parents.clear();
+        self.children.clear();
+        self.previous.clear();
+        for mv in std::mem::take(&mut self.moves) {
+            self.push_move(mv);
+        }
+    }
+
+    /// Revert the moves from an index on, newest first
+    ///
+    /// The moves stay in the log; their undo records are dropped.
+    fn undo_from(&mut self, start: usize) {
+        for index in (start..self.moves.len()).rev() {
+            let node = self.moves[index].node.clone();
+            let previous = self.previous.pop().expect("Every move has an undo record");
+            self.place(&node, previous);
+        }
+    }
+
+    /// Apply a move unless it would create a cycle
+    fn apply(&mut self, mv: &Move) {
+        if mv.node.is_reserved() || mv.node == mv.parent || self.is_ancestor(&mv.node, &mv.parent) {
+            return;
+        }
+        self.place(&mv.node, Some((mv.parent.clone(), mv.position.clone())));
+    }
+
+    /// Put a node under a parent, or detach it
+    fn place(&mut self, node: &NodeId, placement: Option<Placement>) {
+        if let Some((parent, position)) = self.parents.remove(node) {
+            if let Some(siblings) = self.children.get_mut(&parent) {
+                siblings.remove(&(position, node.clone()));
+                if siblings.is_empty() {
+                    self.children.remove(&parent);
+                }
+            }
+        }
+        if let Some((parent, position)) = placement {
+            self.children
+                .entry(parent.clone())
+                .or_default()
+                .insert((position.clone(), node.clone()));
+            self.parents.insert(node.clone(), (parent, position));
+        }
+    }
+
+    /// Walk up from a node: the node, its parent, and so on
+    fn ancestors(&self, node: &NodeId) -> Vec<NodeId> {
+        let mut path = vec![node.clone()];
+        let mut current = node;
+        while let Some((parent, _)) = self.parents.get(current) {
+            path.push(parent.clone());
+            current = parent;
+        }
+        path
+    }
+
+    /// Check if `ancestor` is `node` or above it
+    fn is_ancestor(&self, ancestor: &NodeId, node: &NodeId) -> bool {
+        self.ancestors(node).contains(ancestor)
+    }
+
+    /// Fail unless a node is in the tree
+    fn check_live(&self, node: &NodeId) -> Result<()> {
+        if self.contains(node) {
+            Ok(())
+        } else {
+            Err(SyncError::InvalidOperation(format!(
+                "No tree node {}",
+                node
+            )))
+        }
+    }
+
+    /// Generate a position at the given index among a parent's children,
+    /// skipping `moving`
+    fn position_at(
+        &self,
+        parent: &NodeId,
+        index: usize,
+        moving: Option<&NodeId>,
+    ) -> Result<FractionalIndex> {
+        let siblings: Vec<&FractionalIndex> = self
+            .children
+            .get(parent)
+            .into_iter()
+            .flatten()
+            .filter(|(_, id)| Some(id) != moving)
+            .map(|(position, _)| position)
+            .collect();
+        if index > siblings.len() {
+            return Err(SyncError::InvalidOperation(format!(
+                "Child index {} out of bounds (len {})",
+                index,
+                siblings.len()
+            )));
+        }
+
+        let left = index.checked_sub(1).map(|i| siblings[i]);
+        let right = siblings.get(index).copied();
+        Ok(FractionalIndex::between_unique(
+            left,
+            right,
+            &self.replica_id,
+        ))
+    }
+}
+
+impl TryFrom<TreeState> for Tree {
+    type Error = SyncError;
+
+    fn try_from(state: TreeState) -> Result<Self> {
+        if state.replica_id.is_empty() {
+            return Err(SyncError::InvalidOperation(
+                "Tree replica ID must not be empty".to_string(),
+            ));
+        }
+
+        let mut tree = Tree::new(state.replica_id);
+        tree.clock = state.clock;
+        tree.moves = state.moves;
+        tree.moves.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        tree.values = state.values.into_iter().collect();
+        tree.replay();
+        Ok(tree)
+    }
+}
+
+impl From<Tree> for TreeState {
+    fn from(tree: Tree) -> Self {
+        let mut values: Vec<(NodeId, LWWField)> = tree.values.into_iter().collect();
+        values.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            replica_id: tree.replica_id,
+            clock: tree.clock,
+            moves: tree.moves,
+            values,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn root() -> NodeId {
+        NodeId::root()
+    }
+
+    #[test]
+    fn test_create_children_in_order() {
+        let mut tree = Tree::new("replica1".to_string());
+        let b = tree.create(&root(), 0, json!("b")).unwrap();
+        let a = tree.create(&root(), 0, json!("a")).unwrap();
+        let c = tree.create(&root(), 2, json!("c")).unwrap();
+        let child = tree.create(&a, 0, json!("child")).unwrap();
+
+        assert_eq!(tree.children(&root()), vec![a.clone(), b, c]);
+        assert_eq!(tree.children(&a), vec![child.clone()]);
+        assert_eq!(tree.parent(&child), Some(&a));
+        assert_eq!(tree.get(&child), Some(&json!("child")));
+        assert!(tree.create(&root(), 9, json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_move_and_reorder() {
+        let mut tree = Tree::new("replica1".to_string());
+        let a = tree.create(&root(), 0, json!("a")).unwrap();
+        let b = tree.create(&root(), 1, json!("b")).unwrap();
+        let c = tree.create(&root(), 2, json!("c")).unwrap();
+
+        tree.move_node(&a, &root(), 2).unwrap();
+        assert_eq!(
+            tree.children(&root()),
+            vec![b.clone(), c.clone(), a.clone()]
+        );
+
+        tree.move_node(&c, &b, 0).unwrap();
+        assert_eq!(tree.children(&root()), vec![b.clone(), a.clone()]);
+        assert_eq!(tree.children(&b), vec![c.clone()]);
+    }
+
+    #[test]
+    fn test_local_cycle_is_rejected() {
+        let mut tree = Tree::new("replica1".to_string());
+        let a = tree.create(&root(), 0, json!("a")).unwrap();
+        let b = tree.create(&a, 0, json!("b")).unwrap();
+
+        assert!(tree.move_node(&a, &b, 0).is_err());
+        assert!(tree.move_node(&a, &a, 0).is_err());
+        assert!(tree.move_node(&root(), &a, 0).is_err());
+    }
+
+    #[test]
+    fn test_delete_removes_subtree() {
+        let mut tree = Tree::new("replica1".to_string());
+        let a = tree.create(&root(), 0, json!("a")).unwrap();
+        let b = tree.create(&a, 0, json!("b")).unwrap();
+
+        assert!(tree.delete(&a));
+        assert!(!tree.delete(&a));
+        assert!(!tree.delete(&root()));
+
+        assert!(tree.children(&root()).is_empty());
+        assert!(!tree.contains(&b));
+        assert_eq!(tree.get(&b), None);
+        assert!(tree.create(&b, 0, json!("x")).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_moves_never_cycle() {
+        let mut tree1 = Tree::new("replica1".to_string());
+        let a = tree1.create(&root(), 0, json!("a")).unwrap();
+        let b = tree1.create(&root(), 1, json!("b")).unwrap();
+        let mut tree2 = Tree::new("replica2".to_string());
+        tree2.merge(&tree1);
+
+        tree1.move_node(&a, &b, 0).unwrap();
+        tree2.move_node(&b, &a, 0).unwrap();
+
+        tree1.merge(&tree2);
+        tree2.merge(&tree1);
+
+        // Same clock: replica2's move is later and would close a cycle
+        assert_eq!(tree1.children(&root()), vec![b.clone()]);
+        assert_eq!(tree1.children(&b), vec![a.clone()]);
+        assert_eq!(tree2.children(&root()), vec![b.clone()]);
+        assert_eq!(tree2.children(&b), vec![a]);
+    }
+
+    #[test]
+    fn test_concurrent_move_out_of_deleted_subtree() {
+        let mut tree1 = Tree::new("replica1".to_string());
+        let a = tree1.create(&root(), 0, json!("a")).unwrap();
+        let b = tree1.create(&a, 0, json!("b")).unwrap();
+        let mut tree2 = Tree::new("replica2".to_string());
+        tree2.merge(&tree1);
+
+        tree1.delete(&a);
+        tree2.move_node(&b, &root(), 0).unwrap();
+
+        tree1.merge(&tree2);
+        tree2.merge(&tree1);
+
+        assert_eq!(tree1.children(&root()), vec![b.clone()]);
+        assert_eq!(tree2.children(&root()), vec![b]);
+        assert!(!tree1.contains(&a));
+    }
+
+    #[test]
+    #[should_panic(expected = "Tree replica ID must not be empty")]
+    fn test_empty_replica_id_panics() {
+        Tree::new(String::new());
+    }
+
+    #[test]
+    fn test_merge_redoes_only_newer_moves() {
+        let mut tree1 = Tree::new("replica1".to_string());
+        let a = tree1.create(&root(), 0, json!("a")).unwrap();
+        let b = tree1.create(&root(), 1, json!("b")).unwrap();
+        let mut tree2 = Tree::new("replica2".to_string());
+        tree2.merge(&tree1);
+
+        // An older remote move lands before newer local ones
+        tree2.move_node(&a, &b, 0).unwrap();
+        tree1.move_node(&b, &root(), 0).unwrap();
+        tree1.move_node(&b, &a, 0).unwrap();
+        tree1.merge(&tree2);
+
+        let mut replayed = tree1.clone();
+        replayed.replay();
+        assert_eq!(tree1, replayed);
+
+        // The remote move comes first, so moving b under a would cycle
+        assert_eq!(tree1.children(&root()), vec![b.clone()]);
+        assert_eq!(tree1.children(&b), vec![a]);
+    }
+
+    #[test]
+    fn test_serialization_replays_moves() {
+        let mut tree = Tree::new("replica1".to_string());
+        let a = tree.create(&root(), 0, json!({ "name": "docs" })).unwrap();
+        let b = tree.create(&root(), 1, json!({ "name": "src" })).unwrap();
+        tree.move_node(&b, &a, 0).unwrap();
+
+        let json = serde_json::to_string(&tree).unwrap();
+        let decoded: Tree = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded, tree);
+        assert_eq!(decoded.children(&a), vec![b]);
+    }
+}
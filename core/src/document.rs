@@ -8,16 +8,43 @@
 //! - Determinism: Same inputs always produce same output
 //! - Idempotence: Applying operation twice has no effect
 //! - Commutativity: Order of merges doesn't matter
+//!
+//! # Nested paths
+//!
+//! Field paths may address values inside nested objects (see `crate::path`).
+//! A write replaces the whole subtree at its path, so writes and deletes at
+//! a parent and a child resolve by timestamp: whichever is newer wins, and
+//! an older child write under a newer parent write or delete is discarded.
+//! A newer child write is applied on top of an older parent value.
+//!
+//! # Clocks
+//!
+//! `set_field` and `delete_field` take a caller-supplied clock. A document
+//! created with `with_replica` instead carries a hybrid logical clock
+//! (`crate::sync::HybridClock`): `set` and `delete` stamp writes with it and
+//! tick the document version, and every merged timestamp advances it.
+//!
+//! # Merge policies
+//!
+//! Fields merge by LWW unless a policy is registered for their path (see
+//! `crate::sync::policy`). Every way of merging into a document, including
+//! `merge`, `crate::sync::apply_delta` and protocol deltas, goes through
+//! `merge_field` and so uses the same policy.
 
-use crate::sync::{Timestamp, VectorClock};
+use crate::error::{Result, SyncError};
+use crate::path;
+use crate::sync::policy::{counter_add, counter_value};
+use crate::sync::{HybridClock, MergePolicies, MergePolicy, Timestamp, VectorClock};
 use crate::{ClientID, DocumentID, FieldPath};
-// TODO: Will be used when implementing full error handling
-// use crate::error::{Result, SyncError};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
 
 /// A document with field-level LWW conflict resolution
+///
+/// The clock and merge policies are replica-local: they are neither
+/// serialized nor compared. Re-attach a clock with `set_replica` (and
+/// register policies again) after loading a document.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Document {
     /// Unique document identifier
@@ -26,8 +53,33 @@ pub struct Document {
     /// Document fields with LWW metadata
     pub fields: HashMap<FieldPath, Field>,
 
+    /// Deletion timestamps for removed fields
+    ///
+    /// Tombstones take part in LWW exactly like values, so a delete is only
+    /// undone by a write with a newer timestamp.
+    #[serde(default)]
+    pub tombstones: HashMap<FieldPath, Timestamp>,
+
     /// Vector clock for causality tracking
     pub version: VectorClock,
+
+    /// Clock for local writes (replica-local, not merged or serialized)
+    #[serde(skip)]
+    pub clock: Option<HybridClock>,
+
+    /// Merge policies per field path (replica-local, not serialized)
+    #[serde(skip)]
+    pub policies: MergePolicies,
+}
+
+impl PartialEq for Document {
+    /// Compare the replicated state, ignoring the clock and merge policies
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.fields == other.fields
+            && self.tombstones == other.tombstones
+            && self.version == other.version
+    }
 }
 
 /// A single field with LWW metadata
@@ -40,13 +92,241 @@ pub struct Field {
     pub timestamp: Timestamp,
 }
 
+/// Conflicts resolved by `Document::merge_with_report`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeReport {
+    /// Number of fields updated or deleted, as returned by `merge`
+    pub updated: usize,
+
+    /// One entry per path where the two sides disagreed
+    pub conflicts: Vec<FieldConflict>,
+}
+
+impl MergeReport {
+    /// Check if no conflicts were resolved
+    pub fn is_empty(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Iterate over conflicts where a local value was overwritten
+    pub fn overwritten(&self) -> impl Iterator<Item = &FieldConflict> {
+        self.conflicts.iter().filter(|conflict| conflict.local_lost)
+    }
+}
+
+/// A path where the local and remote documents held different values
+///
+/// A `None` value stands for a delete.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldConflict {
+    /// Path of the field
+    pub field_path: FieldPath,
+
+    /// Value that was discarded
+    pub losing_value: Option<JsonValue>,
+
+    /// Timestamp of the discarded write or delete
+    pub losing_timestamp: Timestamp,
+
+    /// Value that was kept (combined, if a merge policy applies)
+    pub winning_value: Option<JsonValue>,
+
+    /// Timestamp of the kept write or delete
+    pub winning_timestamp: Timestamp,
+
+    /// True if the local side lost, false if the remote side did
+    pub local_lost: bool,
+
+    /// True if the two documents' versions were concurrent when merged
+    ///
+    /// This is document-level, per `VectorClock::is_concurrent` on the
+    /// whole version vectors: neither replica had seen all of the other's
+    /// writes. It is the same for every conflict of one merge and doesn't
+    /// tell whether the writer of the winning value saw this losing one.
+    pub concurrent: bool,
+}
+
+/// Value (None for a delete) and timestamp of a write or delete
+type Entry = (Option<JsonValue>, Timestamp);
+
+/// Local entries a remote write or delete at a path may conflict with
+struct LocalEntries {
+    /// Entry at exactly the path
+    exact: Option<Entry>,
+
+    /// Nearest ancestor entry, if there is none at the path
+    ancestor: Option<(FieldPath, Entry)>,
+
+    /// Entries below the path
+    below: Vec<(FieldPath, Entry)>,
+}
+
+impl FieldConflict {
+    /// Build the conflict between a local and a remote entry, if any
+    ///
+    /// `kept` is the entry stored after the merge if the local one was
+    /// replaced (by the remote one, or both combined by a merge policy).
+    /// Entries with the same timestamp or the same value don't conflict,
+    /// nor does a newer remote write that lost to a write at an ancestor.
+    fn resolve(
+        field_path: FieldPath,
+        local: Entry,
+        remote: Entry,
+        kept: Option<Entry>,
+        concurrent: bool,
+    ) -> Option<Self> {
+        if local.1 == remote.1 || local.0 == remote.0 {
+            return None;
+        }
+
+        let local_lost = kept.is_some();
+        let (loser, winner) = match kept {
+            Some(kept) => (local, kept),
+            None => (remote, local),
+        };
+        if loser.1.is_newer_than(&winner.1) {
+            return None;
+        }
+        Some(Self {
+            field_path,
+            losing_value: loser.0,
+            losing_timestamp: loser.1,
+            winning_value: winner.0,
+            winning_timestamp: winner.1,
+            local_lost,
+            concurrent,
+        })
+    }
+}
+
 impl Document {
     /// Create a new empty document
     pub fn new(id: DocumentID) -> Self {
         Self {
             id,
             fields: HashMap::new(),
+            tombstones: HashMap::new(),
             version: VectorClock::new(),
+            clock: None,
+            policies: MergePolicies::new(),
+        }
+    }
+
+    /// Create a new empty document with a hybrid logical clock for a replica
+    pub fn with_replica(id: DocumentID, replica_id: ClientID) -> Self {
+        let mut document = Self::new(id);
+        document.set_replica(replica_id);
+        document
+    }
+
+    /// Attach a hybrid logical clock for a replica
+    ///
+    /// Use this for documents loaded from storage. The clock starts after
+    /// every timestamp already in the document.
+    pub fn set_replica(&mut self, replica_id: ClientID) {
+        let mut clock = HybridClock::new(replica_id);
+        for timestamp in self
+            .fields
+            .values()
+            .map(|field| &field.timestamp)
+            .chain(self.tombstones.values())
+        {
+            clock.observe(timestamp);
+        }
+        self.clock = Some(clock);
+    }
+
+    /// Get the replica ID of the document's clock (if any)
+    pub fn replica_id(&self) -> Option<&ClientID> {
+        self.clock.as_ref().map(HybridClock::replica_id)
+    }
+
+    /// Register a merge policy for a field path or glob
+    ///
+    /// Replaces a policy registered for the same pattern. See
+    /// `crate::sync::policy` for the glob syntax.
+    pub fn set_merge_policy(&mut self, pattern: &str, policy: MergePolicy) {
+        self.policies.set(pattern, policy);
+    }
+
+    /// Get the merge policy that applies to a field path
+    pub fn merge_policy(&self, field_path: &str) -> &MergePolicy {
+        self.policies.policy_for(field_path)
+    }
+
+    /// Set a field value stamped by the document's clock
+    ///
+    /// Ticks the document version for the replica and returns the
+    /// timestamp of the write.
+    pub fn set(&mut self, field_path: FieldPath, value: JsonValue) -> Result<Timestamp> {
+        let timestamp = self.next_timestamp()?;
+        self.merge_field(
+            field_path,
+            Field {
+                value,
+                timestamp: timestamp.clone(),
+            },
+        );
+        Ok(timestamp)
+    }
+
+    /// Delete a field, stamped by the document's clock
+    ///
+    /// Ticks the document version for the replica and returns the
+    /// timestamp of the delete.
+    pub fn delete(&mut self, field_path: &FieldPath) -> Result<Timestamp> {
+        let timestamp = self.next_timestamp()?;
+        self.merge_tombstone(field_path.clone(), timestamp.clone());
+        Ok(timestamp)
+    }
+
+    /// Add an amount to a counter field, stamped by the document's clock
+    ///
+    /// The field should use the `Counter` merge policy so that concurrent
+    /// increments add up. Returns the timestamp of the write, or
+    /// `SyncError::InvalidOperation` if the field holds something other
+    /// than a counter or the replica's total would overflow.
+    pub fn increment(&mut self, field_path: &FieldPath, amount: i64) -> Result<Timestamp> {
+        let replica_id = self.replica_clock()?.replica_id().clone();
+        let current = self.get_field(field_path).unwrap_or(&JsonValue::Null);
+        let value = counter_add(current, &replica_id, amount).ok_or_else(|| {
+            SyncError::InvalidOperation(format!(
+                "Cannot add {} to counter field {}",
+                amount, field_path
+            ))
+        })?;
+        self.set(field_path.clone(), value)
+    }
+
+    /// Get the value of a counter field
+    ///
+    /// Returns `None` if the field is unset or not a counter.
+    pub fn counter_value(&self, field_path: &FieldPath) -> Option<i64> {
+        self.get_field(field_path).and_then(counter_value)
+    }
+
+    /// Issue a timestamp for a local write and tick the version
+    fn next_timestamp(&mut self) -> Result<Timestamp> {
+        let timestamp = self.replica_clock()?.now()?;
+        self.version.tick(&timestamp.client_id);
+        Ok(timestamp)
+    }
+
+    /// Get the document's clock, or an error if it has none
+    fn replica_clock(&mut self) -> Result<&mut HybridClock> {
+        let id = &self.id;
+        self.clock.as_mut().ok_or_else(|| {
+            SyncError::InvalidOperation(format!(
+                "Document {} has no replica clock (use with_replica)",
+                id
+            ))
+        })
+    }
+
+    /// Advance the document's clock past a timestamp (if it has one)
+    fn observe(&mut self, timestamp: &Timestamp) {
+        if let Some(clock) = &mut self.clock {
+            clock.observe(timestamp);
         }
     }
 
@@ -68,9 +348,49 @@ impl Document {
         self.merge_field(field_path, new_field);
     }
 
-    /// Get a field value
+    /// Get the value written at exactly this path
+    ///
+    /// Writes to nested paths are not reflected here; use `get_path` to read
+    /// the merged value of a subtree.
     pub fn get_field(&self, field_path: &FieldPath) -> Option<&JsonValue> {
-        self.fields.get(field_path).map(|f| &f.value)
+        self.fields
+            .get(&path::normalize(field_path))
+            .map(|f| &f.value)
+    }
+
+    /// Get the timestamp of the write or delete recorded at exactly this path
+    pub fn timestamp_of(&self, field_path: &str) -> Option<&Timestamp> {
+        let field_path = path::normalize(field_path);
+        self.fields
+            .get(&field_path)
+            .map(|field| &field.timestamp)
+            .or_else(|| self.tombstones.get(&field_path))
+    }
+
+    /// Get the merged value at a path (dotted or JSON Pointer)
+    ///
+    /// Returns the subtree with all nested writes applied, or `None` if
+    /// nothing exists at the path. The empty path returns the whole document.
+    pub fn get_path(&self, field_path: &str) -> Option<JsonValue> {
+        let target = path::normalize(field_path);
+
+        // Only the path itself, its ancestors and its descendants matter
+        let related = |candidate: &FieldPath| {
+            *candidate == target
+                || path::is_descendant(candidate, &target)
+                || path::is_descendant(&target, candidate)
+        };
+
+        let root = self.materialize(related);
+        path::parse(&target)
+            .iter()
+            .try_fold(&root, |value, segment| value.get(segment))
+            .cloned()
+    }
+
+    /// Start watching the subtree at a path for changes
+    pub fn watch(&self, field_path: &str) -> SubtreeWatcher {
+        SubtreeWatcher::new(self, field_path)
     }
 
     /// Merge a remote field using LWW algorithm
@@ -78,11 +398,49 @@ impl Document {
     /// This is the core LWW merge algorithm verified by TLA+.
     /// Returns true if the local field was updated.
     ///
+    /// If a merge policy other than LWW applies to the path and both values
+    /// fit it, the values are combined instead and the newer timestamp kept.
+    ///
     /// Comparison order:
     /// 1. Higher timestamp wins
     /// 2. If timestamps equal, higher client_id wins
     /// 3. If both equal (duplicate), use value comparison for determinism
+    ///
+    /// A tombstone for the field beats any write that isn't strictly newer.
+    /// So does a write or delete at an ancestor path; accepting a write
+    /// discards older writes and deletes below it.
     pub fn merge_field(&mut self, field_path: FieldPath, remote_field: Field) -> bool {
+        self.observe(&remote_field.timestamp);
+
+        let field_path = path::normalize(&field_path);
+        if self.is_shadowed(&field_path, &remote_field.timestamp) {
+            return false;
+        }
+
+        let timestamp = remote_field.timestamp.clone();
+        let updated = self.merge_exact_field(field_path.clone(), remote_field);
+        if updated {
+            self.prune_descendants(&field_path, &timestamp);
+        }
+        updated
+    }
+
+    /// Merge of a field against the entry at the same path only
+    fn merge_exact_field(&mut self, field_path: FieldPath, remote_field: Field) -> bool {
+        if let Some(deleted_at) = self.tombstones.get(&field_path) {
+            if !remote_field.timestamp.is_newer_than(deleted_at) {
+                // Deleted after (or exactly when) this value was written
+                return false;
+            }
+            self.tombstones.remove(&field_path);
+        }
+
+        if let Some(merged) = self.combine_field(&field_path, &remote_field) {
+            let updated = self.fields.get(&field_path) != Some(&merged);
+            self.fields.insert(field_path, merged);
+            return updated;
+        }
+
         match self.fields.get(&field_path) {
             Some(local_field) => {
                 // Compare timestamps for LWW
@@ -122,18 +480,101 @@ impl Document {
         }
     }
 
+    /// Combine a remote field with the local one by the path's merge policy
+    ///
+    /// Returns `None` if there is no local field or the policy is LWW.
+    fn combine_field(&self, field_path: &FieldPath, remote_field: &Field) -> Option<Field> {
+        let local_field = self.fields.get(field_path)?;
+        let value = self
+            .policies
+            .policy_for(field_path)
+            .combine(&local_field.value, &remote_field.value)?;
+        let timestamp = if remote_field.timestamp.is_newer_than(&local_field.timestamp) {
+            remote_field.timestamp.clone()
+        } else {
+            local_field.timestamp.clone()
+        };
+        Some(Field { value, timestamp })
+    }
+
     /// Merge an entire remote document
     ///
-    /// Merges all fields and vector clocks.
-    /// Returns the number of fields updated.
+    /// Merges all fields, tombstones and vector clocks.
+    /// Returns the number of fields updated or deleted.
     pub fn merge(&mut self, remote: &Document) -> usize {
+        self.merge_inner(remote, None)
+    }
+
+    /// Merge an entire remote document and report the conflicts it resolved
+    ///
+    /// Same as `merge`, but also lists every path where the two documents
+    /// held different values (or a value and a delete), with the side that
+    /// lost. That includes values discarded by a write or delete at an
+    /// ancestor path. See `MergeReport`.
+    pub fn merge_with_report(&mut self, remote: &Document) -> MergeReport {
+        let mut conflicts = Vec::new();
+        let updated = self.merge_inner(remote, Some(&mut conflicts));
+        MergeReport { updated, conflicts }
+    }
+
+    /// Merge a remote document, recording conflicts if asked to
+    fn merge_inner(
+        &mut self,
+        remote: &Document,
+        mut conflicts: Option<&mut Vec<FieldConflict>>,
+    ) -> usize {
+        let concurrent = self.version.is_concurrent(&remote.version);
         let mut updated_count = 0;
 
+        // Conflicts are found against the local state before the merge
+        let before = conflicts.is_some().then(|| self.clone());
+
         // Merge each remote field
         for (field_path, remote_field) in &remote.fields {
-            if self.merge_field(field_path.clone(), remote_field.clone()) {
+            let field_path = path::normalize(field_path);
+            let remote_entry = (
+                Some(remote_field.value.clone()),
+                remote_field.timestamp.clone(),
+            );
+            let local = before
+                .as_ref()
+                .map(|before| before.local_entries(&field_path));
+            let updated = self.merge_field(field_path.clone(), remote_field.clone());
+            if updated {
+                updated_count += 1;
+            }
+            if let (Some(conflicts), Some(local)) = (conflicts.as_deref_mut(), local) {
+                conflicts.extend(self.conflicts_at(
+                    remote,
+                    field_path,
+                    local,
+                    remote_entry,
+                    updated,
+                    concurrent,
+                ));
+            }
+        }
+
+        // Merge each remote deletion
+        for (field_path, deleted_at) in &remote.tombstones {
+            let field_path = path::normalize(field_path);
+            let local = before
+                .as_ref()
+                .map(|before| before.local_entries(&field_path));
+            let updated = self.merge_tombstone(field_path.clone(), deleted_at.clone());
+            if updated {
                 updated_count += 1;
             }
+            if let (Some(conflicts), Some(local)) = (conflicts.as_deref_mut(), local) {
+                conflicts.extend(self.conflicts_at(
+                    remote,
+                    field_path,
+                    local,
+                    (None, deleted_at.clone()),
+                    updated,
+                    concurrent,
+                ));
+            }
         }
 
         // Merge vector clocks
@@ -142,15 +583,151 @@ impl Document {
         updated_count
     }
 
+    /// Get the value (None for a delete) and timestamp stored at exactly a path
+    fn entry_at(&self, field_path: &FieldPath) -> Option<Entry> {
+        match self.fields.get(field_path) {
+            Some(field) => Some((Some(field.value.clone()), field.timestamp.clone())),
+            None => self
+                .tombstones
+                .get(field_path)
+                .map(|deleted_at| (None, deleted_at.clone())),
+        }
+    }
+
+    /// Collect the local entries a remote entry at a path may conflict with
+    fn local_entries(&self, field_path: &FieldPath) -> LocalEntries {
+        let exact = self.entry_at(field_path);
+        // Ancestors further down are newer, or they would have been pruned
+        let ancestor = exact
+            .is_none()
+            .then(|| {
+                path::ancestors(field_path)
+                    .into_iter()
+                    .rev()
+                    .find_map(|ancestor| Some((self.entry_at(&ancestor)?, ancestor)))
+            })
+            .flatten()
+            .map(|(entry, ancestor)| (ancestor, entry));
+
+        let below = self
+            .fields
+            .keys()
+            .chain(self.tombstones.keys())
+            .filter(|candidate| path::is_descendant(field_path, candidate))
+            .filter_map(|candidate| Some((candidate.clone(), self.entry_at(candidate)?)))
+            .collect();
+
+        LocalEntries {
+            exact,
+            ancestor,
+            below,
+        }
+    }
+
+    /// Conflicts resolved by merging a remote entry at a path
+    ///
+    /// Besides the entry at the path itself, covers local entries below it
+    /// that a winning remote write or delete discarded, and the part of a
+    /// local ancestor's write or delete at the path, whichever side won.
+    /// A discarded local entry is reported once, for the deepest remote
+    /// entry above (or at) it.
+    fn conflicts_at(
+        &self,
+        remote_document: &Document,
+        field_path: FieldPath,
+        local: LocalEntries,
+        remote: Entry,
+        updated: bool,
+        concurrent: bool,
+    ) -> Vec<FieldConflict> {
+        let mut conflicts = Vec::new();
+
+        if updated {
+            for (below, entry) in local.below {
+                let covered = remote_document
+                    .fields
+                    .keys()
+                    .chain(remote_document.tombstones.keys())
+                    .any(|other| {
+                        path::is_descendant(&field_path, other)
+                            && (*other == below || path::is_descendant(other, &below))
+                    });
+                if covered || self.entry_at(&below).as_ref() == Some(&entry) {
+                    continue;
+                }
+                let winner = (
+                    value_below(&remote.0, &field_path, &below),
+                    remote.1.clone(),
+                );
+                conflicts.extend(FieldConflict::resolve(
+                    below,
+                    entry,
+                    winner.clone(),
+                    Some(winner),
+                    concurrent,
+                ));
+            }
+        }
+
+        let local_entry = match (local.exact, local.ancestor) {
+            (Some(exact), _) => exact,
+            (None, Some((ancestor, (value, timestamp)))) => {
+                let below = value_below(&value, &ancestor, &field_path);
+                if updated && value.is_some() && below.is_none() {
+                    // Written next to the ancestor's value, not over it
+                    return conflicts;
+                }
+                (below, timestamp)
+            }
+            (None, None) => return conflicts,
+        };
+        let kept = updated.then(|| self.entry_at(&field_path)).flatten();
+        conflicts.extend(FieldConflict::resolve(
+            field_path,
+            local_entry,
+            remote,
+            kept,
+            concurrent,
+        ));
+        conflicts
+    }
+
     /// Convert document to JSON for serialization
+    ///
+    /// Nested paths become nested objects.
     pub fn to_json(&self) -> JsonValue {
-        let mut obj = serde_json::Map::new();
+        self.materialize(|_| true)
+    }
+
+    /// Build the JSON tree from the fields and tombstones selected by `include`
+    ///
+    /// Writes and deletes are replayed oldest first, so the result only
+    /// depends on the set of entries, not on the order they were merged.
+    fn materialize(&self, include: impl Fn(&FieldPath) -> bool) -> JsonValue {
+        let mut entries: Vec<(&FieldPath, &Timestamp, Option<&JsonValue>)> = self
+            .fields
+            .iter()
+            .map(|(field_path, field)| (field_path, &field.timestamp, Some(&field.value)))
+            .chain(
+                self.tombstones
+                    .iter()
+                    .map(|(field_path, deleted_at)| (field_path, deleted_at, None)),
+            )
+            .filter(|(field_path, _, _)| include(field_path))
+            .collect();
+
+        entries.sort_by(|a, b| a.1.compare_lww(b.1).then_with(|| a.0.cmp(b.0)));
 
-        for (field_path, field) in &self.fields {
-            obj.insert(field_path.clone(), field.value.clone());
+        let mut root = JsonValue::Object(serde_json::Map::new());
+        for (field_path, _, value) in entries {
+            let segments = path::parse(field_path);
+            match value {
+                Some(value) => set_at(&mut root, &segments, value.clone()),
+                None => remove_at(&mut root, &segments),
+            }
         }
 
-        JsonValue::Object(obj)
+        root
     }
 
     /// Get all field paths
@@ -183,9 +760,223 @@ impl Document {
         &self.fields
     }
 
-    /// Delete a field
-    pub fn delete_field(&mut self, field_path: &FieldPath) {
-        self.fields.remove(field_path);
+    /// Get all tombstones (deletion timestamps by field path)
+    pub fn tombstones(&self) -> &HashMap<FieldPath, Timestamp> {
+        &self.tombstones
+    }
+
+    /// Get the newest timestamp clock held for each client
+    ///
+    /// Holding a client's write or delete means having merged everything
+    /// that client did before it, so this is the version a replica
+    /// acknowledges to its peers for tombstone garbage collection. Unlike
+    /// `version`, it counts in timestamp clocks.
+    pub fn frontier(&self) -> VectorClock {
+        let mut frontier = VectorClock::new();
+        for timestamp in self
+            .fields
+            .values()
+            .map(|field| &field.timestamp)
+            .chain(self.tombstones.values())
+        {
+            if timestamp.clock > frontier.get(&timestamp.client_id) {
+                frontier.update(&timestamp.client_id, timestamp.clock);
+            }
+        }
+        frontier
+    }
+
+    /// Drop tombstones that every known replica has seen
+    ///
+    /// `stable` is the version all peers have acknowledged (see
+    /// `crate::sync::StabilityTracker` and `frontier`). A delete is stable
+    /// once its client's entry reaches the delete's clock. Tombstones that
+    /// still hide part of an older ancestor value are kept.
+    ///
+    /// Returns the number of tombstones dropped.
+    pub fn collect_garbage(&mut self, stable: &VectorClock) -> usize {
+        let before = self.tombstones.len();
+        let fields = &self.fields;
+        self.tombstones.retain(|field_path, deleted_at| {
+            deleted_at.clock > stable.get(&deleted_at.client_id)
+                || path::ancestors(field_path)
+                    .iter()
+                    .any(|ancestor| fields.contains_key(ancestor))
+        });
+        before - self.tombstones.len()
+    }
+
+    /// Delete a field (creates a tombstone)
+    ///
+    /// This method uses LWW merge logic, so if the field holds a value
+    /// with a newer timestamp, the delete is ignored.
+    pub fn delete_field(&mut self, field_path: &FieldPath, clock: u64, client_id: ClientID) {
+        let deleted_at = Timestamp::new(clock, client_id);
+
+        // Use merge_tombstone to respect LWW semantics
+        self.merge_tombstone(field_path.clone(), deleted_at);
+    }
+
+    /// Merge a remote deletion using LWW algorithm
+    ///
+    /// Returns true if the local state changed (field removed or a newer
+    /// tombstone recorded). On an exact timestamp tie with a value, the
+    /// tombstone wins so that all replicas agree.
+    ///
+    /// Deleting a path deletes its whole subtree: older writes and deletes
+    /// below it are discarded.
+    pub fn merge_tombstone(&mut self, field_path: FieldPath, deleted_at: Timestamp) -> bool {
+        self.observe(&deleted_at);
+
+        let field_path = path::normalize(&field_path);
+        if self.is_shadowed(&field_path, &deleted_at) {
+            return false;
+        }
+
+        if let Some(existing) = self.tombstones.get(&field_path) {
+            if !deleted_at.is_newer_than(existing) {
+                return false;
+            }
+        }
+
+        if let Some(local_field) = self.fields.get(&field_path) {
+            if local_field.timestamp.is_newer_than(&deleted_at) {
+                // Value was written after the delete
+                return false;
+            }
+            self.fields.remove(&field_path);
+        }
+
+        self.prune_descendants(&field_path, &deleted_at);
+        self.tombstones.insert(field_path, deleted_at);
+        true
+    }
+
+    /// Check if a write or delete at an ancestor path is at least as new
+    fn is_shadowed(&self, field_path: &FieldPath, timestamp: &Timestamp) -> bool {
+        path::ancestors(field_path).iter().any(|ancestor| {
+            let ancestor_timestamp = self
+                .fields
+                .get(ancestor)
+                .map(|field| &field.timestamp)
+                .or_else(|| self.tombstones.get(ancestor));
+
+            ancestor_timestamp
+                .is_some_and(|ancestor_timestamp| !timestamp.is_newer_than(ancestor_timestamp))
+        })
+    }
+
+    /// Drop writes and deletes below a path that are not newer than `timestamp`
+    fn prune_descendants(&mut self, field_path: &FieldPath, timestamp: &Timestamp) {
+        self.fields.retain(|candidate, field| {
+            !path::is_descendant(field_path, candidate) || field.timestamp.is_newer_than(timestamp)
+        });
+        self.tombstones.retain(|candidate, deleted_at| {
+            !path::is_descendant(field_path, candidate) || deleted_at.is_newer_than(timestamp)
+        });
+    }
+}
+
+/// Get the part of a value written at `ancestor` that lies at `field_path`
+///
+/// None if the value is a delete or has nothing at that path.
+fn value_below(
+    value: &Option<JsonValue>,
+    ancestor: &FieldPath,
+    field_path: &FieldPath,
+) -> Option<JsonValue> {
+    let segments = path::parse(field_path);
+    let depth = path::parse(ancestor).len();
+    value
+        .as_ref()?
+        .pointer(&path::to_pointer(&segments[depth..]))
+        .cloned()
+}
+
+/// Write a value at a path, replacing non-object values on the way
+fn set_at(root: &mut JsonValue, segments: &[String], value: JsonValue) {
+    let Some((last, parents)) = segments.split_last() else {
+        *root = value;
+        return;
+    };
+
+    let mut current = root;
+    for segment in parents {
+        current = as_object(current)
+            .entry(segment.clone())
+            .or_insert_with(|| JsonValue::Object(serde_json::Map::new()));
+    }
+    as_object(current).insert(last.clone(), value);
+}
+
+/// Remove the value at a path (if it exists)
+fn remove_at(root: &mut JsonValue, segments: &[String]) {
+    let Some((last, parents)) = segments.split_last() else {
+        *root = JsonValue::Object(serde_json::Map::new());
+        return;
+    };
+
+    let parent = parents
+        .iter()
+        .try_fold(root, |value, segment| value.get_mut(segment));
+    if let Some(JsonValue::Object(map)) = parent {
+        map.remove(last);
+    }
+}
+
+/// Get a value as an object, replacing it with an empty one if it isn't
+fn as_object(value: &mut JsonValue) -> &mut serde_json::Map<String, JsonValue> {
+    if !value.is_object() {
+        *value = JsonValue::Object(serde_json::Map::new());
+    }
+    match value {
+        JsonValue::Object(map) => map,
+        _ => unreachable!("value was just replaced with an object"),
+    }
+}
+
+/// Watches the merged value of a subtree for changes
+///
+/// Call `poll` after applying local or remote updates to find out whether
+/// the subtree changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubtreeWatcher {
+    /// Canonical path being watched
+    path: FieldPath,
+
+    /// Value seen at the last poll
+    value: Option<JsonValue>,
+}
+
+impl SubtreeWatcher {
+    /// Start watching a path, remembering its current value
+    pub fn new(document: &Document, field_path: &str) -> Self {
+        Self {
+            path: path::normalize(field_path),
+            value: document.get_path(field_path),
+        }
+    }
+
+    /// Get the watched path (canonical form)
+    pub fn path(&self) -> &FieldPath {
+        &self.path
+    }
+
+    /// Get the value seen at the last poll (`None` if nothing exists there)
+    pub fn value(&self) -> Option<&JsonValue> {
+        self.value.as_ref()
+    }
+
+    /// Check the document for changes since the last poll
+    ///
+    /// Returns true (and remembers the new value) if the subtree changed.
+    pub fn poll(&mut self, document: &Document) -> bool {
+        let value = document.get_path(&self.path);
+        if value == self.value {
+            return false;
+        }
+        self.value = value;
+        true
     }
 }
 
@@ -193,6 +984,7 @@ impl Document {
 mod tests {
     use super::*;
     use serde_json::json;
+    use std::collections::BTreeSet;
 
     #[test]
     fn test_document_creation() {
@@ -353,50 +1145,385 @@ mod tests {
     }
 
     #[test]
-    fn test_document_to_json() {
-        let mut doc = Document::new("doc-123".to_string());
-        doc.set_field(
+    fn test_merge_report_lists_overwritten_local_values() {
+        let mut doc1 = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        let mut doc2 = Document::with_replica("doc-123".to_string(), "client2".to_string());
+        let local_ts = doc1.set("title".to_string(), json!("Local")).unwrap();
+        doc1.set("body".to_string(), json!("Same")).unwrap();
+        let remote_ts = doc2.set("title".to_string(), json!("Remote")).unwrap();
+        doc2.set("body".to_string(), json!("Same")).unwrap();
+        doc2.set("title".to_string(), json!("Remote")).unwrap();
+
+        let report = doc1.merge_with_report(&doc2);
+
+        // Equal values don't conflict
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.field_path, "title");
+        assert_eq!(conflict.losing_value, Some(json!("Local")));
+        assert_eq!(conflict.losing_timestamp, local_ts);
+        assert_eq!(conflict.winning_value, Some(json!("Remote")));
+        assert!(conflict.winning_timestamp.is_newer_than(&remote_ts));
+        assert!(conflict.local_lost);
+        assert!(conflict.concurrent);
+        assert_eq!(report.overwritten().count(), 1);
+    }
+
+    #[test]
+    fn test_merge_report_lists_discarded_remote_values() {
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field(
             "title".to_string(),
-            json!("Hello"),
-            1,
+            json!("Local"),
+            5,
             "client1".to_string(),
         );
-        doc.set_field("count".to_string(), json!(42), 1, "client1".to_string());
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field(
+            "title".to_string(),
+            json!("Remote"),
+            2,
+            "client2".to_string(),
+        );
+        doc2.delete_field(&"body".to_string(), 3, "client2".to_string());
+        doc1.set_field("body".to_string(), json!("Kept"), 4, "client1".to_string());
 
-        let json = doc.to_json();
-        assert_eq!(json["title"], json!("Hello"));
-        assert_eq!(json["count"], json!(42));
+        let report = doc1.merge_with_report(&doc2);
+
+        assert_eq!(report.updated, 0);
+        assert_eq!(report.conflicts.len(), 2);
+        assert_eq!(report.overwritten().count(), 0);
+        let body = report
+            .conflicts
+            .iter()
+            .find(|conflict| conflict.field_path == "body")
+            .unwrap();
+        assert_eq!(body.losing_value, None);
+        assert_eq!(body.winning_value, Some(json!("Kept")));
     }
 
     #[test]
-    fn test_convergence_property() {
-        // Test convergence: two replicas merging in different orders reach same state
+    fn test_merge_report_marks_causal_overwrites() {
+        let mut doc1 = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        let mut doc2 = Document::with_replica("doc-123".to_string(), "client2".to_string());
+        doc1.set("title".to_string(), json!("Draft")).unwrap();
 
-        let mut replica1 = Document::new("doc-123".to_string());
-        let mut replica2 = Document::new("doc-123".to_string());
+        // doc2 has seen the draft before replacing it
+        doc2.merge(&doc1);
+        doc2.set("title".to_string(), json!("Final")).unwrap();
+        doc2.delete(&"body".to_string()).unwrap();
 
-        // Client1 writes
-        let client1_update = Document {
-            id: "doc-123".to_string(),
-            fields: {
-                let mut map = HashMap::new();
-                map.insert(
-                    "field1".to_string(),
-                    Field {
-                        value: json!("A"),
-                        timestamp: Timestamp::new(1, "client1".to_string()),
-                    },
-                );
-                map
-            },
-            version: VectorClock::new(),
-        };
+        let report = doc1.merge_with_report(&doc2);
+        assert_eq!(report.conflicts.len(), 1);
+        assert!(report.conflicts[0].local_lost);
+        assert!(!report.conflicts[0].concurrent);
 
-        // Client2 writes
-        let client2_update = Document {
-            id: "doc-123".to_string(),
-            fields: {
-                let mut map = HashMap::new();
+        // Merging again changes nothing and reports nothing
+        let report = doc1.merge_with_report(&doc2);
+        assert_eq!(report, MergeReport::default());
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_merge_report_lists_local_children_under_remote_ancestor() {
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field(
+            "user.name".to_string(),
+            json!("Ann"),
+            1,
+            "client1".to_string(),
+        );
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field(
+            "user".to_string(),
+            json!({"name": "Bob"}),
+            2,
+            "client2".to_string(),
+        );
+
+        let report = doc1.clone().merge_with_report(&doc2);
+        assert_eq!(report.updated, 1);
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.field_path, "user.name");
+        assert_eq!(conflict.losing_value, Some(json!("Ann")));
+        assert_eq!(conflict.winning_value, Some(json!("Bob")));
+        assert_eq!(conflict.winning_timestamp, doc2.fields["user"].timestamp);
+        assert!(conflict.local_lost);
+
+        // Deleting the ancestor discards the child too
+        let mut doc3 = Document::new("doc-123".to_string());
+        doc3.delete_field(&"user".to_string(), 2, "client3".to_string());
+        let report = doc1.clone().merge_with_report(&doc3);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].field_path, "user.name");
+        assert_eq!(report.conflicts[0].winning_value, None);
+        assert!(report.conflicts[0].local_lost);
+    }
+
+    #[test]
+    fn test_merge_report_lists_remote_children_under_local_ancestor() {
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field(
+            "user".to_string(),
+            json!({"name": "Ann"}),
+            2,
+            "client1".to_string(),
+        );
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field(
+            "user.name".to_string(),
+            json!("Bob"),
+            1,
+            "client2".to_string(),
+        );
+
+        let report = doc1.merge_with_report(&doc2);
+        assert_eq!(report.updated, 0);
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.field_path, "user.name");
+        assert_eq!(conflict.losing_value, Some(json!("Bob")));
+        assert_eq!(conflict.winning_value, Some(json!("Ann")));
+        assert!(!conflict.local_lost);
+    }
+
+    #[test]
+    fn test_merge_report_lists_each_discarded_child_once() {
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field(
+            "user.name".to_string(),
+            json!("Ann"),
+            1,
+            "client1".to_string(),
+        );
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field(
+            "user".to_string(),
+            json!({"name": "Bob"}),
+            2,
+            "client2".to_string(),
+        );
+        doc2.set_field(
+            "user.name".to_string(),
+            json!("Cid"),
+            3,
+            "client2".to_string(),
+        );
+
+        let report = doc1.merge_with_report(&doc2);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].field_path, "user.name");
+        assert_eq!(report.conflicts[0].losing_value, Some(json!("Ann")));
+        assert_eq!(report.conflicts[0].winning_value, Some(json!("Cid")));
+    }
+
+    #[test]
+    fn test_merge_report_matches_merge() {
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field("a".to_string(), json!(1), 1, "client1".to_string());
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field("a".to_string(), json!(2), 2, "client2".to_string());
+        doc2.set_field("b".to_string(), json!(3), 2, "client2".to_string());
+
+        let mut merged = doc1.clone();
+        let report = doc1.merge_with_report(&doc2);
+
+        assert_eq!(report.updated, merged.merge(&doc2));
+        assert_eq!(doc1, merged);
+    }
+
+    #[test]
+    fn test_merge_policy_defaults_to_lww() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_merge_policy("stats.*", MergePolicy::Max);
+
+        assert_eq!(doc.merge_policy("stats.views"), &MergePolicy::Max);
+        assert_eq!(doc.merge_policy("title"), &MergePolicy::Lww);
+
+        // A value that fits the policy beats one that doesn't, even if older
+        doc.set_field(
+            "stats.views".to_string(),
+            json!("n/a"),
+            2,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "stats.views".to_string(),
+            json!(3),
+            1,
+            "client2".to_string(),
+        );
+        assert_eq!(doc.get_field(&"stats.views".to_string()), Some(&json!(3)));
+        assert_eq!(doc.fields["stats.views"].timestamp.clock, 2);
+    }
+
+    #[test]
+    fn test_max_policy_converges_regardless_of_timestamps() {
+        let mut base = Document::new("doc-123".to_string());
+        base.set_merge_policy("high_score", MergePolicy::Max);
+        let mut doc1 = base.clone();
+        let mut doc2 = base.clone();
+
+        doc1.set_field(
+            "high_score".to_string(),
+            json!(90),
+            1,
+            "client1".to_string(),
+        );
+        doc2.set_field(
+            "high_score".to_string(),
+            json!(40),
+            2,
+            "client2".to_string(),
+        );
+        doc1.merge(&doc2);
+        doc2.merge(&doc1);
+
+        assert_eq!(doc1.get_field(&"high_score".to_string()), Some(&json!(90)));
+        assert_eq!(doc1.fields, doc2.fields);
+        assert_eq!(
+            doc1.timestamp_of("high_score"),
+            Some(&Timestamp::new(2, "client2".to_string()))
+        );
+
+        // A newer delete still removes the field
+        doc1.delete_field(&"high_score".to_string(), 3, "client1".to_string());
+        doc2.merge(&doc1);
+        assert_eq!(doc2.get_field(&"high_score".to_string()), None);
+    }
+
+    #[test]
+    fn test_union_policy_keeps_concurrent_elements() {
+        let mut base = Document::new("doc-123".to_string());
+        base.set_merge_policy("**.tags", MergePolicy::Union);
+        let mut doc1 = base.clone();
+        let mut doc2 = base.clone();
+
+        doc1.set_field(
+            "posts.1.tags".to_string(),
+            json!(["rust"]),
+            1,
+            "client1".to_string(),
+        );
+        doc2.set_field(
+            "posts.1.tags".to_string(),
+            json!(["crdt"]),
+            1,
+            "client2".to_string(),
+        );
+        doc1.merge(&doc2);
+        doc2.merge(&doc1);
+
+        assert_eq!(doc1.get_path("posts.1.tags"), Some(json!(["crdt", "rust"])));
+        assert_eq!(doc1.to_json(), doc2.to_json());
+    }
+
+    #[test]
+    fn test_counter_policy_adds_concurrent_increments() {
+        let mut doc1 = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        doc1.set_merge_policy("likes", MergePolicy::Counter);
+        let mut doc2 = doc1.clone();
+        doc2.set_replica("client2".to_string());
+        let likes = "likes".to_string();
+
+        doc1.increment(&likes, 3).unwrap();
+        doc2.increment(&likes, 2).unwrap();
+        doc2.increment(&likes, -1).unwrap();
+        doc1.merge(&doc2);
+        doc2.merge(&doc1);
+
+        assert_eq!(doc1.counter_value(&likes), Some(4));
+        assert_eq!(doc2.counter_value(&likes), Some(4));
+
+        doc1.set("title".to_string(), json!("Post")).unwrap();
+        let title = "title".to_string();
+        assert!(matches!(
+            doc1.increment(&title, 1),
+            Err(SyncError::InvalidOperation(_))
+        ));
+        assert_eq!(doc1.counter_value(&title), None);
+    }
+
+    #[test]
+    fn test_custom_policy_and_report() {
+        let mut doc1 = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        // Space-separated keywords, merged as a set
+        doc1.set_merge_policy(
+            "keywords",
+            MergePolicy::custom(|a, b| {
+                let words: BTreeSet<&str> = [a, b]
+                    .iter()
+                    .flat_map(|value| value.as_str().unwrap_or("").split_whitespace())
+                    .collect();
+                json!(words.into_iter().collect::<Vec<_>>().join(" "))
+            }),
+        );
+        let mut doc2 = doc1.clone();
+        doc2.set_replica("client2".to_string());
+
+        doc1.set("keywords".to_string(), json!("red green"))
+            .unwrap();
+        doc2.set("keywords".to_string(), json!("blue")).unwrap();
+        let report = doc1.merge_with_report(&doc2);
+        doc2.merge(&doc1);
+
+        assert_eq!(doc1.get_path("keywords"), Some(json!("blue green red")));
+        assert_eq!(doc1.fields, doc2.fields);
+        let conflict = &report.conflicts[0];
+        assert!(conflict.local_lost);
+        assert_eq!(conflict.losing_value, Some(json!("red green")));
+        assert_eq!(conflict.winning_value, Some(json!("blue green red")));
+    }
+
+    #[test]
+    fn test_document_to_json() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field("count".to_string(), json!(42), 1, "client1".to_string());
+
+        let json = doc.to_json();
+        assert_eq!(json["title"], json!("Hello"));
+        assert_eq!(json["count"], json!(42));
+    }
+
+    #[test]
+    fn test_convergence_property() {
+        // Test convergence: two replicas merging in different orders reach same state
+
+        let mut replica1 = Document::new("doc-123".to_string());
+        let mut replica2 = Document::new("doc-123".to_string());
+
+        // Client1 writes
+        let client1_update = Document {
+            id: "doc-123".to_string(),
+            fields: {
+                let mut map = HashMap::new();
+                map.insert(
+                    "field1".to_string(),
+                    Field {
+                        value: json!("A"),
+                        timestamp: Timestamp::new(1, "client1".to_string()),
+                    },
+                );
+                map
+            },
+            tombstones: HashMap::new(),
+            version: VectorClock::new(),
+            clock: None,
+            policies: MergePolicies::new(),
+        };
+
+        // Client2 writes
+        let client2_update = Document {
+            id: "doc-123".to_string(),
+            fields: {
+                let mut map = HashMap::new();
                 map.insert(
                     "field1".to_string(),
                     Field {
@@ -406,7 +1533,10 @@ mod tests {
                 );
                 map
             },
+            tombstones: HashMap::new(),
             version: VectorClock::new(),
+            clock: None,
+            policies: MergePolicies::new(),
         };
 
         // Replica1 merges in order: client1, then client2
@@ -427,4 +1557,445 @@ mod tests {
         assert_eq!(replica1.get_field(&"field1".to_string()), Some(&json!("B")));
         assert_eq!(replica2.get_field(&"field1".to_string()), Some(&json!("B")));
     }
+
+    #[test]
+    fn test_delete_field_creates_tombstone() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        doc.delete_field(&"title".to_string(), 2, "client1".to_string());
+
+        assert_eq!(doc.get_field(&"title".to_string()), None);
+        assert_eq!(
+            doc.tombstones().get("title"),
+            Some(&Timestamp::new(2, "client1".to_string()))
+        );
+        assert_eq!(doc.to_json(), json!({}));
+    }
+
+    #[test]
+    fn test_delete_older_than_value_is_ignored() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            5,
+            "client1".to_string(),
+        );
+
+        doc.delete_field(&"title".to_string(), 3, "client2".to_string());
+
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("Hello")));
+        assert!(doc.tombstones().is_empty());
+    }
+
+    #[test]
+    fn test_older_write_does_not_resurrect_deleted_field() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        doc.delete_field(&"title".to_string(), 3, "client1".to_string());
+
+        // Concurrent older write from another replica
+        let updated = doc.merge_field(
+            "title".to_string(),
+            Field {
+                value: json!("Stale"),
+                timestamp: Timestamp::new(2, "client2".to_string()),
+            },
+        );
+
+        assert!(!updated);
+        assert_eq!(doc.get_field(&"title".to_string()), None);
+
+        // A newer write brings the field back and clears the tombstone
+        doc.set_field(
+            "title".to_string(),
+            json!("Fresh"),
+            4,
+            "client2".to_string(),
+        );
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("Fresh")));
+        assert!(doc.tombstones().is_empty());
+    }
+
+    #[test]
+    fn test_collect_garbage_after_all_peers_saw_delete() {
+        use crate::sync::StabilityTracker;
+
+        let mut doc1 = Document::new("doc-123".to_string());
+        doc1.set_field("title".to_string(), json!("Hello"), 1, "c1".to_string());
+        doc1.set_field("body".to_string(), json!("Text"), 2, "c1".to_string());
+        doc1.delete_field(&"title".to_string(), 3, "c1".to_string());
+
+        let mut doc2 = Document::new("doc-123".to_string());
+        doc2.set_field("title".to_string(), json!("Hello"), 1, "c1".to_string());
+
+        // doc2 hasn't seen the delete yet
+        let mut tracker = StabilityTracker::new();
+        tracker.acknowledge("c2".to_string(), &doc2.frontier());
+        assert_eq!(doc1.collect_garbage(&tracker.stable(&doc1.frontier())), 0);
+
+        doc2.merge(&doc1);
+        tracker.acknowledge("c2".to_string(), &doc2.frontier());
+        assert_eq!(doc1.collect_garbage(&tracker.stable(&doc1.frontier())), 1);
+        assert!(doc1.tombstones().is_empty());
+
+        // Merging with a replica that still has the tombstone stays correct
+        doc1.merge(&doc2);
+        doc2.merge(&doc1);
+        assert_eq!(doc1.to_json(), json!({"body": "Text"}));
+        assert_eq!(doc2.to_json(), doc1.to_json());
+    }
+
+    #[test]
+    fn test_collect_garbage_keeps_tombstone_inside_older_value() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user".to_string(),
+            json!({"name": "A", "age": 3}),
+            1,
+            "c1".to_string(),
+        );
+        doc.delete_field(&"user.age".to_string(), 2, "c1".to_string());
+
+        let mut stable = VectorClock::new();
+        stable.update(&"c1".to_string(), 2);
+
+        // The tombstone still hides `age` inside the older `user` value
+        assert_eq!(doc.collect_garbage(&stable), 0);
+        assert_eq!(doc.to_json(), json!({"user": {"name": "A"}}));
+    }
+
+    #[test]
+    fn test_tombstone_wins_exact_tie() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        doc.delete_field(&"title".to_string(), 1, "client1".to_string());
+        assert_eq!(doc.get_field(&"title".to_string()), None);
+
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        assert_eq!(doc.get_field(&"title".to_string()), None);
+    }
+
+    #[test]
+    fn test_delete_survives_merge_in_any_order() {
+        let mut base = Document::new("doc-123".to_string());
+        base.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        let mut deleter = base.clone();
+        deleter.delete_field(&"title".to_string(), 3, "client1".to_string());
+
+        let mut writer = base.clone();
+        writer.set_field(
+            "title".to_string(),
+            json!("Edited"),
+            2,
+            "client2".to_string(),
+        );
+
+        let mut replica1 = base.clone();
+        replica1.merge(&deleter);
+        replica1.merge(&writer);
+
+        let mut replica2 = base.clone();
+        replica2.merge(&writer);
+        replica2.merge(&deleter);
+
+        assert_eq!(replica1, replica2);
+        assert_eq!(replica1.get_field(&"title".to_string()), None);
+    }
+
+    #[test]
+    fn test_nested_paths_build_nested_json() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user.name".to_string(),
+            json!("Alice"),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "/user/address/city".to_string(),
+            json!("Paris"),
+            2,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "/files/a.txt".to_string(),
+            json!(10),
+            3,
+            "client1".to_string(),
+        );
+
+        assert_eq!(
+            doc.to_json(),
+            json!({
+                "user": {"name": "Alice", "address": {"city": "Paris"}},
+                "files": {"a.txt": 10}
+            })
+        );
+
+        // Both notations address the same field
+        assert_eq!(
+            doc.get_field(&"/user/name".to_string()),
+            Some(&json!("Alice"))
+        );
+        assert_eq!(
+            doc.get_path("user"),
+            Some(json!({"name": "Alice", "address": {"city": "Paris"}}))
+        );
+        assert_eq!(doc.get_path("/files/a.txt"), Some(json!(10)));
+        assert_eq!(doc.get_path("user.missing"), None);
+    }
+
+    #[test]
+    fn test_newer_parent_write_replaces_child() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user.name".to_string(),
+            json!("Alice"),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "user".to_string(),
+            json!({"email": "bob@example.com"}),
+            2,
+            "client2".to_string(),
+        );
+
+        assert_eq!(
+            doc.get_path("user"),
+            Some(json!({"email": "bob@example.com"}))
+        );
+        assert_eq!(doc.get_field(&"user.name".to_string()), None);
+
+        // An older child write arriving late is discarded
+        doc.set_field("user.age".to_string(), json!(30), 1, "client3".to_string());
+        assert_eq!(doc.get_path("user.age"), None);
+    }
+
+    #[test]
+    fn test_newer_child_write_applies_on_top_of_parent() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user".to_string(),
+            json!({"name": "Alice", "age": 30}),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "user.name".to_string(),
+            json!("Bob"),
+            2,
+            "client2".to_string(),
+        );
+
+        assert_eq!(
+            doc.get_path("user"),
+            Some(json!({"name": "Bob", "age": 30}))
+        );
+    }
+
+    #[test]
+    fn test_parent_delete_removes_subtree() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user.name".to_string(),
+            json!("Alice"),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field("user.age".to_string(), json!(30), 3, "client1".to_string());
+
+        doc.delete_field(&"user".to_string(), 2, "client2".to_string());
+
+        // The newer child write survives the delete
+        assert_eq!(doc.to_json(), json!({"user": {"age": 30}}));
+        assert_eq!(doc.field_count(), 1);
+    }
+
+    #[test]
+    fn test_concurrent_parent_and_child_writes_converge() {
+        let base = Document::new("doc-123".to_string());
+
+        let mut parent = base.clone();
+        parent.set_field(
+            "user".to_string(),
+            json!({"name": "Alice"}),
+            2,
+            "client1".to_string(),
+        );
+        parent.delete_field(&"settings".to_string(), 3, "client1".to_string());
+
+        let mut child = base.clone();
+        child.set_field(
+            "user.name".to_string(),
+            json!("Bob"),
+            1,
+            "client2".to_string(),
+        );
+        child.set_field("user.age".to_string(), json!(40), 3, "client2".to_string());
+        child.set_field(
+            "settings.theme".to_string(),
+            json!("dark"),
+            2,
+            "client2".to_string(),
+        );
+
+        let mut replica1 = base.clone();
+        replica1.merge(&parent);
+        replica1.merge(&child);
+
+        let mut replica2 = base.clone();
+        replica2.merge(&child);
+        replica2.merge(&parent);
+
+        assert_eq!(replica1, replica2);
+        assert_eq!(
+            replica1.to_json(),
+            json!({"user": {"name": "Alice", "age": 40}})
+        );
+    }
+
+    #[test]
+    fn test_subtree_watcher() {
+        let mut doc = Document::new("doc-123".to_string());
+        doc.set_field(
+            "user.name".to_string(),
+            json!("Alice"),
+            1,
+            "client1".to_string(),
+        );
+
+        let mut watcher = doc.watch("user");
+        assert_eq!(watcher.value(), Some(&json!({"name": "Alice"})));
+
+        // Changes elsewhere don't trigger the watcher
+        doc.set_field("title".to_string(), json!("Hi"), 2, "client1".to_string());
+        assert!(!watcher.poll(&doc));
+
+        doc.set_field("/user/age".to_string(), json!(30), 3, "client1".to_string());
+        assert!(watcher.poll(&doc));
+        assert_eq!(watcher.value(), Some(&json!({"name": "Alice", "age": 30})));
+        assert!(!watcher.poll(&doc));
+
+        doc.delete_field(&"user".to_string(), 4, "client1".to_string());
+        assert!(watcher.poll(&doc));
+        assert_eq!(watcher.value(), None);
+    }
+
+    #[test]
+    fn test_replica_clock_stamps_local_writes() {
+        let mut doc = Document::with_replica("doc-123".to_string(), "client1".to_string());
+
+        let ts1 = doc.set("title".to_string(), json!("Hello")).unwrap();
+        let ts2 = doc.set("title".to_string(), json!("World")).unwrap();
+        let ts3 = doc.delete(&"body".to_string()).unwrap();
+
+        assert!(ts2.is_newer_than(&ts1));
+        assert!(ts3.is_newer_than(&ts2));
+        assert_eq!(ts1.client_id, "client1");
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("World")));
+        assert_eq!(doc.version().get(&"client1".to_string()), 3);
+    }
+
+    #[test]
+    fn test_replica_clock_absorbs_merged_timestamps() {
+        let mut doc = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        doc.set("title".to_string(), json!("Local")).unwrap();
+
+        // A remote replica whose clock runs far ahead
+        let mut remote = Document::new("doc-123".to_string());
+        remote.set_field(
+            "title".to_string(),
+            json!("Remote"),
+            u64::MAX / 2,
+            "client2".to_string(),
+        );
+        doc.merge(&remote);
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("Remote")));
+
+        // The next local write still wins
+        let ts = doc.set("title".to_string(), json!("Later")).unwrap();
+        assert!(ts.clock > u64::MAX / 2);
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("Later")));
+    }
+
+    #[test]
+    fn test_set_requires_replica_clock() {
+        let mut doc = Document::new("doc-123".to_string());
+        let err = doc.set("title".to_string(), json!("Hello")).unwrap_err();
+        assert!(matches!(err, SyncError::InvalidOperation(_)));
+
+        // Attaching a clock to a loaded document starts after its timestamps
+        doc.set_field(
+            "title".to_string(),
+            json!("Old"),
+            u64::MAX / 2,
+            "client2".to_string(),
+        );
+        doc.set_replica("client1".to_string());
+        doc.set("title".to_string(), json!("New")).unwrap();
+        assert_eq!(doc.get_field(&"title".to_string()), Some(&json!("New")));
+        assert_eq!(doc.replica_id(), Some(&"client1".to_string()));
+    }
+
+    #[test]
+    fn test_replica_clock_is_not_serialized() {
+        let mut doc = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        doc.set("title".to_string(), json!("Hello")).unwrap();
+
+        let json = serde_json::to_string(&doc).unwrap();
+        assert!(!json.contains("\"clock\":{"));
+
+        let mut loaded: Document = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, doc);
+        assert_eq!(loaded.replica_id(), None);
+        assert!(loaded.set("title".to_string(), json!("Stolen")).is_err());
+
+        loaded.set_replica("client2".to_string());
+        let ts = loaded.set("title".to_string(), json!("Mine")).unwrap();
+        assert_eq!(ts.client_id, "client2");
+    }
+
+    #[test]
+    fn test_exhausted_clock_rejects_writes() {
+        let mut doc = Document::with_replica("doc-123".to_string(), "client1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Max"),
+            u64::MAX,
+            "client2".to_string(),
+        );
+
+        let err = doc.set("title".to_string(), json!("Next")).unwrap_err();
+        assert!(matches!(err, SyncError::InvalidOperation(_)));
+        assert_eq!(doc.version().get(&"client1".to_string()), 0);
+    }
 }
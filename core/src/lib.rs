@@ -2,9 +2,11 @@
 //!
 //! This is the Rust core of SyncKit, compiled to both native and WASM.
 //! It implements:
-//! - Document structure with field-level LWW
+//! - Document structure with field-level LWW (or per-field merge policies)
+//!   and nested field paths
 //! - Vector clocks for causality tracking
 //! - CRDT data structures (OR-Set, PN-Counter, Text)
+//! - Replica-local undo/redo that never reverts other users' edits
 //! - Binary protocol encoding/decoding (when prost feature enabled)
 //!
 //! # Examples
@@ -28,8 +30,10 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 pub mod document;
 pub mod error;
+pub mod path;
 pub mod storage;
 pub mod sync;
+pub mod undo;
 
 // Protocol module only included if prost feature is enabled
 #[cfg(feature = "prost")]
@@ -40,7 +44,8 @@ pub mod protocol;
     feature = "text-crdt",
     feature = "counters",
     feature = "sets",
-    feature = "fractional-index"
+    feature = "fractional-index",
+    feature = "registers"
 ))]
 pub mod crdt;
 
@@ -51,6 +56,7 @@ pub mod wasm;
 pub use document::Document;
 pub use error::{Result, SyncError};
 pub use sync::{Timestamp, VectorClock};
+pub use undo::UndoManager;
 
 /// Client identifier type
 pub type ClientID = String;
@@ -58,7 +64,7 @@ pub type ClientID = String;
 /// Document identifier type  
 pub type DocumentID = String;
 
-/// Field path within a document
+/// Field path within a document (dotted or JSON Pointer, see `path`)
 pub type FieldPath = String;
 
 #[cfg(test)]
@@ -0,0 +1,130 @@
+//! Nested field paths
+//!
+//! Field paths address values inside a document's JSON tree. Two notations
+//! are accepted:
+//! - Dotted: `user.profile.name`
+//! - JSON Pointer (RFC 6901): `/user/profile/name`, with `~1` for `/` and
+//!   `~0` for `~` inside a segment
+//!
+//! The empty path addresses the document root.
+//!
+//! Documents store fields under a canonical form so both notations hit the
+//! same entry: dotted when every segment can be written that way, JSON
+//! Pointer otherwise (a segment that is empty, contains `.`, or a first
+//! segment starting with `/`).
+
+use crate::FieldPath;
+
+/// Split a path into its segments
+pub fn parse(path: &str) -> Vec<String> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+
+    match path.strip_prefix('/') {
+        Some(pointer) => pointer
+            .split('/')
+            .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+        None => path.split('.').map(str::to_string).collect(),
+    }
+}
+
+/// Build the canonical path for a list of segments
+pub fn canonical(segments: &[String]) -> FieldPath {
+    let needs_pointer = segments.iter().enumerate().any(|(i, segment)| {
+        segment.is_empty() || segment.contains('.') || (i == 0 && segment.starts_with('/'))
+    });
+
+    if needs_pointer {
+        to_pointer(segments)
+    } else {
+        segments.join(".")
+    }
+}
+
+/// Convert a path in either notation to its canonical form
+pub fn normalize(path: &str) -> FieldPath {
+    canonical(&parse(path))
+}
+
+/// Format segments as a JSON Pointer
+pub fn to_pointer(segments: &[String]) -> String {
+    segments
+        .iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+/// Check if `path` lies strictly below `ancestor` (both canonical)
+pub fn is_descendant(ancestor: &str, path: &str) -> bool {
+    if ancestor.is_empty() {
+        return !path.is_empty();
+    }
+
+    if path.starts_with('/') {
+        let ancestor = parse(ancestor);
+        let path = parse(path);
+        return path.len() > ancestor.len() && path.starts_with(&ancestor);
+    }
+
+    // A dotted path only has dotted ancestors
+    !ancestor.starts_with('/')
+        && path.len() > ancestor.len()
+        && path.starts_with(ancestor)
+        && path.as_bytes()[ancestor.len()] == b'.'
+}
+
+/// Canonical paths of all strict ancestors, from the root down
+pub fn ancestors(path: &str) -> Vec<FieldPath> {
+    let segments = parse(path);
+    (0..segments.len())
+        .map(|len| canonical(&segments[..len]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_both_notations() {
+        assert_eq!(parse("user.profile.name"), ["user", "profile", "name"]);
+        assert_eq!(parse("/user/profile/name"), ["user", "profile", "name"]);
+        assert_eq!(parse("/a~1b/c~0d"), ["a/b", "c~d"]);
+        assert_eq!(parse("title"), ["title"]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn test_normalize_round_trips() {
+        assert_eq!(normalize("/user/name"), "user.name");
+        assert_eq!(normalize("user.name"), "user.name");
+        assert_eq!(normalize("/file.txt/size"), "/file.txt/size");
+        assert_eq!(normalize("/"), "/");
+        assert_eq!(normalize("a..b"), "/a//b");
+
+        for path in ["/file.txt/size", "/", "/a//b", "/~1etc"] {
+            assert_eq!(canonical(&parse(path)), path);
+        }
+    }
+
+    #[test]
+    fn test_is_descendant() {
+        assert!(is_descendant("user", "user.name"));
+        assert!(is_descendant("user", "user.profile.name"));
+        assert!(is_descendant("user", "/user/file.txt"));
+        assert!(is_descendant("", "user"));
+        assert!(!is_descendant("user", "user"));
+        assert!(!is_descendant("user", "username"));
+        assert!(!is_descendant("user.name", "user"));
+        assert!(!is_descendant("/file.txt", "file"));
+    }
+
+    #[test]
+    fn test_ancestors() {
+        assert_eq!(ancestors("a.b.c"), ["", "a", "a.b"]);
+        assert_eq!(ancestors("/a/b.c"), ["", "a"]);
+        assert!(ancestors("").is_empty());
+    }
+}
@@ -89,12 +89,15 @@ impl DocumentDelta {
             }
         }
 
-        // Check for removed fields (tombstones)
-        for (path, from_field) in from_fields {
-            if !to_fields.contains_key(path) {
+        // Check for deleted fields (tombstones)
+        for (path, deleted_at) in to.tombstones() {
+            if from.tombstones().get(path) != Some(deleted_at) {
                 delta.changes.push(FieldChange {
                     path: path.clone(),
-                    field: from_field.clone(),
+                    field: DocField {
+                        value: serde_json::Value::Null,
+                        timestamp: deleted_at.clone(),
+                    },
                     is_delete: true,
                 });
             }
@@ -104,6 +107,9 @@ impl DocumentDelta {
     }
 
     /// Apply this delta to a document
+    ///
+    /// Changes merge like remote writes, so the document's merge policies
+    /// apply.
     pub fn apply_to(&self, document: &mut Document, _client_id: &str) -> Result<()> {
         if document.id() != &self.document_id {
             return Err(SyncError::InvalidOperation(
@@ -123,7 +129,8 @@ impl DocumentDelta {
                     original_client.clone(),
                 );
             } else {
-                document.delete_field(&change.path);
+                // Tombstones carry their deletion timestamp
+                document.merge_tombstone(change.path.clone(), change.field.timestamp.clone());
             }
         }
 
@@ -139,7 +146,7 @@ impl DocumentDelta {
                 // Convert to protocol Field
                 Field {
                     path: Some(FieldPath {
-                        segments: vec![change.path.clone()],
+                        segments: path_to_segments(&change.path),
                     }),
                     timestamp: Some(Timestamp {
                         millis: change.field.timestamp.clock as i64,
@@ -150,7 +157,7 @@ impl DocumentDelta {
                     content: if change.is_delete {
                         Some(field::Content::Tombstone(Tombstone {
                             deleted_at: Some(Timestamp {
-                                millis: chrono::Utc::now().timestamp_millis(),
+                                millis: change.field.timestamp.clock as i64,
                                 client_id: Some(ClientId {
                                     id: change.field.timestamp.client_id.clone(),
                                 }),
@@ -204,14 +211,17 @@ impl DocumentDelta {
                 let path = field
                     .path
                     .as_ref()
-                    .and_then(|p| p.segments.first())
-                    .ok_or_else(|| SyncError::Protocol("Missing field path".to_string()))?
-                    .clone();
-
-                let timestamp_proto = field
-                    .timestamp
-                    .as_ref()
-                    .ok_or_else(|| SyncError::Protocol("Missing timestamp".to_string()))?;
+                    .and_then(|p| path_from_segments(&p.segments))
+                    .ok_or_else(|| SyncError::Protocol("Missing field path".to_string()))?;
+
+                // Deletions are ordered by when they happened
+                let timestamp_proto = match &field.content {
+                    Some(field::Content::Tombstone(Tombstone {
+                        deleted_at: Some(deleted_at),
+                    })) => Some(deleted_at),
+                    _ => field.timestamp.as_ref(),
+                }
+                .ok_or_else(|| SyncError::Protocol("Missing timestamp".to_string()))?;
 
                 let timestamp = crate::sync::Timestamp::new(
                     timestamp_proto.millis as u64,
@@ -247,8 +257,31 @@ impl DocumentDelta {
     }
 }
 
+/// Convert a canonical field path to protocol segments
+///
+/// Nested paths are sent as their segments. A top-level (or root) path is
+/// sent as a single segment holding the canonical path, which peers that
+/// predate nested paths read unchanged.
+fn path_to_segments(field_path: &str) -> Vec<String> {
+    let segments = crate::path::parse(field_path);
+    if segments.len() > 1 {
+        segments
+    } else {
+        vec![field_path.to_string()]
+    }
+}
+
+/// Convert protocol segments back to a canonical field path
+fn path_from_segments(segments: &[String]) -> Option<String> {
+    match segments {
+        [] => None,
+        [field_path] => Some(crate::path::normalize(field_path)),
+        segments => Some(crate::path::canonical(segments)),
+    }
+}
+
 /// Convert VectorClock to protocol format
-fn vector_clock_to_protocol(vc: &VectorClock) -> crate::protocol::VectorClock {
+pub(crate) fn vector_clock_to_protocol(vc: &VectorClock) -> crate::protocol::VectorClock {
     let mut clocks = HashMap::new();
     for (client_id, clock) in &vc.clocks {
         clocks.insert(client_id.clone(), *clock as i64);
@@ -258,7 +291,7 @@ fn vector_clock_to_protocol(vc: &VectorClock) -> crate::protocol::VectorClock {
 }
 
 /// Convert protocol VectorClock to internal format
-fn vector_clock_from_protocol(proto: &crate::protocol::VectorClock) -> VectorClock {
+pub(crate) fn vector_clock_from_protocol(proto: &crate::protocol::VectorClock) -> VectorClock {
     let mut vc = VectorClock::new();
     for (client_id, clock) in &proto.clocks {
         vc.update(client_id, *clock as u64);
@@ -269,6 +302,7 @@ fn vector_clock_from_protocol(proto: &crate::protocol::VectorClock) -> VectorClo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sync::MergePolicy;
 
     #[test]
     fn test_delta_computation() {
@@ -333,4 +367,104 @@ mod tests {
         assert_eq!(delta.document_id, delta2.document_id);
         assert_eq!(delta.changes.len(), delta2.changes.len());
     }
+
+    #[test]
+    fn test_delta_carries_deletes() {
+        let mut doc1 = Document::new("doc-1".to_string());
+        doc1.set_field(
+            "name".to_string(),
+            serde_json::json!("Alice"),
+            1,
+            "client1".to_string(),
+        );
+
+        let mut doc2 = doc1.clone();
+        doc2.delete_field(&"name".to_string(), 3, "client2".to_string());
+
+        let delta = DocumentDelta::compute(&doc1, &doc2).unwrap();
+        assert_eq!(delta.changes.len(), 1);
+        assert!(delta.changes[0].is_delete);
+
+        let proto = delta.to_protocol();
+        let decoded = DocumentDelta::from_protocol(&proto, "client1").unwrap();
+        assert_eq!(
+            decoded.changes[0].field.timestamp,
+            crate::sync::Timestamp::new(3, "client2".to_string())
+        );
+
+        // A concurrent older write doesn't survive the delete
+        let mut replica = doc1.clone();
+        replica.set_field(
+            "name".to_string(),
+            serde_json::json!("Bob"),
+            2,
+            "client3".to_string(),
+        );
+        decoded.apply_to(&mut replica, "client1").unwrap();
+        assert_eq!(replica.get_field(&"name".to_string()), None);
+    }
+
+    #[test]
+    fn test_nested_paths_round_trip() {
+        let doc1 = Document::new("doc-1".to_string());
+        let mut doc2 = doc1.clone();
+        for (i, path) in ["title", "user.name", "/files/a.txt"].iter().enumerate() {
+            doc2.set_field(
+                path.to_string(),
+                serde_json::json!(i),
+                i as u64 + 1,
+                "client1".to_string(),
+            );
+        }
+
+        let proto = DocumentDelta::compute(&doc1, &doc2).unwrap().to_protocol();
+        let mut segments: Vec<_> = proto
+            .changes
+            .iter()
+            .map(|change| change.path.as_ref().unwrap().segments.clone())
+            .collect();
+        segments.sort();
+        assert_eq!(
+            segments,
+            vec![
+                vec!["files".to_string(), "a.txt".to_string()],
+                vec!["title".to_string()],
+                vec!["user".to_string(), "name".to_string()],
+            ]
+        );
+
+        let mut replica = doc1.clone();
+        DocumentDelta::from_protocol(&proto, "client1")
+            .unwrap()
+            .apply_to(&mut replica, "client1")
+            .unwrap();
+        assert_eq!(replica.to_json(), doc2.to_json());
+    }
+
+    #[test]
+    fn test_apply_uses_merge_policy() {
+        let mut base = Document::new("doc-1".to_string());
+        base.set_merge_policy("high_score", MergePolicy::Max);
+        let mut remote = base.clone();
+        remote.set_field(
+            "high_score".to_string(),
+            serde_json::json!(10),
+            2,
+            "client2".to_string(),
+        );
+
+        let mut replica = base.clone();
+        replica.set_field(
+            "high_score".to_string(),
+            serde_json::json!(25),
+            1,
+            "client1".to_string(),
+        );
+        DocumentDelta::compute(&base, &remote)
+            .unwrap()
+            .apply_to(&mut replica, "client1")
+            .unwrap();
+
+        assert_eq!(replica.get_path("high_score"), Some(serde_json::json!(25)));
+    }
 }
@@ -285,6 +285,9 @@ pub struct TextOperation {
     /// Timestamp
     #[prost(message, optional, tag = "8")]
     pub timestamp: ::core::option::Option<Timestamp>,
+    /// Right origin: ID of the character this was inserted before (YATA)
+    #[prost(string, tag = "9")]
+    pub right_id: ::prost::alloc::string::String,
 }
 /// Nested message and enum types in `TextOperation`.
 pub mod text_operation {
@@ -398,6 +401,9 @@ pub struct CounterOperation {
     /// Client performing operation
     #[prost(message, optional, tag = "3")]
     pub client_id: ::core::option::Option<ClientId>,
+    /// For transfer: replica receiving the rights
+    #[prost(message, optional, tag = "4")]
+    pub target: ::core::option::Option<ClientId>,
 }
 /// Nested message and enum types in `CounterOperation`.
 pub mod counter_operation {
@@ -418,6 +424,8 @@ pub mod counter_operation {
     pub enum OpType {
         Increment = 0,
         Decrement = 1,
+        /// Bounded counter: move decrement rights to another replica
+        Transfer = 2,
     }
     impl OpType {
         /// String value of the enum field names used in the ProtoBuf definition.
@@ -428,6 +436,7 @@ pub mod counter_operation {
             match self {
                 OpType::Increment => "INCREMENT",
                 OpType::Decrement => "DECREMENT",
+                OpType::Transfer => "TRANSFER",
             }
         }
         /// Creates an enum from field names used in the ProtoBuf definition.
@@ -435,6 +444,7 @@ pub mod counter_operation {
             match value {
                 "INCREMENT" => Some(Self::Increment),
                 "DECREMENT" => Some(Self::Decrement),
+                "TRANSFER" => Some(Self::Transfer),
                 _ => None,
             }
         }
@@ -549,6 +559,9 @@ pub struct SyncRequest {
     /// Maximum deltas to receive in response
     #[prost(int32, tag = "6")]
     pub max_deltas: i32,
+    /// Continue a paginated response (next_page_token from SyncResponse)
+    #[prost(string, tag = "7")]
+    pub page_token: ::prost::alloc::string::String,
 }
 /// Server responds with changes
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -10,10 +10,21 @@ use prost::Message;
 
 // Import CRDTs only if their features are enabled
 #[cfg(feature = "counters")]
-use crate::crdt::PNCounter;
+use crate::crdt::{BoundedCounter, PNCounter};
+#[cfg(feature = "counters")]
+use crate::sync::VectorClock;
 
+#[cfg(feature = "sets")]
+use crate::crdt::or_set::{CausalContext, Dot};
 #[cfg(feature = "sets")]
 use crate::crdt::ORSet;
+#[cfg(any(feature = "counters", feature = "sets"))]
+use std::collections::HashMap;
+#[cfg(feature = "sets")]
+use std::collections::HashSet;
+
+#[cfg(feature = "text-crdt")]
+use crate::crdt::text::{IdRange, Item, ItemId, TextUpdate};
 
 /// Serialize a PN-Counter to protocol format
 #[cfg(feature = "counters")]
@@ -27,30 +38,35 @@ pub fn serialize_pn_counter(counter: &PNCounter, client_id: &str) -> CounterOper
         } else {
             counter_operation::OpType::Decrement as i32
         },
-        amount: value.abs(),
+        amount: value.saturating_abs(),
         client_id: Some(ClientId {
             id: client_id.to_string(),
         }),
+        target: None,
     }
 }
 
 /// Deserialize a PN-Counter from protocol format
+///
+/// Rejects negative amounts instead of trusting the sender.
 #[cfg(feature = "counters")]
 pub fn deserialize_pn_counter(op: &CounterOperation, client_id: &str) -> Result<PNCounter> {
     let mut counter = PNCounter::new(client_id.to_string());
+    if op.amount < 0 {
+        return Err(SyncError::Protocol(format!(
+            "Negative counter amount: {}",
+            op.amount
+        )));
+    }
 
     match counter_operation::OpType::try_from(op.op_type) {
         Ok(counter_operation::OpType::Increment) => {
-            if op.amount > 0 {
-                counter.increment(op.amount);
-            }
+            counter.increment(op.amount)?;
         }
         Ok(counter_operation::OpType::Decrement) => {
-            if op.amount > 0 {
-                counter.decrement(op.amount);
-            }
+            counter.decrement(op.amount)?;
         }
-        Err(_) => {
+        Ok(counter_operation::OpType::Transfer) | Err(_) => {
             return Err(SyncError::Protocol(
                 "Invalid counter operation type".to_string(),
             ))
@@ -60,7 +76,182 @@ pub fn deserialize_pn_counter(op: &CounterOperation, client_id: &str) -> Result<
     Ok(counter)
 }
 
+/// Serialize a PN-Counter delta-state to protocol format
+///
+/// One operation per entry: the entry's replica as `client_id` and its
+/// running total (not the change) as `amount`, so applying a delta twice
+/// is harmless. Works for full states too.
+#[cfg(feature = "counters")]
+pub fn serialize_pn_counter_delta(delta: &PNCounter) -> Vec<CounterOperation> {
+    let entries = delta
+        .increments()
+        .map(|entry| (counter_operation::OpType::Increment, entry))
+        .chain(
+            delta
+                .decrements()
+                .map(|entry| (counter_operation::OpType::Decrement, entry)),
+        );
+
+    entries
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(op_type, (replica_id, count))| CounterOperation {
+            op_type: op_type as i32,
+            amount: count,
+            client_id: Some(ClientId {
+                id: replica_id.clone(),
+            }),
+            target: None,
+        })
+        .collect()
+}
+
+/// Deserialize a PN-Counter delta-state from protocol format
+///
+/// The result is meant to be merged into a replica's counter.
+#[cfg(feature = "counters")]
+pub fn deserialize_pn_counter_delta(
+    operations: &[CounterOperation],
+    client_id: &str,
+) -> Result<PNCounter> {
+    let mut positive = HashMap::new();
+    let mut negative = HashMap::new();
+
+    for op in operations {
+        let replica_id = op
+            .client_id
+            .as_ref()
+            .map(|id| id.id.clone())
+            .ok_or_else(|| SyncError::Protocol("Counter delta without client".to_string()))?;
+        if op.amount < 0 {
+            return Err(SyncError::Protocol(format!(
+                "Negative counter total: {}",
+                op.amount
+            )));
+        }
+
+        let entries = match counter_operation::OpType::try_from(op.op_type) {
+            Ok(counter_operation::OpType::Increment) => &mut positive,
+            Ok(counter_operation::OpType::Decrement) => &mut negative,
+            Ok(counter_operation::OpType::Transfer) | Err(_) => {
+                return Err(SyncError::Protocol(
+                    "Invalid counter operation type".to_string(),
+                ))
+            }
+        };
+        let count = entries.entry(replica_id).or_insert(0);
+        *count = op.amount.max(*count);
+    }
+
+    Ok(PNCounter::from_parts(
+        client_id.to_string(),
+        positive,
+        negative,
+    ))
+}
+
+/// Serialize a Bounded Counter (or one of its delta-states) to protocol format
+///
+/// Like PN-Counter deltas, every operation carries a running total:
+/// INCREMENT and DECREMENT per replica, and TRANSFER per sender (`client_id`)
+/// and receiver (`target`).
+#[cfg(feature = "counters")]
+pub fn serialize_bounded_counter(counter: &BoundedCounter) -> Vec<CounterOperation> {
+    let client = |id: &str| Some(ClientId { id: id.to_string() });
+    let total = |total: u64| i64::try_from(total).unwrap_or(i64::MAX);
+    let mut operations = Vec::new();
+
+    for (op_type, totals) in [
+        (counter_operation::OpType::Increment, counter.increments()),
+        (counter_operation::OpType::Decrement, counter.decrements()),
+    ] {
+        for (replica_id, &amount) in totals.clocks() {
+            operations.push(CounterOperation {
+                op_type: op_type as i32,
+                amount: total(amount),
+                client_id: client(replica_id),
+                target: None,
+            });
+        }
+    }
+
+    for (from, sent) in counter.transfers() {
+        for (to, &amount) in sent.clocks() {
+            operations.push(CounterOperation {
+                op_type: counter_operation::OpType::Transfer as i32,
+                amount: total(amount),
+                client_id: client(from),
+                target: client(to),
+            });
+        }
+    }
+
+    operations
+}
+
+/// Deserialize a Bounded Counter from protocol format
+///
+/// Rejects negative totals, missing replicas and transfers to the sender.
+/// The result is meant to be merged into a replica's counter.
+#[cfg(feature = "counters")]
+pub fn deserialize_bounded_counter(
+    operations: &[CounterOperation],
+    client_id: &str,
+) -> Result<BoundedCounter> {
+    let mut increments = VectorClock::new();
+    let mut decrements = VectorClock::new();
+    let mut transfers: HashMap<String, VectorClock> = HashMap::new();
+
+    for op in operations {
+        let replica_id = op
+            .client_id
+            .as_ref()
+            .map(|id| id.id.clone())
+            .ok_or_else(|| SyncError::Protocol("Counter operation without client".to_string()))?;
+        let amount = u64::try_from(op.amount)
+            .map_err(|_| SyncError::Protocol(format!("Negative counter total: {}", op.amount)))?;
+
+        let (totals, key) = match counter_operation::OpType::try_from(op.op_type) {
+            Ok(counter_operation::OpType::Increment) => (&mut increments, replica_id),
+            Ok(counter_operation::OpType::Decrement) => (&mut decrements, replica_id),
+            Ok(counter_operation::OpType::Transfer) => {
+                let target =
+                    op.target.as_ref().map(|id| id.id.clone()).ok_or_else(|| {
+                        SyncError::Protocol("Transfer without target".to_string())
+                    })?;
+                if target == replica_id {
+                    return Err(SyncError::Protocol(format!(
+                        "Transfer from {} to itself",
+                        target
+                    )));
+                }
+                (transfers.entry(replica_id).or_default(), target)
+            }
+            Err(_) => {
+                return Err(SyncError::Protocol(
+                    "Invalid counter operation type".to_string(),
+                ))
+            }
+        };
+        totals.update(&key, amount.max(totals.get(&key)));
+    }
+
+    Ok(BoundedCounter::from_parts(
+        client_id.to_string(),
+        increments,
+        decrements,
+        transfers,
+    ))
+}
+
 /// Serialize an OR-Set to protocol format
+///
+/// Lossless: every live tag becomes an ADD of its element with the tag's
+/// dot (`replica:counter`), and the causal context (every tag seen, so also
+/// the removed ones) one REMOVE without an element. Context entries are
+/// dots or, for a replica's contiguous dots, ranges (`replica:..counter`).
+///
+/// Delta-states returned by `add`, `remove` and `clear` are sets too and
+/// serialize the same way.
 #[cfg(feature = "sets")]
 pub fn serialize_or_set<T>(set: &ORSet<T>, _client_id: &str) -> Vec<SetOperation>
 where
@@ -71,39 +262,77 @@ where
     for element in set.iter() {
         // Serialize element to JSON for Value encoding
         if let Ok(json_value) = serde_json::to_value(element) {
-            operations.push(SetOperation {
-                op_type: set_operation::OpType::Add as i32,
-                element: Some(json_to_protocol_value(&json_value)),
-                tag: format!("{}", chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)),
-                remove_tags: vec![],
-            });
+            // One add per live tag, carrying the tag's dot
+            for dot in set.tags(element) {
+                operations.push(SetOperation {
+                    op_type: set_operation::OpType::Add as i32,
+                    element: Some(json_to_protocol_value(&json_value)),
+                    tag: dot.to_string(),
+                    remove_tags: vec![],
+                });
+            }
         }
     }
 
+    let context = set.context();
+    let remove_tags: Vec<String> = context
+        .version()
+        .clocks()
+        .iter()
+        .map(|(replica_id, counter)| format!("{}:..{}", replica_id, counter))
+        .chain(context.cloud().map(Dot::to_string))
+        .collect();
+    if !remove_tags.is_empty() {
+        operations.push(SetOperation {
+            op_type: set_operation::OpType::Remove as i32,
+            element: None,
+            tag: String::new(),
+            remove_tags,
+        });
+    }
+
     operations
 }
 
 /// Deserialize an OR-Set from protocol format
+///
+/// Tags listed by REMOVE operations are recorded as seen; those without a
+/// matching ADD count as removed.
 #[cfg(feature = "sets")]
 pub fn deserialize_or_set<T>(operations: &[SetOperation], client_id: &str) -> Result<ORSet<T>>
 where
     T: serde::de::DeserializeOwned + Eq + std::hash::Hash + Clone + serde::Serialize,
 {
-    let mut set = ORSet::new(client_id.to_string());
+    let mut elements: HashMap<T, HashSet<Dot>> = HashMap::new();
+    let mut context = CausalContext::new();
 
     for op in operations {
         match set_operation::OpType::try_from(op.op_type) {
             Ok(set_operation::OpType::Add) => {
-                if let Some(value) = &op.element {
-                    let json_value = protocol_value_to_json(value)?;
-                    let element: T = serde_json::from_value(json_value).map_err(|e| {
-                        SyncError::Protocol(format!("Failed to deserialize element: {}", e))
-                    })?;
-                    set.add(element);
-                }
+                let value = op.element.as_ref().ok_or_else(|| {
+                    SyncError::Protocol(format!("Set add {} without element", op.tag))
+                })?;
+                let json_value = protocol_value_to_json(value)?;
+                let element: T = serde_json::from_value(json_value).map_err(|e| {
+                    SyncError::Protocol(format!("Failed to deserialize element: {}", e))
+                })?;
+                elements
+                    .entry(element)
+                    .or_default()
+                    .insert(parse_dot(&op.tag)?);
             }
             Ok(set_operation::OpType::Remove) => {
-                // Handle remove operations if needed
+                for tag in &op.remove_tags {
+                    match tag.rsplit_once(":..") {
+                        Some((replica_id, counter)) => {
+                            let counter = counter.parse().map_err(|_| {
+                                SyncError::Protocol(format!("Invalid set tag range: {:?}", tag))
+                            })?;
+                            context.insert_range(&replica_id.to_string(), counter);
+                        }
+                        None => context.insert(parse_dot(tag)?),
+                    }
+                }
             }
             Err(_) => {
                 return Err(SyncError::Protocol(
@@ -113,7 +342,133 @@ where
         }
     }
 
-    Ok(set)
+    Ok(ORSet::from_parts(client_id.to_string(), elements, context))
+}
+
+/// Parse a `replica:counter` set tag
+#[cfg(feature = "sets")]
+fn parse_dot(tag: &str) -> Result<Dot> {
+    tag.rsplit_once(':')
+        .and_then(|(replica_id, counter)| {
+            let counter: u64 = counter.parse().ok()?;
+            (counter > 0).then(|| Dot::new(replica_id.to_string(), counter))
+        })
+        .ok_or_else(|| SyncError::Protocol(format!("Invalid set tag: {:?}", tag)))
+}
+
+/// Serialize a Text update to protocol operations
+///
+/// Inserts carry the block's first ID in `op_id` and its origins in
+/// `parent_id`/`right_id` (empty for none); deletes carry the first deleted
+/// ID and a length. IDs are written as `client:clock`. `position` is unused:
+/// receivers place characters by their origins.
+///
+/// Formatting marks have no `TextOperation` form and are not included.
+#[cfg(feature = "text-crdt")]
+pub fn serialize_text_update(update: &TextUpdate) -> Vec<TextOperation> {
+    let id_string = |id: Option<ItemId>| id.map(|id| id.to_string()).unwrap_or_default();
+
+    let inserts = update.inserts.iter().map(|item| TextOperation {
+        op_type: text_operation::OpType::Insert as i32,
+        content: item.content.clone(),
+        length: item.len() as i64,
+        op_id: item.id.to_string(),
+        parent_id: id_string(item.left),
+        right_id: id_string(item.right),
+        client_id: Some(ClientId {
+            id: item.client.to_string(),
+        }),
+        ..Default::default()
+    });
+
+    let deletes = update.deletes.iter().map(|range| TextOperation {
+        op_type: text_operation::OpType::Delete as i32,
+        length: range.len as i64,
+        op_id: range.start.to_string(),
+        client_id: Some(ClientId {
+            id: range.start.client.to_string(),
+        }),
+        ..Default::default()
+    });
+
+    inserts.chain(deletes).collect()
+}
+
+/// Deserialize a Text update from protocol operations
+#[cfg(feature = "text-crdt")]
+pub fn deserialize_text_update(operations: &[TextOperation]) -> Result<TextUpdate> {
+    let mut update = TextUpdate::new();
+
+    for op in operations {
+        let id = parse_item_id(&op.op_id)?;
+        match text_operation::OpType::try_from(op.op_type) {
+            Ok(text_operation::OpType::Insert) => {
+                let left = optional_item_id(&op.parent_id)?;
+                let right = optional_item_id(&op.right_id)?;
+                if !op.content.is_empty() {
+                    check_clock_range(&id, op.content.chars().count() as u64)?;
+                    update.push_insert(Item::new(id, op.content.clone(), left, right));
+                } else if op.length > 0 {
+                    // Deleted characters whose content was garbage collected
+                    let len = usize::try_from(op.length).map_err(|_| {
+                        SyncError::Protocol(format!("Invalid text insert length {}", op.length))
+                    })?;
+                    check_clock_range(&id, len as u64)?;
+                    update.push_insert(Item::new_collected(id, len, left, right));
+                } else {
+                    return Err(SyncError::Protocol(format!(
+                        "Empty text insert {}",
+                        op.op_id
+                    )));
+                }
+            }
+            Ok(text_operation::OpType::Delete) => {
+                let len = u64::try_from(op.length).map_err(|_| {
+                    SyncError::Protocol(format!("Invalid text delete length {}", op.length))
+                })?;
+                if len > 0 {
+                    check_clock_range(&id, len)?;
+                    update.push_delete(IdRange::new(id, len));
+                }
+            }
+            Err(_) => {
+                return Err(SyncError::Protocol(
+                    "Invalid text operation type".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok(update)
+}
+
+/// Fail unless `len` characters starting at `id` fit in the clock range
+#[cfg(feature = "text-crdt")]
+fn check_clock_range(id: &ItemId, len: u64) -> Result<()> {
+    id.clock.checked_add(len).map(|_| ()).ok_or_else(|| {
+        SyncError::Protocol(format!(
+            "Text operation {}:{} of length {} overflows the clock",
+            id.client, id.clock, len
+        ))
+    })
+}
+
+/// Parse a `client:clock` text item ID
+#[cfg(feature = "text-crdt")]
+fn parse_item_id(id: &str) -> Result<ItemId> {
+    id.split_once(':')
+        .and_then(|(client, clock)| Some(ItemId::new(client.parse().ok()?, clock.parse().ok()?)))
+        .ok_or_else(|| SyncError::Protocol(format!("Invalid text item ID: {:?}", id)))
+}
+
+/// Parse an optional text item ID (empty string for none)
+#[cfg(feature = "text-crdt")]
+fn optional_item_id(id: &str) -> Result<Option<ItemId>> {
+    if id.is_empty() {
+        Ok(None)
+    } else {
+        parse_item_id(id).map(Some)
+    }
 }
 
 /// Convert serde_json::Value to protocol::Value
@@ -233,13 +588,117 @@ mod tests {
     #[cfg(feature = "counters")]
     fn test_pn_counter_serialization() {
         let mut counter = PNCounter::new("client1".to_string());
-        counter.increment(5);
-        counter.decrement(2);
+        counter.increment(5).unwrap();
+        counter.decrement(2).unwrap();
 
         let op = serialize_pn_counter(&counter, "client1");
         assert_eq!(op.amount, 3);
     }
 
+    #[test]
+    #[cfg(feature = "counters")]
+    fn test_pn_counter_deserialize_validates() {
+        let mut counter = PNCounter::new("client1".to_string());
+        counter.decrement(4).unwrap();
+        let op = serialize_pn_counter(&counter, "client1");
+        assert_eq!(deserialize_pn_counter(&op, "client1").unwrap().value(), -4);
+
+        let negative = CounterOperation {
+            amount: -4,
+            ..op.clone()
+        };
+        assert!(deserialize_pn_counter(&negative, "client1").is_err());
+
+        let bad_type = CounterOperation { op_type: 7, ..op };
+        assert!(deserialize_pn_counter(&bad_type, "client1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "counters")]
+    fn test_pn_counter_delta_round_trip() {
+        let mut counter = PNCounter::new("client1".to_string());
+        let mut remote = PNCounter::new("client2".to_string());
+        remote.increment(4).unwrap();
+
+        let mut buffer = counter.increment(5).unwrap();
+        buffer.merge(&counter.decrement(2).unwrap());
+
+        let ops = serialize_pn_counter_delta(&buffer);
+        assert_eq!(ops.len(), 2);
+
+        let delta = deserialize_pn_counter_delta(&ops, "client1").unwrap();
+        assert_eq!(delta, buffer);
+
+        remote.merge(&delta);
+        remote.merge(&delta);
+        assert_eq!(remote.value(), 7);
+    }
+
+    #[test]
+    #[cfg(feature = "counters")]
+    fn test_bounded_counter_round_trip() {
+        let mut counter = BoundedCounter::new("client1".to_string());
+        counter.increment(10).unwrap();
+        counter.transfer(&"client2".to_string(), 4).unwrap();
+        counter.decrement(1).unwrap();
+
+        let ops = serialize_bounded_counter(&counter);
+        assert_eq!(ops.len(), 3);
+
+        let decoded = deserialize_bounded_counter(&ops, "client1").unwrap();
+        assert_eq!(decoded, counter);
+        assert_eq!(decoded.rights(&"client2".to_string()), 4);
+    }
+
+    #[test]
+    #[cfg(feature = "counters")]
+    fn test_bounded_counter_rejects_bad_transfers() {
+        let transfer = CounterOperation {
+            op_type: counter_operation::OpType::Transfer as i32,
+            amount: 3,
+            client_id: Some(ClientId {
+                id: "client1".to_string(),
+            }),
+            target: None,
+        };
+        assert!(deserialize_bounded_counter(std::slice::from_ref(&transfer), "client1").is_err());
+
+        let to_self = CounterOperation {
+            target: transfer.client_id.clone(),
+            ..transfer.clone()
+        };
+        assert!(deserialize_bounded_counter(&[to_self], "client1").is_err());
+
+        let negative = CounterOperation {
+            amount: -3,
+            target: Some(ClientId {
+                id: "client2".to_string(),
+            }),
+            ..transfer
+        };
+        assert!(deserialize_bounded_counter(&[negative], "client1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "sets")]
+    fn test_or_set_delta_round_trip() {
+        let mut set = ORSet::new("client1".to_string());
+        let mut remote = set.clone();
+        set.add("item1".to_string());
+        remote.merge(&set);
+
+        let mut buffer = set.add("item2".to_string());
+        buffer.merge(&set.remove(&"item1".to_string()));
+
+        let ops = serialize_or_set(&buffer, "client1");
+        let delta: ORSet<String> = deserialize_or_set(&ops, "client1").unwrap();
+        assert_eq!(delta, buffer);
+
+        remote.merge(&delta);
+        assert!(remote.contains(&"item2".to_string()));
+        assert!(!remote.contains(&"item1".to_string()));
+    }
+
     #[test]
     #[cfg(feature = "sets")]
     fn test_or_set_serialization() {
@@ -248,6 +707,108 @@ mod tests {
         set.add("item2".to_string());
 
         let ops = serialize_or_set(&set, "client1");
-        assert_eq!(ops.len(), 2);
+        assert_eq!(ops.len(), 3); // Two adds and the causal context
+
+        // Tags are the set's own dots, not fresh timestamps
+        let mut tags: Vec<&str> = ops
+            .iter()
+            .filter(|op| op.op_type == set_operation::OpType::Add as i32)
+            .map(|op| op.tag.as_str())
+            .collect();
+        tags.sort();
+        assert_eq!(tags, vec!["client1:1", "client1:2"]);
+    }
+
+    #[test]
+    #[cfg(feature = "sets")]
+    fn test_or_set_round_trip_keeps_removes() {
+        let mut set = ORSet::new("client1".to_string());
+        set.add("item1".to_string());
+        let stale = set.clone();
+        set.add("item2".to_string());
+        set.remove(&"item1".to_string());
+
+        let ops = serialize_or_set(&set, "client1");
+        let bytes = encode_message(&ops[0]).unwrap();
+        assert_eq!(decode_message::<SetOperation>(&bytes).unwrap(), ops[0]);
+
+        let mut decoded: ORSet<String> = deserialize_or_set(&ops, "client1").unwrap();
+        assert_eq!(decoded, set);
+
+        // The remove still beats the stale add
+        decoded.merge(&stale);
+        assert!(!decoded.contains(&"item1".to_string()));
+
+        let bad = SetOperation {
+            op_type: set_operation::OpType::Add as i32,
+            element: Some(json_to_protocol_value(&serde_json::json!("x"))),
+            tag: "no-counter".to_string(),
+            remove_tags: vec![],
+        };
+        assert!(deserialize_or_set::<String>(&[bad], "client1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "text-crdt")]
+    fn test_text_update_round_trip() {
+        use crate::crdt::text::{StateVector, Text};
+
+        let mut text1 = Text::new(1);
+        text1.insert(0, "Hello");
+        text1.insert(5, "!");
+        text1.delete(0, 1);
+
+        let ops = serialize_text_update(&text1.take_update());
+        let bytes = encode_message(&ops[0]).unwrap();
+        let decoded: TextOperation = decode_message(&bytes).unwrap();
+        assert_eq!(decoded, ops[0]);
+
+        let update = deserialize_text_update(&ops).unwrap();
+        let mut text2 = Text::new(2);
+        text2.apply_update(&update);
+        assert_eq!(text2.to_string(), "ello!");
+
+        // Collected content travels as a length only
+        assert_eq!(text1.collect_garbage(&text1.state_vector()), 1);
+        let ops = serialize_text_update(&text1.update_since(&StateVector::new()));
+        let mut text3 = Text::new(3);
+        text3.apply_update(&deserialize_text_update(&ops).unwrap());
+        assert_eq!(text3.to_string(), "ello!");
+
+        let bad = TextOperation {
+            op_id: "not-an-id".to_string(),
+            content: "x".to_string(),
+            ..Default::default()
+        };
+        assert!(deserialize_text_update(&[bad]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "text-crdt")]
+    fn test_text_update_rejects_clock_overflow() {
+        let insert = TextOperation {
+            op_id: format!("7:{}", u64::MAX),
+            content: "ab".to_string(),
+            ..Default::default()
+        };
+        let delete = TextOperation {
+            op_type: text_operation::OpType::Delete as i32,
+            op_id: format!("7:{}", u64::MAX - 1),
+            length: 2,
+            ..Default::default()
+        };
+        let fits = TextOperation {
+            op_id: format!("7:{}", u64::MAX - 1),
+            content: "a".to_string(),
+            ..Default::default()
+        };
+
+        for op in [insert, delete] {
+            assert!(matches!(
+                deserialize_text_update(&[op]),
+                Err(SyncError::Protocol(_))
+            ));
+        }
+        assert!(deserialize_text_update(&[fits]).is_ok());
     }
 }
@@ -1,7 +1,818 @@
 // Sync coordinator module
 //!
-//! This module provides sync coordination logic
+//! This module provides sync coordination logic: a transport-agnostic
+//! (sans-IO) state machine for the client side of the sync protocol in
+//! `sync.proto`.
+//!
+//! The coordinator never touches the network. Callers feed it incoming
+//! `WSMessage`s with `handle_message` and send whatever messages it returns,
+//! so the same logic can be driven by WebSockets, HTTP long-polling or an
+//! in-process test harness.
+//!
+//! Flow covered:
+//! - Subscribe/unsubscribe to documents (`SUBSCRIBE`, `UNSUBSCRIBE`, `SUBSCRIBED`)
+//! - Upload of pending local deltas with `SyncRequest`, tracked until the
+//!   matching `SyncResponse` acknowledges them
+//! - Paginated responses (`has_more` / `next_page_token`)
+//! - Real-time notifications, acknowledged with `SyncAck`
+//! - Heartbeats (`PING` / `PONG`)
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::protocol::sync::SyncCoordinator;
+//! use synckit_core::Document;
+//!
+//! let mut coordinator = SyncCoordinator::new("client1".to_string());
+//!
+//! let mut doc = Document::new("doc-1".to_string());
+//! doc.set_field("title".to_string(), serde_json::json!("Hello"), 1, "client1".to_string());
+//! coordinator.update_document(doc).unwrap();
+//!
+//! // Send this over any transport
+//! let request = coordinator.sync(false);
+//! assert_eq!(coordinator.pending_count(), 0);
+//! assert_eq!(coordinator.in_flight_count(), 1);
+//! # let _ = request;
+//! ```
+
+use crate::document::Document;
+use crate::error::{Result, SyncError};
+use crate::protocol::delta::{vector_clock_from_protocol, vector_clock_to_protocol, DocumentDelta};
+use crate::protocol::*;
+use crate::{ClientID, DocumentID};
+use std::collections::{BTreeSet, HashMap};
 
+/// Default page size requested from the server
+const DEFAULT_MAX_DELTAS: i32 = 100;
+
+/// A sync request whose (possibly paginated) response is still arriving
+#[derive(Debug, Clone)]
+struct ActiveRequest {
+    /// Request ID of the page currently awaited
+    request_id: String,
+
+    /// Whether the client asked for a full sync
+    full_sync: bool,
+
+    /// Documents the request was scoped to
+    document_ids: Vec<DocumentID>,
+
+    /// Local deltas uploaded with the first page, acknowledged by its response
+    uploaded: Vec<DocumentDelta>,
+}
+
+/// Client-side sync session state machine
+#[derive(Debug, Clone)]
 pub struct SyncCoordinator {
-    // TODO: Implement sync coordinator
+    /// Client identifier used for request IDs and delta attribution
+    client_id: ClientID,
+
+    /// Documents owned by this session
+    documents: HashMap<DocumentID, Document>,
+
+    /// Checkpoint to resume sync from
+    checkpoint: SyncCheckpoint,
+
+    /// Documents the server confirmed subscriptions for
+    subscriptions: BTreeSet<DocumentID>,
+
+    /// Subscriptions sent but not yet confirmed
+    requested_subscriptions: BTreeSet<DocumentID>,
+
+    /// Local deltas not yet uploaded (in order)
+    pending: Vec<DocumentDelta>,
+
+    /// Sync request awaiting its response
+    active: Option<ActiveRequest>,
+
+    /// Maximum deltas per response page
+    max_deltas: i32,
+
+    /// Counter for generating request IDs
+    next_request: u64,
+}
+
+impl SyncCoordinator {
+    /// Create a new coordinator with an empty checkpoint
+    pub fn new(client_id: ClientID) -> Self {
+        Self {
+            client_id,
+            documents: HashMap::new(),
+            checkpoint: SyncCheckpoint::default(),
+            subscriptions: BTreeSet::new(),
+            requested_subscriptions: BTreeSet::new(),
+            pending: Vec::new(),
+            active: None,
+            max_deltas: DEFAULT_MAX_DELTAS,
+            next_request: 0,
+        }
+    }
+
+    /// Resume from a previously saved checkpoint
+    pub fn with_checkpoint(mut self, checkpoint: SyncCheckpoint) -> Self {
+        self.checkpoint = checkpoint;
+        self
+    }
+
+    /// Set the maximum number of deltas per response page
+    pub fn with_max_deltas(mut self, max_deltas: i32) -> Self {
+        self.max_deltas = max_deltas;
+        self
+    }
+
+    /// Get the client ID
+    pub fn client_id(&self) -> &ClientID {
+        &self.client_id
+    }
+
+    /// Get the current checkpoint (save it to resume later)
+    pub fn checkpoint(&self) -> &SyncCheckpoint {
+        &self.checkpoint
+    }
+
+    /// Get a document by ID
+    pub fn document(&self, document_id: &DocumentID) -> Option<&Document> {
+        self.documents.get(document_id)
+    }
+
+    /// Get all documents owned by this session
+    pub fn documents(&self) -> &HashMap<DocumentID, Document> {
+        &self.documents
+    }
+
+    /// Add a document without recording any local changes
+    ///
+    /// Use this for documents loaded from storage.
+    pub fn insert_document(&mut self, document: Document) {
+        self.documents.insert(document.id.clone(), document);
+    }
+
+    /// Replace a document with a locally modified version
+    ///
+    /// The difference to the current version is queued for upload with the
+    /// next sync request.
+    pub fn update_document(&mut self, document: Document) -> Result<()> {
+        let delta = match self.documents.get(&document.id) {
+            Some(current) => DocumentDelta::compute(current, &document)?,
+            None => DocumentDelta::compute(&Document::new(document.id.clone()), &document)?,
+        };
+
+        if !delta.changes.is_empty() {
+            self.pending.push(delta);
+        }
+        self.documents.insert(document.id.clone(), document);
+        Ok(())
+    }
+
+    /// Number of local deltas waiting to be uploaded
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Number of uploaded deltas waiting for the server's acknowledgement
+    pub fn in_flight_count(&self) -> usize {
+        self.active
+            .as_ref()
+            .map_or(0, |active| active.uploaded.len())
+    }
+
+    /// Check if a sync request is awaiting its response
+    pub fn is_syncing(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Check if the server confirmed a subscription to a document
+    pub fn is_subscribed(&self, document_id: &DocumentID) -> bool {
+        self.subscriptions.contains(document_id)
+    }
+
+    /// Build a message subscribing to real-time updates for documents
+    pub fn subscribe(&mut self, document_ids: Vec<DocumentID>) -> WsMessage {
+        self.requested_subscriptions
+            .extend(document_ids.iter().cloned());
+
+        Self::message(
+            ws_message::Type::Subscribe,
+            ws_message::Payload::Subscribe(SubscribeRequest {
+                document_ids: to_protocol_ids(&document_ids),
+            }),
+        )
+    }
+
+    /// Build a message unsubscribing from documents
+    ///
+    /// Subscriptions are dropped locally right away; later notifications
+    /// for these documents are acknowledged but not applied.
+    pub fn unsubscribe(&mut self, document_ids: Vec<DocumentID>) -> WsMessage {
+        for document_id in &document_ids {
+            self.subscriptions.remove(document_id);
+            self.requested_subscriptions.remove(document_id);
+        }
+
+        Self::message(
+            ws_message::Type::Unsubscribe,
+            ws_message::Payload::Unsubscribe(UnsubscribeRequest {
+                document_ids: to_protocol_ids(&document_ids),
+            }),
+        )
+    }
+
+    /// Build a sync request for all subscribed documents
+    ///
+    /// Pending local deltas are uploaded with the request and stay in flight
+    /// until the response acknowledges them. Any request still in flight is
+    /// superseded: its deltas are uploaded again with this one.
+    pub fn sync(&mut self, full_sync: bool) -> WsMessage {
+        let document_ids = self.subscriptions.iter().cloned().collect();
+        self.start_request(full_sync, document_ids)
+    }
+
+    /// Build the messages to send after the transport reconnects
+    ///
+    /// Deltas that were in flight are queued again (the server may never
+    /// have seen them; re-applying a delta is idempotent), subscriptions are
+    /// renewed and a new sync request is started.
+    pub fn reconnect(&mut self) -> Vec<WsMessage> {
+        self.requeue_active();
+
+        let mut messages = Vec::new();
+
+        let document_ids: Vec<DocumentID> = self
+            .subscriptions
+            .union(&self.requested_subscriptions)
+            .cloned()
+            .collect();
+        if !document_ids.is_empty() {
+            messages.push(self.subscribe(document_ids));
+        }
+
+        messages.push(self.sync(false));
+        messages
+    }
+
+    /// Consume an incoming message and return the messages to send in reply
+    ///
+    /// Returns an error for `ERROR` messages, failed sync responses and
+    /// messages a client should never receive. State changes that happened
+    /// before the error (such as re-queuing rejected deltas) are kept.
+    pub fn handle_message(&mut self, message: WsMessage) -> Result<Vec<WsMessage>> {
+        match message.payload {
+            Some(ws_message::Payload::SyncResponse(response)) => self.handle_response(response),
+            Some(ws_message::Payload::Notification(notification)) => {
+                self.handle_notification(notification)
+            }
+            Some(ws_message::Payload::Subscribed(confirm)) => self.handle_subscribed(confirm),
+            Some(ws_message::Payload::Error(error)) => Err(SyncError::Protocol(format!(
+                "Server error ({}): {}",
+                status_name(error.status),
+                error.message
+            ))),
+            Some(_) => Err(SyncError::Protocol(format!(
+                "Unexpected message type for client: {}",
+                message.r#type
+            ))),
+            None => match ws_message::Type::try_from(message.r#type) {
+                Ok(ws_message::Type::Ping) => Ok(vec![WsMessage {
+                    r#type: ws_message::Type::Pong as i32,
+                    payload: None,
+                    timestamp: message.timestamp,
+                }]),
+                Ok(ws_message::Type::Pong) => Ok(Vec::new()),
+                _ => Err(SyncError::Protocol(format!(
+                    "Missing payload for message type {}",
+                    message.r#type
+                ))),
+            },
+        }
+    }
+
+    /// Handle a (possibly paginated) sync response
+    fn handle_response(&mut self, response: SyncResponse) -> Result<Vec<WsMessage>> {
+        let is_active = self
+            .active
+            .as_ref()
+            .is_some_and(|active| active.request_id == response.request_id);
+        if !is_active {
+            return Err(SyncError::Protocol(format!(
+                "Response for unknown request: {}",
+                response.request_id
+            )));
+        }
+
+        if response.status != Status::Ok as i32 {
+            self.requeue_active();
+            return Err(SyncError::Protocol(format!(
+                "Sync request failed ({}): {}",
+                status_name(response.status),
+                response.error_message
+            )));
+        }
+
+        // The server accepted our upload with the first page
+        let mut active = self.active.take().expect("active request checked above");
+        active.uploaded.clear();
+
+        for delta in &response.deltas {
+            self.apply_remote_delta(delta)?;
+        }
+
+        if let Some(new_checkpoint) = response.new_checkpoint {
+            self.update_checkpoint(new_checkpoint);
+        }
+
+        if !response.has_more {
+            return Ok(Vec::new());
+        }
+
+        // Ask for the next page under a fresh request ID
+        let request_id = self.next_request_id();
+        let request = SyncRequest {
+            request_id: request_id.clone(),
+            checkpoint: Some(self.checkpoint.clone()),
+            full_sync: active.full_sync,
+            document_ids: to_protocol_ids(&active.document_ids),
+            pending_deltas: Vec::new(),
+            max_deltas: self.max_deltas,
+            page_token: response.next_page_token,
+        };
+        active.request_id = request_id;
+        self.active = Some(active);
+
+        Ok(vec![Self::message(
+            ws_message::Type::SyncRequest,
+            ws_message::Payload::SyncRequest(request),
+        )])
+    }
+
+    /// Apply a pushed delta and acknowledge it
+    fn handle_notification(&mut self, notification: SyncNotification) -> Result<Vec<WsMessage>> {
+        if let Some(delta) = &notification.delta {
+            let document_id = delta.document_id.as_ref().map(|id| &id.id);
+            if document_id.is_some_and(|id| self.subscriptions.contains(id)) {
+                self.apply_remote_delta(delta)?;
+            }
+        }
+
+        let ack = SyncAck {
+            notification_id: notification.notification_id,
+            version: self.checkpoint.version.clone(),
+        };
+
+        Ok(vec![Self::message(
+            ws_message::Type::Ack,
+            ws_message::Payload::Ack(ack),
+        )])
+    }
+
+    /// Record confirmed subscriptions and catch up on stale documents
+    fn handle_subscribed(&mut self, confirm: SubscriptionConfirm) -> Result<Vec<WsMessage>> {
+        for document_id in &confirm.document_ids {
+            self.requested_subscriptions.remove(&document_id.id);
+            self.subscriptions.insert(document_id.id.clone());
+        }
+
+        // Documents where the server has seen changes we haven't
+        let mut stale: Vec<DocumentID> = confirm
+            .versions
+            .iter()
+            .filter(|(document_id, server_version)| {
+                let server_version = vector_clock_from_protocol(server_version);
+                let local_version = self
+                    .documents
+                    .get(*document_id)
+                    .map(|doc| doc.version().clone())
+                    .unwrap_or_default();
+                server_version
+                    .clocks()
+                    .iter()
+                    .any(|(client, &clock)| clock > local_version.get(client))
+            })
+            .map(|(document_id, _)| document_id.clone())
+            .collect();
+        stale.sort();
+
+        if stale.is_empty() || self.active.is_some() {
+            return Ok(Vec::new());
+        }
+
+        Ok(vec![self.start_request(false, stale)])
+    }
+
+    /// Apply a protocol delta to the owned document (creating it if needed)
+    fn apply_remote_delta(&mut self, delta: &Delta) -> Result<()> {
+        let delta = DocumentDelta::from_protocol(delta, &self.client_id)?;
+
+        let document = self
+            .documents
+            .entry(delta.document_id.clone())
+            .or_insert_with(|| Document::new(delta.document_id.clone()));
+        delta.apply_to(document, &self.client_id)?;
+        document.version.merge(&delta.new_version);
+
+        let mut version = vector_clock_from_protocol(
+            self.checkpoint
+                .version
+                .as_ref()
+                .unwrap_or(&Default::default()),
+        );
+        version.merge(&delta.new_version);
+        self.checkpoint.version = Some(vector_clock_to_protocol(&version));
+
+        Ok(())
+    }
+
+    /// Adopt the server's checkpoint without losing versions we already have
+    fn update_checkpoint(&mut self, mut new_checkpoint: SyncCheckpoint) {
+        let mut version = vector_clock_from_protocol(
+            self.checkpoint
+                .version
+                .as_ref()
+                .unwrap_or(&Default::default()),
+        );
+        if let Some(new_version) = &new_checkpoint.version {
+            version.merge(&vector_clock_from_protocol(new_version));
+        }
+        new_checkpoint.version = Some(vector_clock_to_protocol(&version));
+
+        self.checkpoint = new_checkpoint;
+    }
+
+    /// Start a new sync request, uploading all pending deltas
+    fn start_request(&mut self, full_sync: bool, document_ids: Vec<DocumentID>) -> WsMessage {
+        self.requeue_active();
+
+        let uploaded = std::mem::take(&mut self.pending);
+        let request_id = self.next_request_id();
+
+        let request = SyncRequest {
+            request_id: request_id.clone(),
+            checkpoint: Some(self.checkpoint.clone()),
+            full_sync,
+            document_ids: to_protocol_ids(&document_ids),
+            pending_deltas: uploaded.iter().map(DocumentDelta::to_protocol).collect(),
+            max_deltas: self.max_deltas,
+            page_token: String::new(),
+        };
+
+        self.active = Some(ActiveRequest {
+            request_id,
+            full_sync,
+            document_ids,
+            uploaded,
+        });
+
+        Self::message(
+            ws_message::Type::SyncRequest,
+            ws_message::Payload::SyncRequest(request),
+        )
+    }
+
+    /// Put unacknowledged deltas back at the front of the queue
+    fn requeue_active(&mut self) {
+        if let Some(active) = self.active.take() {
+            let mut requeued = active.uploaded;
+            requeued.append(&mut self.pending);
+            self.pending = requeued;
+        }
+    }
+
+    /// Generate the next request ID
+    fn next_request_id(&mut self) -> String {
+        self.next_request += 1;
+        format!("{}-{}", self.client_id, self.next_request)
+    }
+
+    /// Wrap a payload in a WebSocket envelope
+    fn message(r#type: ws_message::Type, payload: ws_message::Payload) -> WsMessage {
+        WsMessage {
+            r#type: r#type as i32,
+            payload: Some(payload),
+            timestamp: None,
+        }
+    }
+}
+
+/// Convert document IDs to protocol format
+fn to_protocol_ids(document_ids: &[DocumentID]) -> Vec<DocumentId> {
+    document_ids
+        .iter()
+        .map(|id| DocumentId { id: id.clone() })
+        .collect()
+}
+
+/// Human-readable name of a status code
+fn status_name(status: i32) -> &'static str {
+    Status::try_from(status)
+        .map(|status| status.as_str_name())
+        .unwrap_or("UNKNOWN")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request_of(message: &WsMessage) -> &SyncRequest {
+        match &message.payload {
+            Some(ws_message::Payload::SyncRequest(request)) => request,
+            other => panic!("Expected sync request, got {:?}", other),
+        }
+    }
+
+    fn response(request_id: &str, deltas: Vec<Delta>, has_more: bool) -> WsMessage {
+        WsMessage {
+            r#type: ws_message::Type::SyncResponse as i32,
+            payload: Some(ws_message::Payload::SyncResponse(SyncResponse {
+                request_id: request_id.to_string(),
+                status: Status::Ok as i32,
+                error_message: String::new(),
+                deltas,
+                new_checkpoint: None,
+                has_more,
+                next_page_token: if has_more {
+                    "page-2".to_string()
+                } else {
+                    String::new()
+                },
+            })),
+            timestamp: None,
+        }
+    }
+
+    /// Protocol delta setting one field on a fresh document
+    fn remote_delta(document_id: &str, field: &str, value: serde_json::Value, clock: u64) -> Delta {
+        let base = Document::new(document_id.to_string());
+        let mut changed = base.clone();
+        changed.set_field(field.to_string(), value, clock, "server".to_string());
+        changed.version.update(&"server".to_string(), clock);
+        DocumentDelta::compute(&base, &changed)
+            .unwrap()
+            .to_protocol()
+    }
+
+    fn confirm(document_ids: &[&str]) -> WsMessage {
+        WsMessage {
+            r#type: ws_message::Type::Subscribed as i32,
+            payload: Some(ws_message::Payload::Subscribed(SubscriptionConfirm {
+                document_ids: document_ids
+                    .iter()
+                    .map(|id| DocumentId { id: id.to_string() })
+                    .collect(),
+                versions: HashMap::new(),
+            })),
+            timestamp: None,
+        }
+    }
+
+    #[test]
+    fn test_subscribe_and_unsubscribe() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+
+        let message = coordinator.subscribe(vec!["doc1".to_string()]);
+        assert_eq!(message.r#type, ws_message::Type::Subscribe as i32);
+        assert!(!coordinator.is_subscribed(&"doc1".to_string()));
+
+        let replies = coordinator.handle_message(confirm(&["doc1"])).unwrap();
+        assert!(replies.is_empty());
+        assert!(coordinator.is_subscribed(&"doc1".to_string()));
+
+        let message = coordinator.unsubscribe(vec!["doc1".to_string()]);
+        assert_eq!(message.r#type, ws_message::Type::Unsubscribe as i32);
+        assert!(!coordinator.is_subscribed(&"doc1".to_string()));
+    }
+
+    #[test]
+    fn test_pending_deltas_acknowledged_by_response() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        coordinator.update_document(doc).unwrap();
+        assert_eq!(coordinator.pending_count(), 1);
+
+        let message = coordinator.sync(false);
+        let request = request_of(&message);
+        assert_eq!(request.pending_deltas.len(), 1);
+        assert_eq!(coordinator.pending_count(), 0);
+        assert_eq!(coordinator.in_flight_count(), 1);
+
+        let request_id = request.request_id.clone();
+        let replies = coordinator
+            .handle_message(response(&request_id, vec![], false))
+            .unwrap();
+
+        assert!(replies.is_empty());
+        assert_eq!(coordinator.in_flight_count(), 0);
+        assert!(!coordinator.is_syncing());
+    }
+
+    #[test]
+    fn test_failed_response_requeues_deltas() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        coordinator.update_document(doc.clone()).unwrap();
+
+        let message = coordinator.sync(false);
+        let request_id = request_of(&message).request_id.clone();
+
+        // Another local change while the request is in flight
+        doc.set_field("body".to_string(), json!("World"), 2, "client1".to_string());
+        coordinator.update_document(doc).unwrap();
+
+        let mut failure = response(&request_id, vec![], false);
+        if let Some(ws_message::Payload::SyncResponse(response)) = &mut failure.payload {
+            response.status = Status::InternalError as i32;
+            response.error_message = "database unavailable".to_string();
+        }
+
+        let err = coordinator.handle_message(failure).unwrap_err();
+        assert!(matches!(err, SyncError::Protocol(_)));
+        assert_eq!(coordinator.pending_count(), 2);
+        assert_eq!(coordinator.in_flight_count(), 0);
+
+        // The retried request uploads both deltas in order
+        let message = coordinator.sync(false);
+        let request = request_of(&message);
+        assert_eq!(request.pending_deltas.len(), 2);
+        assert_eq!(
+            request.pending_deltas[0].changes[0]
+                .path
+                .as_ref()
+                .unwrap()
+                .segments,
+            ["title"]
+        );
+    }
+
+    #[test]
+    fn test_paginated_response() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+
+        let message = coordinator.sync(true);
+        let request_id = request_of(&message).request_id.clone();
+
+        let page1 = response(
+            &request_id,
+            vec![remote_delta("doc1", "a", json!(1), 1)],
+            true,
+        );
+        let replies = coordinator.handle_message(page1).unwrap();
+
+        assert_eq!(replies.len(), 1);
+        let next = request_of(&replies[0]);
+        assert_eq!(next.page_token, "page-2");
+        assert!(next.full_sync);
+        assert!(next.pending_deltas.is_empty());
+        assert!(coordinator.is_syncing());
+
+        // The old request ID is no longer accepted
+        assert!(coordinator
+            .handle_message(response(&request_id, vec![], false))
+            .is_err());
+
+        let page2 = response(
+            &next.request_id.clone(),
+            vec![remote_delta("doc2", "b", json!(2), 2)],
+            false,
+        );
+        assert!(coordinator.handle_message(page2).unwrap().is_empty());
+        assert!(!coordinator.is_syncing());
+
+        assert_eq!(
+            coordinator
+                .document(&"doc1".to_string())
+                .unwrap()
+                .get_field(&"a".to_string()),
+            Some(&json!(1))
+        );
+        assert_eq!(
+            coordinator
+                .document(&"doc2".to_string())
+                .unwrap()
+                .get_field(&"b".to_string()),
+            Some(&json!(2))
+        );
+
+        let version =
+            vector_clock_from_protocol(coordinator.checkpoint().version.as_ref().unwrap());
+        assert_eq!(version.get(&"server".to_string()), 2);
+    }
+
+    #[test]
+    fn test_notification_applied_and_acknowledged() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+        coordinator.subscribe(vec!["doc1".to_string()]);
+        coordinator.handle_message(confirm(&["doc1"])).unwrap();
+
+        let notification = WsMessage {
+            r#type: ws_message::Type::Notification as i32,
+            payload: Some(ws_message::Payload::Notification(SyncNotification {
+                notification_id: "n-1".to_string(),
+                delta: Some(remote_delta("doc1", "title", json!("Pushed"), 5)),
+                document_ids: vec![DocumentId {
+                    id: "doc1".to_string(),
+                }],
+            })),
+            timestamp: None,
+        };
+
+        let replies = coordinator.handle_message(notification).unwrap();
+        assert_eq!(replies.len(), 1);
+
+        match &replies[0].payload {
+            Some(ws_message::Payload::Ack(ack)) => {
+                assert_eq!(ack.notification_id, "n-1");
+                let version = vector_clock_from_protocol(ack.version.as_ref().unwrap());
+                assert_eq!(version.get(&"server".to_string()), 5);
+            }
+            other => panic!("Expected ack, got {:?}", other),
+        }
+
+        assert_eq!(
+            coordinator
+                .document(&"doc1".to_string())
+                .unwrap()
+                .get_field(&"title".to_string()),
+            Some(&json!("Pushed"))
+        );
+    }
+
+    #[test]
+    fn test_subscribed_with_newer_server_version_starts_sync() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+        coordinator.subscribe(vec!["doc1".to_string()]);
+
+        let mut message = confirm(&["doc1"]);
+        if let Some(ws_message::Payload::Subscribed(confirm)) = &mut message.payload {
+            let mut version = crate::sync::VectorClock::new();
+            version.update(&"server".to_string(), 3);
+            confirm
+                .versions
+                .insert("doc1".to_string(), vector_clock_to_protocol(&version));
+        }
+
+        let replies = coordinator.handle_message(message).unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(request_of(&replies[0]).document_ids[0].id, "doc1");
+    }
+
+    #[test]
+    fn test_reconnect_requeues_in_flight_deltas() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+        coordinator.subscribe(vec!["doc1".to_string()]);
+        coordinator.handle_message(confirm(&["doc1"])).unwrap();
+
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        coordinator.update_document(doc).unwrap();
+        coordinator.sync(false);
+        assert_eq!(coordinator.in_flight_count(), 1);
+
+        let messages = coordinator.reconnect();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].r#type, ws_message::Type::Subscribe as i32);
+        assert_eq!(request_of(&messages[1]).pending_deltas.len(), 1);
+    }
+
+    #[test]
+    fn test_ping_pong_and_errors() {
+        let mut coordinator = SyncCoordinator::new("client1".to_string());
+
+        let ping = WsMessage {
+            r#type: ws_message::Type::Ping as i32,
+            payload: None,
+            timestamp: None,
+        };
+        let replies = coordinator.handle_message(ping).unwrap();
+        assert_eq!(replies[0].r#type, ws_message::Type::Pong as i32);
+
+        let error = WsMessage {
+            r#type: ws_message::Type::Error as i32,
+            payload: Some(ws_message::Payload::Error(ErrorMessage {
+                status: Status::PermissionDenied as i32,
+                message: "nope".to_string(),
+                details: HashMap::new(),
+            })),
+            timestamp: None,
+        };
+        assert!(coordinator.handle_message(error).is_err());
+    }
 }
@@ -0,0 +1,439 @@
+//! Append-only file storage backend
+//!
+//! Every write is appended to a single log file as one JSON record per line
+//! and flushed to disk before the call returns. On open, the log is replayed
+//! into memory to rebuild the current state.
+//!
+//! A crash in the middle of an append can leave a partial last line. Such a
+//! torn record was never acknowledged to the caller, so it is discarded (and
+//! truncated away) on the next open. Any other unreadable record is reported
+//! as corruption.
+//!
+//! The log only grows; call `FileStorage::compact` to rewrite it with just
+//! the live snapshots and deltas.
+
+use super::{MemoryStorage, Storage};
+use crate::document::Document;
+use crate::error::{Result, SyncError};
+use crate::sync::Delta;
+use crate::DocumentID;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// A single entry in the append-only log
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum LogRecord {
+    /// Snapshot of a document (supersedes earlier deltas)
+    SaveDocument { document: Document },
+
+    /// Delta appended for a document
+    AppendDelta { delta: Delta },
+
+    /// Document removed along with its deltas
+    DeleteDocument { document_id: DocumentID },
+}
+
+/// Durable storage backend backed by an append-only log file
+#[derive(Debug)]
+pub struct FileStorage {
+    /// Path of the log file
+    path: PathBuf,
+
+    /// Log file opened for appending
+    file: File,
+
+    /// Current state rebuilt from the log
+    state: MemoryStorage,
+}
+
+impl FileStorage {
+    /// Open (or create) a log file and replay it
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .map_err(|e| io_error("open", &path, e))?;
+
+        // Read raw bytes: a torn tail may end inside a multi-byte character
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)
+            .map_err(|e| io_error("read", &path, e))?;
+
+        let mut state = MemoryStorage::new();
+        let mut valid_len = 0;
+
+        for line in contents.split_inclusive(|&byte| byte == b'\n') {
+            if line.last() != Some(&b'\n') {
+                // Torn write from a crash: never acknowledged, drop it
+                break;
+            }
+
+            let record: LogRecord = serde_json::from_slice(line).map_err(|e| {
+                SyncError::StorageError(format!(
+                    "Corrupt record at byte {} of {}: {}",
+                    valid_len,
+                    path.display(),
+                    e
+                ))
+            })?;
+            Self::replay(&mut state, record)?;
+            valid_len += line.len();
+        }
+
+        if valid_len < contents.len() {
+            file.set_len(valid_len as u64)
+                .map_err(|e| io_error("truncate", &path, e))?;
+            file.sync_all().map_err(|e| io_error("sync", &path, e))?;
+        }
+
+        Ok(Self { path, file, state })
+    }
+
+    /// Get the path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Rewrite the log so it only contains live snapshots and deltas
+    ///
+    /// The new log is written to a temporary file and atomically renamed
+    /// over the old one (then the directory is synced), so a crash during
+    /// compaction loses nothing.
+    pub fn compact(&mut self) -> Result<()> {
+        let tmp_path = self.path.with_extension("compact");
+
+        {
+            let mut tmp = File::create(&tmp_path).map_err(|e| io_error("create", &tmp_path, e))?;
+
+            for document_id in self.state.list_documents()? {
+                if let Some(document) = self.state.load_document(&document_id)? {
+                    write_record(&mut tmp, &tmp_path, &LogRecord::SaveDocument { document })?;
+                }
+                for delta in self.state.load_deltas(&document_id)? {
+                    write_record(&mut tmp, &tmp_path, &LogRecord::AppendDelta { delta })?;
+                }
+            }
+
+            tmp.sync_all().map_err(|e| io_error("sync", &tmp_path, e))?;
+        }
+
+        fs::rename(&tmp_path, &self.path).map_err(|e| io_error("rename", &tmp_path, e))?;
+        sync_parent(&self.path)?;
+
+        self.file = OpenOptions::new()
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| io_error("open", &self.path, e))?;
+
+        Ok(())
+    }
+
+    /// Apply a log record to the in-memory state
+    fn replay(state: &mut MemoryStorage, record: LogRecord) -> Result<()> {
+        match record {
+            LogRecord::SaveDocument { document } => state.save_document(&document),
+            LogRecord::AppendDelta { delta } => state.append_delta(&delta),
+            LogRecord::DeleteDocument { document_id } => state.delete_document(&document_id),
+        }
+    }
+
+    /// Durably append a record, then apply it to the in-memory state
+    ///
+    /// If the write or sync fails, the file is truncated back to where it
+    /// was, so a partial line can't get glued to the next record.
+    fn append(&mut self, record: LogRecord) -> Result<()> {
+        let len = self
+            .file
+            .metadata()
+            .map_err(|e| io_error("stat", &self.path, e))?
+            .len();
+
+        let written = write_record(&mut self.file, &self.path, &record).and_then(|()| {
+            self.file
+                .sync_data()
+                .map_err(|e| io_error("sync", &self.path, e))
+        });
+        if let Err(error) = written {
+            // Best effort: if this fails too, the next open drops the torn tail
+            let _ = self.file.set_len(len);
+            return Err(error);
+        }
+
+        Self::replay(&mut self.state, record)
+    }
+}
+
+impl Storage for FileStorage {
+    fn load_document(&self, document_id: &DocumentID) -> Result<Option<Document>> {
+        self.state.load_document(document_id)
+    }
+
+    fn save_document(&mut self, document: &Document) -> Result<()> {
+        self.append(LogRecord::SaveDocument {
+            document: document.clone(),
+        })
+    }
+
+    fn append_delta(&mut self, delta: &Delta) -> Result<()> {
+        self.append(LogRecord::AppendDelta {
+            delta: delta.clone(),
+        })
+    }
+
+    fn load_deltas(&self, document_id: &DocumentID) -> Result<Vec<Delta>> {
+        self.state.load_deltas(document_id)
+    }
+
+    fn delete_document(&mut self, document_id: &DocumentID) -> Result<()> {
+        self.append(LogRecord::DeleteDocument {
+            document_id: document_id.clone(),
+        })
+    }
+
+    fn list_documents(&self) -> Result<Vec<DocumentID>> {
+        self.state.list_documents()
+    }
+}
+
+/// Serialize a record as a single line and write it in one call
+fn write_record(file: &mut File, path: &Path, record: &LogRecord) -> Result<()> {
+    let mut line =
+        serde_json::to_string(record).map_err(|e| SyncError::SerializationError(e.to_string()))?;
+    line.push('\n');
+
+    file.write_all(line.as_bytes())
+        .map_err(|e| io_error("write", path, e))
+}
+
+/// Fsync the directory holding `path` so a rename into it is durable
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)
+        .and_then(|dir| dir.sync_all())
+        .map_err(|e| io_error("sync", parent, e))
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+fn io_error(action: &str, path: &Path, error: std::io::Error) -> SyncError {
+    SyncError::StorageError(format!(
+        "Failed to {} {}: {}",
+        action,
+        path.display(),
+        error
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::{compute_delta, Timestamp};
+    use serde_json::json;
+
+    /// Unique log path in the system temp directory
+    fn temp_log() -> PathBuf {
+        std::env::temp_dir().join(format!("synckit-storage-{}.log", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_roundtrip_across_reopen() {
+        let path = temp_log();
+
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+        doc.set_field(
+            "ratio".to_string(),
+            json!(0.1 + 0.2),
+            u64::MAX,
+            "client2".to_string(),
+        );
+        doc.version.update(&"client1".to_string(), 7);
+        doc.version.update(&"client2".to_string(), u64::MAX);
+
+        let mut changed = doc.clone();
+        changed.set_field(
+            "body".to_string(),
+            json!({"nested": [1, 2, 3]}),
+            9,
+            "client3".to_string(),
+        );
+        changed.version.tick(&"client3".to_string());
+        let delta = compute_delta(&doc, &changed);
+
+        {
+            let mut storage = FileStorage::open(&path).unwrap();
+            storage.save_document(&doc).unwrap();
+            storage.append_delta(&delta).unwrap();
+        }
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(
+            storage.load_document(&"doc1".to_string()).unwrap(),
+            Some(doc)
+        );
+        assert_eq!(
+            storage.load_deltas(&"doc1".to_string()).unwrap(),
+            vec![delta]
+        );
+
+        let latest = storage.load_latest(&"doc1".to_string()).unwrap().unwrap();
+        assert_eq!(latest, changed);
+        assert_eq!(
+            latest.fields["ratio"].timestamp,
+            Timestamp::new(u64::MAX, "client2".to_string())
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_delete_survives_reopen() {
+        let path = temp_log();
+
+        {
+            let mut storage = FileStorage::open(&path).unwrap();
+            storage
+                .save_document(&Document::new("a".to_string()))
+                .unwrap();
+            storage
+                .save_document(&Document::new("b".to_string()))
+                .unwrap();
+            storage.delete_document(&"a".to_string()).unwrap();
+        }
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.list_documents().unwrap(), vec!["b"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_write_is_discarded() {
+        let path = temp_log();
+
+        {
+            let mut storage = FileStorage::open(&path).unwrap();
+            storage
+                .save_document(&Document::new("a".to_string()))
+                .unwrap();
+        }
+
+        // Simulate a crash halfway through appending a record
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"op\":\"save_document\",\"docu").unwrap();
+        drop(file);
+
+        {
+            let mut storage = FileStorage::open(&path).unwrap();
+            assert_eq!(storage.list_documents().unwrap(), vec!["a"]);
+            storage
+                .save_document(&Document::new("b".to_string()))
+                .unwrap();
+        }
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.list_documents().unwrap(), vec!["a", "b"]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_torn_multibyte_tail_is_discarded() {
+        let path = temp_log();
+
+        let mut doc = Document::new("a".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("héllo"),
+            1,
+            "client1".to_string(),
+        );
+        {
+            let mut storage = FileStorage::open(&path).unwrap();
+            storage.save_document(&doc).unwrap();
+        }
+
+        // Crash in the middle of the two bytes of "é"
+        let line = fs::read(&path).unwrap();
+        let cut = line.iter().position(|&byte| byte >= 0x80).unwrap() + 1;
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&line[..cut]).unwrap();
+        drop(file);
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.load_document(&"a".to_string()).unwrap(), Some(doc));
+        assert_eq!(fs::read(&path).unwrap(), line);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_record_is_reported() {
+        let path = temp_log();
+        fs::write(&path, "not json\n").unwrap();
+
+        let err = FileStorage::open(&path).unwrap_err();
+        assert!(matches!(err, SyncError::StorageError(_)));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compact_keeps_live_state() {
+        let path = temp_log();
+
+        let mut storage = FileStorage::open(&path).unwrap();
+        let base = Document::new("doc1".to_string());
+        for clock in 1..=10 {
+            let mut doc = base.clone();
+            doc.set_field(
+                "count".to_string(),
+                json!(clock),
+                clock,
+                "client1".to_string(),
+            );
+            storage.save_document(&doc).unwrap();
+        }
+        let mut changed = base.clone();
+        changed.set_field("count".to_string(), json!(11), 11, "client1".to_string());
+        storage
+            .append_delta(&compute_delta(&base, &changed))
+            .unwrap();
+
+        let size_before = fs::metadata(&path).unwrap().len();
+        storage.compact().unwrap();
+        assert!(fs::metadata(&path).unwrap().len() < size_before);
+
+        // Appends after compaction land in the new log
+        storage
+            .save_document(&Document::new("doc2".to_string()))
+            .unwrap();
+        drop(storage);
+
+        let storage = FileStorage::open(&path).unwrap();
+        assert_eq!(storage.list_documents().unwrap(), vec!["doc1", "doc2"]);
+        let latest = storage.load_latest(&"doc1".to_string()).unwrap().unwrap();
+        assert_eq!(latest.get_field(&"count".to_string()), Some(&json!(11)));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,159 @@
+//! In-memory storage backend
+//!
+//! Keeps everything in process memory. Useful for tests and for
+//! short-lived replicas that don't need durability.
+
+use super::Storage;
+use crate::document::Document;
+use crate::error::Result;
+use crate::sync::Delta;
+use crate::DocumentID;
+use std::collections::HashMap;
+
+/// Storage backend that keeps documents and deltas in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStorage {
+    /// Latest snapshot per document
+    documents: HashMap<DocumentID, Document>,
+
+    /// Deltas appended since the last snapshot, per document
+    deltas: HashMap<DocumentID, Vec<Delta>>,
+}
+
+impl MemoryStorage {
+    /// Create a new empty storage
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for MemoryStorage {
+    fn load_document(&self, document_id: &DocumentID) -> Result<Option<Document>> {
+        Ok(self.documents.get(document_id).cloned())
+    }
+
+    fn save_document(&mut self, document: &Document) -> Result<()> {
+        self.deltas.remove(&document.id);
+        self.documents.insert(document.id.clone(), document.clone());
+        Ok(())
+    }
+
+    fn append_delta(&mut self, delta: &Delta) -> Result<()> {
+        self.deltas
+            .entry(delta.document_id.clone())
+            .or_default()
+            .push(delta.clone());
+        Ok(())
+    }
+
+    fn load_deltas(&self, document_id: &DocumentID) -> Result<Vec<Delta>> {
+        Ok(self.deltas.get(document_id).cloned().unwrap_or_default())
+    }
+
+    fn delete_document(&mut self, document_id: &DocumentID) -> Result<()> {
+        self.documents.remove(document_id);
+        self.deltas.remove(document_id);
+        Ok(())
+    }
+
+    fn list_documents(&self) -> Result<Vec<DocumentID>> {
+        let mut ids: Vec<DocumentID> = self
+            .documents
+            .keys()
+            .chain(self.deltas.keys())
+            .cloned()
+            .collect();
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::compute_delta;
+    use serde_json::json;
+
+    #[test]
+    fn test_save_and_load_document() {
+        let mut storage = MemoryStorage::new();
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        storage.save_document(&doc).unwrap();
+
+        let loaded = storage.load_document(&"doc1".to_string()).unwrap();
+        assert_eq!(loaded, Some(doc));
+        assert_eq!(storage.load_document(&"missing".to_string()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_append_and_load_deltas() {
+        let mut storage = MemoryStorage::new();
+        let base = Document::new("doc1".to_string());
+        let mut changed = base.clone();
+        changed.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        let delta = compute_delta(&base, &changed);
+        storage.append_delta(&delta).unwrap();
+
+        assert_eq!(
+            storage.load_deltas(&"doc1".to_string()).unwrap(),
+            vec![delta]
+        );
+
+        let latest = storage.load_latest(&"doc1".to_string()).unwrap().unwrap();
+        assert_eq!(
+            latest.get_field(&"title".to_string()),
+            Some(&json!("Hello"))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_supersedes_deltas() {
+        let mut storage = MemoryStorage::new();
+        let base = Document::new("doc1".to_string());
+        let mut changed = base.clone();
+        changed.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        storage
+            .append_delta(&compute_delta(&base, &changed))
+            .unwrap();
+        storage.save_document(&changed).unwrap();
+
+        assert!(storage.load_deltas(&"doc1".to_string()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_list_and_delete_documents() {
+        let mut storage = MemoryStorage::new();
+        storage
+            .save_document(&Document::new("b".to_string()))
+            .unwrap();
+        storage
+            .append_delta(&Delta::empty("a".to_string(), Default::default()))
+            .unwrap();
+
+        assert_eq!(storage.list_documents().unwrap(), vec!["a", "b"]);
+
+        storage.delete_document(&"a".to_string()).unwrap();
+        assert_eq!(storage.list_documents().unwrap(), vec!["b"]);
+        assert_eq!(storage.load_latest(&"a".to_string()).unwrap(), None);
+    }
+}
@@ -1,12 +1,93 @@
 //! Storage abstraction layer
 //!
-//! Phase 2 implementation:
-//! - Storage trait
-//! - In-memory storage (for testing)
+//! Provides a `Storage` trait for persisting documents and the deltas
+//! recorded against them, plus two backends:
+//! - `MemoryStorage`: in-memory storage (for testing)
+//! - `FileStorage`: durable append-only log on the local filesystem
+//!   (native targets only)
 //!
 //! Future:
 //! - IndexedDB adapter
 //! - OPFS adapter
 //! - SQLite adapter
+//!
+//! # Example
+//!
+//! ```
+//! use synckit_core::storage::{MemoryStorage, Storage};
+//! use synckit_core::Document;
+//!
+//! let mut storage = MemoryStorage::new();
+//!
+//! let mut doc = Document::new("doc-1".to_string());
+//! doc.set_field("title".to_string(), serde_json::json!("Hello"), 1, "client1".to_string());
+//! storage.save_document(&doc).unwrap();
+//!
+//! let loaded = storage.load_document(&"doc-1".to_string()).unwrap().unwrap();
+//! assert_eq!(loaded, doc);
+//! ```
+
+pub mod memory;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file;
+
+pub use memory::MemoryStorage;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileStorage;
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::sync::{apply_delta, Delta};
+use crate::DocumentID;
+
+/// Persistence backend for documents and their deltas
+///
+/// A backend stores at most one snapshot per document plus the deltas
+/// appended since that snapshot. Saving a new snapshot supersedes (and
+/// discards) the deltas appended before it, so callers should only save
+/// snapshots that already include those changes.
+pub trait Storage {
+    /// Load the latest snapshot of a document, if one was saved
+    ///
+    /// Durable backends don't keep the document's replica clock or merge
+    /// policies; re-attach them with `set_replica` and `set_merge_policy`.
+    fn load_document(&self, document_id: &DocumentID) -> Result<Option<Document>>;
+
+    /// Save a snapshot of a document, replacing any previous snapshot
+    fn save_document(&mut self, document: &Document) -> Result<()>;
+
+    /// Append a delta for a document
+    ///
+    /// The document does not need to have a snapshot yet.
+    fn append_delta(&mut self, delta: &Delta) -> Result<()>;
+
+    /// Load all deltas appended since the document's last snapshot, in append order
+    fn load_deltas(&self, document_id: &DocumentID) -> Result<Vec<Delta>>;
+
+    /// Delete a document's snapshot and deltas
+    fn delete_document(&mut self, document_id: &DocumentID) -> Result<()>;
+
+    /// List the IDs of all documents with a snapshot or pending deltas (sorted)
+    fn list_documents(&self) -> Result<Vec<DocumentID>>;
+
+    /// Load a document with all its pending deltas applied
+    ///
+    /// Returns `None` if neither a snapshot nor deltas exist for the document.
+    fn load_latest(&self, document_id: &DocumentID) -> Result<Option<Document>> {
+        let snapshot = self.load_document(document_id)?;
+        let deltas = self.load_deltas(document_id)?;
+
+        if snapshot.is_none() && deltas.is_empty() {
+            return Ok(None);
+        }
+
+        let mut document = snapshot.unwrap_or_else(|| Document::new(document_id.clone()));
+        for delta in &deltas {
+            apply_delta(&mut document, delta);
+        }
 
-// Placeholder for Phase 2
+        Ok(Some(document))
+    }
+}
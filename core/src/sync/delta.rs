@@ -4,7 +4,7 @@
 //! Only transmits fields that actually changed rather than full documents.
 
 use crate::document::{Document, Field};
-use crate::sync::VectorClock;
+use crate::sync::{Timestamp, VectorClock};
 use crate::{DocumentID, FieldPath};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -21,6 +21,10 @@ pub struct Delta {
     /// Changed fields (only includes fields that differ)
     pub fields: HashMap<FieldPath, Field>,
 
+    /// Deleted fields with their deletion timestamps
+    #[serde(default)]
+    pub tombstones: HashMap<FieldPath, Timestamp>,
+
     /// Vector clock after applying this delta
     pub version: VectorClock,
 }
@@ -35,6 +39,7 @@ impl Delta {
         Self {
             document_id,
             fields,
+            tombstones: HashMap::new(),
             version,
         }
     }
@@ -44,24 +49,32 @@ impl Delta {
         Self {
             document_id,
             fields: HashMap::new(),
+            tombstones: HashMap::new(),
             version,
         }
     }
 
+    /// Add deleted fields to this delta
+    pub fn with_tombstones(mut self, tombstones: HashMap<FieldPath, Timestamp>) -> Self {
+        self.tombstones = tombstones;
+        self
+    }
+
     /// Check if delta is empty (no changes)
     pub fn is_empty(&self) -> bool {
-        self.fields.is_empty()
+        self.fields.is_empty() && self.tombstones.is_empty()
     }
 
-    /// Get the number of changed fields
+    /// Get the number of changed and deleted fields
     pub fn len(&self) -> usize {
-        self.fields.len()
+        self.fields.len() + self.tombstones.len()
     }
 }
 
 /// Compute delta between two documents
 ///
-/// Returns a Delta containing only fields that changed between old and new.
+/// Returns a Delta containing only fields that changed between old and new,
+/// plus tombstones for fields deleted since old.
 /// If documents have the same content, returns an empty delta.
 ///
 /// # Example
@@ -92,16 +105,24 @@ pub fn compute_delta(old: &Document, new: &Document) -> Delta {
         }
     }
 
-    // Note: Deleted fields would be represented as tombstones in a full implementation
-    // For now, we only track additions and modifications
+    // Find tombstones that are new or newer than in old document
+    let mut changed_tombstones = HashMap::new();
+    for (field_path, deleted_at) in &new.tombstones {
+        if old.tombstones.get(field_path) != Some(deleted_at) {
+            changed_tombstones.insert(field_path.clone(), deleted_at.clone());
+        }
+    }
 
     Delta::new(new.id.clone(), changed_fields, new.version.clone())
+        .with_tombstones(changed_tombstones)
 }
 
 /// Apply a delta to a document
 ///
 /// Updates the document with all changes from the delta using LWW merge semantics.
-/// If a field in the delta is newer, it replaces the local field.
+/// If a field in the delta is newer, it replaces the local field; if a
+/// tombstone in the delta is newer, the local field is deleted. Fields with
+/// a merge policy registered on the document are combined by that policy.
 ///
 /// # Example
 /// ```ignore
@@ -113,30 +134,14 @@ pub fn apply_delta(doc: &mut Document, delta: &Delta) {
     // Verify we're applying to the correct document
     assert_eq!(doc.id, delta.document_id, "Delta document ID mismatch");
 
-    // Apply each changed field using LWW merge
+    // Apply each changed field using LWW merge (or the field's merge policy)
     for (field_path, delta_field) in &delta.fields {
-        match doc.fields.get(field_path) {
-            Some(local_field) => {
-                // Field exists locally - use LWW merge
-                match delta_field.timestamp.cmp(&local_field.timestamp) {
-                    std::cmp::Ordering::Greater => {
-                        doc.fields.insert(field_path.clone(), delta_field.clone());
-                    }
-                    std::cmp::Ordering::Equal => {
-                        // Tie-breaking: use client_id comparison
-                        if delta_field.timestamp.client_id > local_field.timestamp.client_id {
-                            doc.fields.insert(field_path.clone(), delta_field.clone());
-                        }
-                    }
-                    std::cmp::Ordering::Less => {} // local is newer, keep local
-                }
-                // else: local is newer, keep local
-            }
-            None => {
-                // New field - insert it
-                doc.fields.insert(field_path.clone(), delta_field.clone());
-            }
-        }
+        doc.merge_field(field_path.clone(), delta_field.clone());
+    }
+
+    // Apply each deletion using LWW merge
+    for (field_path, deleted_at) in &delta.tombstones {
+        doc.merge_tombstone(field_path.clone(), deleted_at.clone());
     }
 
     // Merge vector clocks
@@ -146,7 +151,7 @@ pub fn apply_delta(doc: &mut Document, delta: &Delta) {
 /// Merge two deltas into a single delta
 ///
 /// Combines changes from both deltas, using LWW semantics when the same field
-/// is modified in both deltas.
+/// is modified (or deleted) in both deltas.
 ///
 /// Useful for combining multiple pending changes before transmission.
 pub fn merge_deltas(delta1: &Delta, delta2: &Delta) -> Delta {
@@ -155,44 +160,18 @@ pub fn merge_deltas(delta1: &Delta, delta2: &Delta) -> Delta {
         "Cannot merge deltas for different documents"
     );
 
-    let mut merged_fields = delta1.fields.clone();
-
-    // Merge fields from delta2
-    for (field_path, field2) in &delta2.fields {
-        match merged_fields.get(field_path) {
-            Some(field1) => {
-                // Field in both deltas - use LWW
-                match field2.timestamp.cmp(&field1.timestamp) {
-                    std::cmp::Ordering::Greater => {
-                        merged_fields.insert(field_path.clone(), field2.clone());
-                    }
-                    std::cmp::Ordering::Equal => {
-                        // Tie-breaking
-                        if field2.timestamp.client_id > field1.timestamp.client_id {
-                            merged_fields.insert(field_path.clone(), field2.clone());
-                        }
-                    }
-                    std::cmp::Ordering::Less => {} // field1 is newer, keep it
-                }
-            }
-            None => {
-                // New field from delta2
-                merged_fields.insert(field_path.clone(), field2.clone());
-            }
-        }
-    }
-
-    // Merge vector clocks
-    let mut merged_version = delta1.version.clone();
-    merged_version.merge(&delta2.version);
+    // Resolve conflicts with the same LWW rules a document uses
+    let mut merged = Document::new(delta1.document_id.clone());
+    apply_delta(&mut merged, delta1);
+    apply_delta(&mut merged, delta2);
 
-    Delta::new(delta1.document_id.clone(), merged_fields, merged_version)
+    Delta::new(merged.id, merged.fields, merged.version).with_tombstones(merged.tombstones)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::sync::Timestamp;
+    use crate::sync::MergePolicy;
     use serde_json::json;
 
     #[test]
@@ -343,6 +322,29 @@ mod tests {
         assert_eq!(doc.fields["title"].timestamp.clock, 2);
     }
 
+    #[test]
+    fn test_apply_delta_uses_merge_policy() {
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_merge_policy("tags", MergePolicy::Union);
+        doc.set_field("tags".to_string(), json!(["a"]), 2, "client1".to_string());
+
+        // Older, but its elements are kept
+        let mut delta_fields = HashMap::new();
+        delta_fields.insert(
+            "tags".to_string(),
+            Field {
+                value: json!(["b"]),
+                timestamp: Timestamp::new(1, "client2".to_string()),
+            },
+        );
+        let delta = Delta::new("doc1".to_string(), delta_fields, VectorClock::new());
+
+        apply_delta(&mut doc, &delta);
+
+        assert_eq!(doc.fields["tags"].value, json!(["a", "b"]));
+        assert_eq!(doc.fields["tags"].timestamp.clock, 2);
+    }
+
     #[test]
     fn test_merge_deltas_non_overlapping() {
         let mut fields1 = HashMap::new();
@@ -425,4 +427,81 @@ mod tests {
         assert_eq!(reconstructed.fields["title"], new.fields["title"]);
         assert_eq!(reconstructed.fields["body"], new.fields["body"]);
     }
+
+    #[test]
+    fn test_compute_delta_includes_tombstones() {
+        let mut old = Document::new("doc1".to_string());
+        old.set_field(
+            "title".to_string(),
+            json!("Hello"),
+            1,
+            "client1".to_string(),
+        );
+
+        let mut new = old.clone();
+        new.delete_field(&"title".to_string(), 2, "client1".to_string());
+
+        let delta = compute_delta(&old, &new);
+
+        assert_eq!(delta.len(), 1);
+        assert!(delta.fields.is_empty());
+        assert_eq!(
+            delta.tombstones["title"],
+            Timestamp::new(2, "client1".to_string())
+        );
+
+        let mut reconstructed = old.clone();
+        apply_delta(&mut reconstructed, &delta);
+        assert_eq!(reconstructed, new);
+    }
+
+    #[test]
+    fn test_apply_delta_older_tombstone_keeps_field() {
+        let mut doc = Document::new("doc1".to_string());
+        doc.set_field("title".to_string(), json!("New"), 3, "client1".to_string());
+
+        let mut tombstones = HashMap::new();
+        tombstones.insert(
+            "title".to_string(),
+            Timestamp::new(2, "client2".to_string()),
+        );
+        let delta =
+            Delta::empty("doc1".to_string(), VectorClock::new()).with_tombstones(tombstones);
+
+        apply_delta(&mut doc, &delta);
+
+        assert_eq!(doc.fields["title"].value, json!("New"));
+    }
+
+    #[test]
+    fn test_merge_deltas_tombstone_vs_field() {
+        let mut fields = HashMap::new();
+        fields.insert(
+            "title".to_string(),
+            Field {
+                value: json!("Old"),
+                timestamp: Timestamp::new(1, "client1".to_string()),
+            },
+        );
+        let mut tombstones = HashMap::new();
+        tombstones.insert(
+            "title".to_string(),
+            Timestamp::new(2, "client2".to_string()),
+        );
+
+        let delta1 = Delta::new("doc1".to_string(), fields, VectorClock::new());
+        let delta2 =
+            Delta::empty("doc1".to_string(), VectorClock::new()).with_tombstones(tombstones);
+
+        for merged in [
+            merge_deltas(&delta1, &delta2),
+            merge_deltas(&delta2, &delta1),
+        ] {
+            assert!(merged.fields.is_empty());
+            assert_eq!(
+                merged.tombstones["title"],
+                Timestamp::new(2, "client2".to_string())
+            );
+        }
+    }
 }
@@ -0,0 +1,183 @@
+//! Hybrid Logical Clock for LWW timestamps
+//!
+//! A hybrid logical clock (HLC) produces timestamps that follow wall-clock
+//! time when clocks are in sync, yet never go backwards and always order
+//! after every timestamp the replica has seen. A replica with a fast or
+//! slow wall clock therefore can't win (or lose) LWW by more than its skew.
+//!
+//! Clock values are packed into the `u64` of `Timestamp::clock`:
+//! the upper 48 bits hold milliseconds since the Unix epoch, the lower 16
+//! bits a logical counter for events within the same millisecond.
+
+use super::Timestamp;
+use crate::error::{Result, SyncError};
+use crate::ClientID;
+use serde::{Deserialize, Serialize};
+
+/// Number of low bits used by the logical counter
+pub const LOGICAL_BITS: u32 = 16;
+
+/// Hybrid logical clock tied to a replica ID
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HybridClock {
+    /// Replica that stamps its writes with this clock
+    replica_id: ClientID,
+
+    /// Highest clock value issued or observed
+    last: u64,
+}
+
+impl HybridClock {
+    /// Create a new clock for a replica
+    pub fn new(replica_id: ClientID) -> Self {
+        Self {
+            replica_id,
+            last: 0,
+        }
+    }
+
+    /// Get the replica ID
+    pub fn replica_id(&self) -> &ClientID {
+        &self.replica_id
+    }
+
+    /// Get the highest clock value issued or observed
+    pub fn last(&self) -> u64 {
+        self.last
+    }
+
+    /// Issue a timestamp for a local event using the system clock
+    ///
+    /// See `tick_at` for the error case.
+    pub fn now(&mut self) -> Result<Timestamp> {
+        self.tick_at(physical_now())
+    }
+
+    /// Issue a timestamp for a local event at a given wall-clock time (ms)
+    ///
+    /// The result is newer than every timestamp issued or observed before,
+    /// even if the wall clock went backwards. Returns
+    /// `SyncError::InvalidOperation` if the clock has reached `u64::MAX`
+    /// (e.g. after observing a bogus remote timestamp), since no newer
+    /// value exists.
+    pub fn tick_at(&mut self, physical_ms: u64) -> Result<Timestamp> {
+        let wall = pack(physical_ms, 0);
+        let next = self.last.checked_add(1).ok_or_else(|| {
+            SyncError::InvalidOperation(format!("Clock of {} is exhausted", self.replica_id))
+        })?;
+        self.last = next.max(wall);
+        Ok(Timestamp::new(self.last, self.replica_id.clone()))
+    }
+
+    /// Absorb a timestamp received from another replica
+    ///
+    /// Local timestamps issued afterwards order after it.
+    pub fn observe(&mut self, remote: &Timestamp) {
+        self.last = self.last.max(remote.clock);
+    }
+}
+
+/// Pack wall-clock milliseconds and a logical counter into a clock value
+pub fn pack(physical_ms: u64, logical: u16) -> u64 {
+    (physical_ms << LOGICAL_BITS) | logical as u64
+}
+
+/// Wall-clock milliseconds of a packed clock value
+pub fn physical(clock: u64) -> u64 {
+    clock >> LOGICAL_BITS
+}
+
+/// Logical counter of a packed clock value
+pub fn logical(clock: u64) -> u16 {
+    (clock & ((1 << LOGICAL_BITS) - 1)) as u16
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn physical_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Current wall-clock time in milliseconds since the Unix epoch
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn physical_now() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+/// No wall clock available: the HLC degrades to a Lamport clock
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+pub(crate) fn physical_now() -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follows_wall_clock() {
+        let mut clock = HybridClock::new("c1".to_string());
+
+        let ts1 = clock.tick_at(1_000).unwrap();
+        let ts2 = clock.tick_at(2_000).unwrap();
+
+        assert_eq!(physical(ts1.clock), 1_000);
+        assert_eq!(logical(ts1.clock), 0);
+        assert_eq!(physical(ts2.clock), 2_000);
+        assert_eq!(ts2.client_id, "c1");
+    }
+
+    #[test]
+    fn test_monotonic_when_wall_clock_stalls_or_goes_back() {
+        let mut clock = HybridClock::new("c1".to_string());
+
+        let ts1 = clock.tick_at(1_000).unwrap();
+        let ts2 = clock.tick_at(1_000).unwrap();
+        let ts3 = clock.tick_at(500).unwrap();
+
+        assert!(ts2.is_newer_than(&ts1));
+        assert!(ts3.is_newer_than(&ts2));
+        assert_eq!(physical(ts3.clock), 1_000);
+        assert_eq!(logical(ts3.clock), 2);
+    }
+
+    #[test]
+    fn test_observe_orders_after_remote() {
+        let mut clock = HybridClock::new("c1".to_string());
+        clock.tick_at(1_000).unwrap();
+
+        // Remote replica with a clock running ahead
+        let remote = Timestamp::new(pack(5_000, 3), "c2".to_string());
+        clock.observe(&remote);
+
+        let local = clock.tick_at(1_001).unwrap();
+        assert!(local.is_newer_than(&remote));
+        assert_eq!(physical(local.clock), 5_000);
+        assert_eq!(logical(local.clock), 4);
+
+        // Observing an older timestamp changes nothing
+        clock.observe(&Timestamp::new(pack(10, 0), "c3".to_string()));
+        assert_eq!(clock.last(), local.clock);
+    }
+
+    #[test]
+    fn test_now_uses_system_clock() {
+        let mut clock = HybridClock::new("c1".to_string());
+        let ts = clock.now().unwrap();
+
+        // Some time after 2020-01-01
+        assert!(physical(ts.clock) > 1_577_836_800_000);
+    }
+
+    #[test]
+    fn test_exhausted_clock_fails() {
+        let mut clock = HybridClock::new("c1".to_string());
+        clock.observe(&Timestamp::new(u64::MAX, "c2".to_string()));
+
+        assert!(clock.tick_at(1_000).is_err());
+        assert_eq!(clock.last(), u64::MAX);
+    }
+}
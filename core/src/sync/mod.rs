@@ -3,15 +3,24 @@
 //! This module contains the core synchronization algorithms:
 //! - Vector clocks for causality tracking
 //! - Timestamps for LWW conflict resolution
+//! - Hybrid logical clock for issuing timestamps
 //! - LWW merge algorithm
+//! - Per-field merge policies
 //! - Delta computation
+//! - Causal stability for tombstone garbage collection
 
 pub mod delta;
+pub mod hlc;
 pub mod lww;
+pub mod policy;
+pub mod stability;
 pub mod vector_clock;
 
 pub use delta::{apply_delta, compute_delta, merge_deltas, Delta};
+pub use hlc::HybridClock;
 pub use lww::LWWField;
+pub use policy::{MergePolicies, MergePolicy};
+pub use stability::{StabilityTracker, Version};
 pub use vector_clock::VectorClock;
 
 use crate::ClientID;
@@ -0,0 +1,436 @@
+//! Per-field merge policies
+//!
+//! Document fields resolve conflicts with Last-Write-Wins by default. A
+//! `MergePolicies` registry assigns another policy to a field path or to a
+//! glob over paths, so simple cases don't need a separate CRDT type:
+//! - `Max` / `Min`: keep the larger / smaller number
+//! - `Union`: keep every element of both arrays
+//! - `Counter`: per-replica totals that add up (see `Document::increment`)
+//! - `Custom`: a user-supplied function
+//!
+//! A merged field keeps the newer of the two timestamps, so deletes still
+//! resolve against it by LWW.
+//!
+//! Values that don't fit a policy (e.g. a string under `Max`) rank below
+//! the ones that do, whatever their timestamps: a fitting value beats one
+//! that doesn't, and of two that don't, the larger serialized form wins.
+//! That keeps every built-in policy commutative and associative, so
+//! replicas converge in any merge order.
+//!
+//! Every replica must register the same policies, or they won't converge.
+//!
+//! # Globs
+//!
+//! Patterns are field paths (dotted or JSON Pointer) whose segments may be
+//! `*`, matching any one segment, or `**`, matching any number of them.
+//! An exact path takes precedence over globs; among globs, the first one
+//! registered wins.
+//!
+//! # Example
+//!
+//! ```
+//! use serde_json::json;
+//! use synckit_core::sync::MergePolicy;
+//! use synckit_core::Document;
+//!
+//! let mut doc1 = Document::new("doc-1".to_string());
+//! doc1.set_merge_policy("scores.*", MergePolicy::Max);
+//! let mut doc2 = doc1.clone();
+//!
+//! doc1.set_field("scores.alice".to_string(), json!(42), 1, "r1".to_string());
+//! doc2.set_field("scores.alice".to_string(), json!(17), 2, "r2".to_string());
+//! doc1.merge(&doc2);
+//!
+//! assert_eq!(doc1.get_path("scores.alice"), Some(json!(42)));
+//! ```
+
+use crate::{path, ClientID};
+use serde_json::{Map, Number, Value as JsonValue};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Function that merges a local and a remote value
+///
+/// Must be commutative, associative and idempotent for replicas to converge.
+pub type MergeFn = dyn Fn(&JsonValue, &JsonValue) -> JsonValue + Send + Sync;
+
+/// How concurrent values of a field are merged
+#[derive(Clone, Default)]
+pub enum MergePolicy {
+    /// Last-Write-Wins by timestamp
+    #[default]
+    Lww,
+
+    /// Keep the larger number
+    Max,
+
+    /// Keep the smaller number
+    Min,
+
+    /// Keep every element of both arrays, once each, in canonical order
+    ///
+    /// Elements can only be removed by deleting the field.
+    Union,
+
+    /// Counter of per-replica totals: `{"p": {replica: n}, "n": {replica: n}}`
+    Counter,
+
+    /// User-supplied merge function
+    Custom(Arc<MergeFn>),
+}
+
+impl MergePolicy {
+    /// Create a policy from a merge function
+    pub fn custom<F>(merge: F) -> Self
+    where
+        F: Fn(&JsonValue, &JsonValue) -> JsonValue + Send + Sync + 'static,
+    {
+        MergePolicy::Custom(Arc::new(merge))
+    }
+
+    /// Merge two values, or `None` if the policy is LWW
+    ///
+    /// A value that doesn't fit the policy loses to one that does; of two
+    /// such values, the larger serialized form wins.
+    pub fn combine(&self, local: &JsonValue, remote: &JsonValue) -> Option<JsonValue> {
+        match (self.fits(local), self.fits(remote)) {
+            (true, true) => {}
+            (true, false) => return Some(local.clone()),
+            (false, true) => return Some(remote.clone()),
+            (false, false) => {
+                let larger = if remote.to_string().cmp(&local.to_string()) == Ordering::Greater {
+                    remote
+                } else {
+                    local
+                };
+                return Some(larger.clone());
+            }
+        }
+
+        match (self, local, remote) {
+            (MergePolicy::Lww, _, _) => None,
+            (MergePolicy::Max, JsonValue::Number(a), JsonValue::Number(b)) => {
+                Some(JsonValue::Number(max_number(a, b).clone()))
+            }
+            (MergePolicy::Min, JsonValue::Number(a), JsonValue::Number(b)) => {
+                let max = max_number(a, b);
+                Some(JsonValue::Number(if max == a { b } else { a }.clone()))
+            }
+
+            (MergePolicy::Union, JsonValue::Array(a), JsonValue::Array(b)) => {
+                // Keyed by serialized form: deduplicates and fixes the order
+                let elements: BTreeMap<String, &JsonValue> = a
+                    .iter()
+                    .chain(b)
+                    .map(|element| (element.to_string(), element))
+                    .collect();
+                Some(JsonValue::Array(elements.into_values().cloned().collect()))
+            }
+            (MergePolicy::Counter, _, _) => {
+                let mut merged = counter_totals(local)?;
+                for (key, totals) in counter_totals(remote)? {
+                    let entry = merged.entry(key).or_default();
+                    for (replica, total) in totals {
+                        let current = entry.entry(replica).or_default();
+                        *current = (*current).max(total);
+                    }
+                }
+                Some(counter_json(&merged))
+            }
+            (MergePolicy::Custom(merge), _, _) => Some(merge(local, remote)),
+            (MergePolicy::Max | MergePolicy::Min | MergePolicy::Union, _, _) => {
+                unreachable!("Both values fit the policy")
+            }
+        }
+    }
+
+    /// Check if a value is of the kind the policy merges
+    fn fits(&self, value: &JsonValue) -> bool {
+        match self {
+            MergePolicy::Max | MergePolicy::Min => value.is_number(),
+            MergePolicy::Union => value.is_array(),
+            MergePolicy::Counter => counter_totals(value).is_some(),
+            MergePolicy::Lww | MergePolicy::Custom(_) => true,
+        }
+    }
+}
+
+impl fmt::Debug for MergePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergePolicy::Lww => write!(f, "Lww"),
+            MergePolicy::Max => write!(f, "Max"),
+            MergePolicy::Min => write!(f, "Min"),
+            MergePolicy::Union => write!(f, "Union"),
+            MergePolicy::Counter => write!(f, "Counter"),
+            MergePolicy::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+impl PartialEq for MergePolicy {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (MergePolicy::Custom(a), MergePolicy::Custom(b)) => Arc::ptr_eq(a, b),
+            _ => std::mem::discriminant(self) == std::mem::discriminant(other),
+        }
+    }
+}
+
+/// Merge policies registered by field path or glob
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergePolicies {
+    /// Rules in registration order, patterns split into segments
+    rules: Vec<(Vec<String>, MergePolicy)>,
+}
+
+impl MergePolicies {
+    /// Create an empty registry (every field uses LWW)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a policy for a path or glob, replacing an earlier one
+    pub fn set(&mut self, pattern: &str, policy: MergePolicy) {
+        let segments = path::parse(pattern);
+        match self.rules.iter_mut().find(|(rule, _)| *rule == segments) {
+            Some((_, existing)) => *existing = policy,
+            None => self.rules.push((segments, policy)),
+        }
+    }
+
+    /// Remove the policy registered for a path or glob
+    pub fn remove(&mut self, pattern: &str) -> Option<MergePolicy> {
+        let segments = path::parse(pattern);
+        let index = self.rules.iter().position(|(rule, _)| *rule == segments)?;
+        Some(self.rules.remove(index).1)
+    }
+
+    /// Get the policy for a field path
+    pub fn policy_for(&self, field_path: &str) -> &MergePolicy {
+        static LWW: MergePolicy = MergePolicy::Lww;
+
+        let segments = path::parse(field_path);
+        let exact = self.rules.iter().find(|(rule, _)| *rule == segments);
+        exact
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .find(|(rule, _)| glob_matches(rule, &segments))
+            })
+            .map_or(&LWW, |(_, policy)| policy)
+    }
+
+    /// Check if no policies are registered
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Match path segments against glob segments (`*` one, `**` any number)
+fn glob_matches(pattern: &[String], segments: &[String]) -> bool {
+    match pattern.split_first() {
+        None => segments.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=segments.len()).any(|skip| glob_matches(rest, &segments[skip..]))
+        }
+        Some((first, rest)) => match segments.split_first() {
+            Some((segment, remaining)) => {
+                (first == "*" || first == segment) && glob_matches(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Pick the larger of two numbers, breaking ties by their text
+fn max_number<'a>(a: &'a Number, b: &'a Number) -> &'a Number {
+    let ordering = match (a.as_i64(), b.as_i64(), a.as_u64(), b.as_u64()) {
+        (Some(x), Some(y), _, _) => x.cmp(&y),
+        (_, _, Some(x), Some(y)) => x.cmp(&y),
+        _ => {
+            let (x, y) = (a.as_f64().unwrap_or(0.0), b.as_f64().unwrap_or(0.0));
+            x.partial_cmp(&y).unwrap_or(Ordering::Equal)
+        }
+    };
+    match ordering.then_with(|| a.to_string().cmp(&b.to_string())) {
+        Ordering::Less => b,
+        _ => a,
+    }
+}
+
+/// Per-replica counter totals keyed by "p" (increments) and "n" (decrements)
+type CounterTotals = BTreeMap<String, BTreeMap<ClientID, u64>>;
+
+/// Read a counter value, or `None` if the value isn't one
+///
+/// Null stands for a counter nobody has touched yet.
+fn counter_totals(value: &JsonValue) -> Option<CounterTotals> {
+    let object = match value {
+        JsonValue::Null => return Some(CounterTotals::new()),
+        JsonValue::Object(object) => object,
+        _ => return None,
+    };
+
+    let mut totals = CounterTotals::new();
+    for (key, replicas) in object {
+        if key != "p" && key != "n" {
+            return None;
+        }
+        let entry = totals.entry(key.clone()).or_default();
+        for (replica, total) in replicas.as_object()? {
+            entry.insert(replica.clone(), total.as_u64()?);
+        }
+    }
+    Some(totals)
+}
+
+/// Write counter totals back as JSON
+fn counter_json(totals: &CounterTotals) -> JsonValue {
+    let object: Map<String, JsonValue> = totals
+        .iter()
+        .map(|(key, replicas)| {
+            let replicas: Map<String, JsonValue> = replicas
+                .iter()
+                .map(|(replica, total)| (replica.clone(), JsonValue::from(*total)))
+                .collect();
+            (key.clone(), JsonValue::Object(replicas))
+        })
+        .collect();
+    JsonValue::Object(object)
+}
+
+/// Get the value of a counter: increments minus decrements
+///
+/// Returns `None` if the value isn't a counter.
+pub fn counter_value(value: &JsonValue) -> Option<i64> {
+    let totals = counter_totals(value)?;
+    let sum = |key: &str| -> i128 {
+        totals
+            .get(key)
+            .map(|replicas| replicas.values().map(|&n| n as i128).sum())
+            .unwrap_or(0)
+    };
+    Some((sum("p") - sum("n")).clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+}
+
+/// Add an amount to a replica's totals in a counter value
+///
+/// Returns `None` if the value isn't a counter or a total would overflow.
+pub fn counter_add(value: &JsonValue, replica_id: &ClientID, amount: i64) -> Option<JsonValue> {
+    let mut totals = counter_totals(value)?;
+    let key = if amount < 0 { "n" } else { "p" };
+    let total = totals
+        .entry(key.to_string())
+        .or_default()
+        .entry(replica_id.clone())
+        .or_default();
+    *total = total.checked_add(amount.unsigned_abs())?;
+    Some(counter_json(&totals))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn matches(pattern: &str, field_path: &str) -> bool {
+        glob_matches(&path::parse(pattern), &path::parse(field_path))
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(matches("scores.*", "scores.alice"));
+        assert!(!matches("scores.*", "scores"));
+        assert!(!matches("scores.*", "scores.alice.best"));
+        assert!(matches("**.tags", "tags"));
+        assert!(matches("**.tags", "posts.1.tags"));
+        assert!(matches("/posts/*/tags", "posts.1.tags"));
+        assert!(!matches("posts.**.tags", "posts.1.title"));
+    }
+
+    #[test]
+    fn test_exact_path_beats_glob() {
+        let mut policies = MergePolicies::new();
+        policies.set("stats.*", MergePolicy::Max);
+        policies.set("stats.low", MergePolicy::Min);
+
+        assert_eq!(policies.policy_for("stats.high"), &MergePolicy::Max);
+        assert_eq!(policies.policy_for("stats.low"), &MergePolicy::Min);
+        assert_eq!(policies.policy_for("title"), &MergePolicy::Lww);
+
+        policies.remove("stats.low");
+        assert_eq!(policies.policy_for("stats.low"), &MergePolicy::Max);
+    }
+
+    #[test]
+    fn test_numeric_policies() {
+        assert_eq!(
+            MergePolicy::Max.combine(&json!(3), &json!(7.5)),
+            Some(json!(7.5))
+        );
+        assert_eq!(
+            MergePolicy::Min.combine(&json!(3), &json!(7.5)),
+            Some(json!(3))
+        );
+        assert_eq!(
+            MergePolicy::Min.combine(&json!(-1), &json!(u64::MAX)),
+            Some(json!(-1))
+        );
+
+        // Numbers beat other values under both policies
+        assert_eq!(
+            MergePolicy::Max.combine(&json!("a"), &json!(1)),
+            Some(json!(1))
+        );
+        assert_eq!(
+            MergePolicy::Min.combine(&json!(1), &json!("a")),
+            Some(json!(1))
+        );
+        assert_eq!(
+            MergePolicy::Max.combine(&json!("a"), &json!("b")),
+            Some(json!("b"))
+        );
+    }
+
+    #[test]
+    fn test_union_is_order_independent() {
+        let a = json!(["x", 1, "y"]);
+        let b = json!(["y", "z"]);
+
+        let merged = MergePolicy::Union.combine(&a, &b).unwrap();
+        assert_eq!(merged, MergePolicy::Union.combine(&b, &a).unwrap());
+        assert_eq!(merged.as_array().unwrap().len(), 4);
+        assert_eq!(MergePolicy::Union.combine(&merged, &a), Some(merged));
+    }
+
+    #[test]
+    fn test_counter_totals_add_up() {
+        let replica1 = counter_add(&JsonValue::Null, &"r1".to_string(), 5).unwrap();
+        let replica2 = counter_add(&JsonValue::Null, &"r2".to_string(), -2).unwrap();
+
+        let merged = MergePolicy::Counter.combine(&replica1, &replica2).unwrap();
+        assert_eq!(counter_value(&merged), Some(3));
+        assert_eq!(
+            MergePolicy::Counter.combine(&merged, &replica1),
+            Some(merged)
+        );
+        assert_eq!(
+            MergePolicy::Counter.combine(&json!(1), &replica1),
+            Some(replica1)
+        );
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let larger = MergePolicy::custom(|a, b| {
+            let (a, b) = (a.as_str().unwrap_or(""), b.as_str().unwrap_or(""));
+            json!(a.max(b))
+        });
+        assert_eq!(larger.combine(&json!("a"), &json!("b")), Some(json!("b")));
+        assert_eq!(larger, larger.clone());
+        assert_ne!(larger, MergePolicy::custom(|a, _| a.clone()));
+    }
+}
@@ -0,0 +1,158 @@
+//! Causal stability for tombstone garbage collection
+//!
+//! A tombstone (a deleted field, deleted text) has to be kept only as long
+//! as some replica might still send the state it deletes.
+//! Once every known replica has seen the delete, it is causally stable and
+//! the tombstone can be dropped.
+//!
+//! `StabilityTracker` records the version each known peer has acknowledged
+//! and computes the stable version: the pointwise minimum over all peers
+//! and the local replica. `Text::collect_garbage` and
+//! `Document::collect_garbage` then drop the tombstones covered by it.
+//!
+//! `ORSet` needs no collection: a remove leaves no tombstone, only the
+//! removed dots' counters in its causal context's version vector. Dots
+//! that arrived past a gap wait in the context's cloud and are folded into
+//! the version vector as soon as the gap fills. They can't be folded any
+//! earlier, since the missing dots may be adds this replica hasn't seen.
+//!
+//! Stability only covers the peers the tracker knows about. A replica that
+//! was never registered (or an old backup) may still resurrect collected
+//! state, so register every replica before collecting.
+
+use super::VectorClock;
+use crate::ClientID;
+use std::collections::HashMap;
+
+/// A version vector that can be combined pointwise
+pub trait Version: Clone {
+    /// Raise every entry to at least the other version's
+    fn join(&mut self, other: &Self);
+
+    /// Pointwise minimum of both versions
+    fn meet(&self, other: &Self) -> Self;
+}
+
+impl Version for VectorClock {
+    fn join(&mut self, other: &Self) {
+        self.merge(other);
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        let mut result = VectorClock::new();
+        for (client_id, &clock) in &self.clocks {
+            let clock = clock.min(other.get(client_id));
+            if clock > 0 {
+                result.update(client_id, clock);
+            }
+        }
+        result
+    }
+}
+
+/// Versions acknowledged by the known peers of a replica
+#[derive(Debug, Clone, PartialEq)]
+pub struct StabilityTracker<V: Version = VectorClock> {
+    /// Latest acknowledged version of each peer
+    peers: HashMap<ClientID, V>,
+}
+
+impl<V: Version> StabilityTracker<V> {
+    /// Create a tracker with no known peers
+    pub fn new() -> Self {
+        Self {
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Record the version a peer has acknowledged
+    ///
+    /// Registers unknown peers. Acknowledgements only move forward: an
+    /// older version arriving late doesn't lower what the peer has seen.
+    pub fn acknowledge(&mut self, peer: ClientID, version: &V) {
+        match self.peers.get_mut(&peer) {
+            Some(acked) => acked.join(version),
+            None => {
+                self.peers.insert(peer, version.clone());
+            }
+        }
+    }
+
+    /// Stop waiting for a peer (e.g. it left for good)
+    ///
+    /// Returns false if the peer wasn't known.
+    pub fn remove_peer(&mut self, peer: &ClientID) -> bool {
+        self.peers.remove(peer).is_some()
+    }
+
+    /// Get the version a peer has acknowledged
+    pub fn acknowledged(&self, peer: &ClientID) -> Option<&V> {
+        self.peers.get(peer)
+    }
+
+    /// Number of known peers
+    pub fn peer_count(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Get the version every known peer and the local replica have seen
+    pub fn stable(&self, local: &V) -> V {
+        self.peers
+            .values()
+            .fold(local.clone(), |stable, acked| stable.meet(acked))
+    }
+}
+
+impl<V: Version> Default for StabilityTracker<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock(entries: &[(&str, u64)]) -> VectorClock {
+        let mut clock = VectorClock::new();
+        for &(client_id, value) in entries {
+            clock.update(&client_id.to_string(), value);
+        }
+        clock
+    }
+
+    #[test]
+    fn test_stable_is_pointwise_minimum() {
+        let mut tracker = StabilityTracker::new();
+        tracker.acknowledge("b".to_string(), &clock(&[("a", 3), ("b", 5)]));
+        tracker.acknowledge("c".to_string(), &clock(&[("a", 4), ("b", 2), ("c", 1)]));
+
+        let local = clock(&[("a", 5), ("b", 5), ("c", 1)]);
+        assert_eq!(tracker.stable(&local), clock(&[("a", 3), ("b", 2)]));
+    }
+
+    #[test]
+    fn test_acknowledgements_only_move_forward() {
+        let mut tracker = StabilityTracker::new();
+        tracker.acknowledge("b".to_string(), &clock(&[("a", 5)]));
+        tracker.acknowledge("b".to_string(), &clock(&[("a", 2)]));
+
+        assert_eq!(
+            tracker.acknowledged(&"b".to_string()),
+            Some(&clock(&[("a", 5)]))
+        );
+    }
+
+    #[test]
+    fn test_no_peers_everything_local_is_stable() {
+        let mut tracker = StabilityTracker::new();
+        let local = clock(&[("a", 5)]);
+        assert_eq!(tracker.stable(&local), local);
+
+        tracker.acknowledge("b".to_string(), &VectorClock::new());
+        assert_eq!(tracker.stable(&local), VectorClock::new());
+
+        assert!(tracker.remove_peer(&"b".to_string()));
+        assert_eq!(tracker.stable(&local), local);
+    }
+}
@@ -0,0 +1,693 @@
+//! Replica-local undo/redo
+//!
+//! Undo in a collaborative setting must not simply restore an old state:
+//! that would overwrite what other users did in the meantime. Instead,
+//! `UndoManager` records the local replica's changes and undoes them by
+//! issuing new CRDT operations that revert only those changes:
+//! - Document: a field gets its previous value back, unless someone else
+//!   wrote it (or a path above or below it) since
+//! - Text: inserted characters are deleted by ID, deleted ones are
+//!   re-inserted where they used to be, around any concurrent edits
+//!
+//! Changes made within the capture timeout of each other are grouped and
+//! undone together (like a burst of typing).
+//!
+//! # Example
+//!
+//! ```
+//! use serde_json::json;
+//! use synckit_core::{Document, UndoManager};
+//!
+//! let mut doc = Document::with_replica("doc-1".to_string(), "alice".to_string());
+//! let mut undo = UndoManager::new();
+//!
+//! undo.set(&mut doc, "title".to_string(), json!("Draft")).unwrap();
+//! assert!(undo.undo(&mut doc));
+//! assert_eq!(doc.get_path("title"), None);
+//!
+//! assert!(undo.redo(&mut doc));
+//! assert_eq!(doc.get_path("title"), Some(json!("Draft")));
+//! ```
+
+use crate::document::Document;
+use crate::error::Result;
+use crate::sync::hlc::physical_now;
+use crate::sync::Timestamp;
+use crate::{path, ClientID, FieldPath};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeSet;
+
+#[cfg(feature = "text-crdt")]
+use crate::crdt::text::{ItemId, Text};
+#[cfg(feature = "text-crdt")]
+use std::collections::HashMap;
+
+/// Default capture timeout in milliseconds
+pub const DEFAULT_CAPTURE_TIMEOUT: u64 = 500;
+
+/// A CRDT whose local changes can be reverted
+pub trait Undoable {
+    /// A recorded local change
+    type Change;
+
+    /// Bookkeeping kept across undo/redo calls
+    type Context: Default;
+
+    /// Revert a group of changes (given in the order they were made)
+    ///
+    /// Changes overwritten by other replicas are skipped. Returns the
+    /// changes that re-apply what was reverted, in the order they were made.
+    fn revert(
+        &mut self,
+        changes: &[Self::Change],
+        context: &mut Self::Context,
+    ) -> Vec<Self::Change>;
+}
+
+/// Undo/redo stacks of local changes to one CRDT
+pub struct UndoManager<T: Undoable> {
+    /// Groups of changes to undo, oldest first
+    undo_stack: Vec<Vec<T::Change>>,
+
+    /// Groups of undone changes to redo, oldest first
+    redo_stack: Vec<Vec<T::Change>>,
+
+    /// Changes closer together than this (ms) are grouped
+    capture_timeout: u64,
+
+    /// Time of the last recorded change, if the group is still open
+    last_capture: Option<u64>,
+
+    /// Bookkeeping for the target CRDT
+    context: T::Context,
+}
+
+impl<T: Undoable> Default for UndoManager<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Undoable> UndoManager<T> {
+    /// Create an undo manager with the default capture timeout
+    pub fn new() -> Self {
+        Self::with_capture_timeout(DEFAULT_CAPTURE_TIMEOUT)
+    }
+
+    /// Create an undo manager grouping changes closer than `timeout_ms`
+    pub fn with_capture_timeout(timeout_ms: u64) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            capture_timeout: timeout_ms,
+            last_capture: None,
+            context: T::Context::default(),
+        }
+    }
+
+    /// Record a local change using the system clock
+    pub fn record(&mut self, change: T::Change) {
+        self.record_at(change, physical_now());
+    }
+
+    /// Record a local change made at a given time (ms)
+    ///
+    /// Clears the redo stack.
+    pub fn record_at(&mut self, change: T::Change, now_ms: u64) {
+        self.redo_stack.clear();
+
+        let grouped = self
+            .last_capture
+            .is_some_and(|last| now_ms.saturating_sub(last) < self.capture_timeout);
+        match self.undo_stack.last_mut() {
+            Some(group) if grouped => group.push(change),
+            _ => self.undo_stack.push(vec![change]),
+        }
+
+        self.last_capture = Some(now_ms);
+    }
+
+    /// Start a new group with the next recorded change
+    pub fn stop_capturing(&mut self) {
+        self.last_capture = None;
+    }
+
+    /// Undo the most recent group of changes
+    ///
+    /// Groups whose changes were all overwritten by other replicas are
+    /// dropped. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, target: &mut T) -> bool {
+        let undone = Self::replay(
+            target,
+            &mut self.context,
+            &mut self.undo_stack,
+            &mut self.redo_stack,
+        );
+        self.last_capture = None;
+        undone
+    }
+
+    /// Redo the most recently undone group of changes
+    ///
+    /// Returns false if there was nothing to redo.
+    pub fn redo(&mut self, target: &mut T) -> bool {
+        let redone = Self::replay(
+            target,
+            &mut self.context,
+            &mut self.redo_stack,
+            &mut self.undo_stack,
+        );
+        self.last_capture = None;
+        redone
+    }
+
+    /// Check if there is anything to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Check if there is anything to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Forget all recorded changes
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_capture = None;
+        self.context = T::Context::default();
+    }
+
+    /// Revert the newest effective group of `from` and push its inverse
+    fn replay(
+        target: &mut T,
+        context: &mut T::Context,
+        from: &mut Vec<Vec<T::Change>>,
+        to: &mut Vec<Vec<T::Change>>,
+    ) -> bool {
+        while let Some(group) = from.pop() {
+            let inverse = target.revert(&group, context);
+            if !inverse.is_empty() {
+                to.push(inverse);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// A local write or delete of a Document field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Path that was written (normalized)
+    pub path: FieldPath,
+
+    /// Value at the path before the change (None if there was nothing)
+    pub before: Option<JsonValue>,
+
+    /// Value written (None for a delete)
+    pub after: Option<JsonValue>,
+
+    /// Timestamp of the write
+    pub timestamp: Timestamp,
+}
+
+impl Undoable for Document {
+    type Change = FieldChange;
+
+    /// Timestamps of the writes made by undo/redo
+    type Context = BTreeSet<Timestamp>;
+
+    /// Restore each field's previous value with a new write
+    ///
+    /// A change is skipped if the field, an ancestor or a descendant was
+    /// written since by anyone other than this change or undo/redo.
+    /// Restoring writes use the document's replica clock if it has one,
+    /// otherwise the clock after the value they replace.
+    fn revert(
+        &mut self,
+        changes: &[FieldChange],
+        written: &mut BTreeSet<Timestamp>,
+    ) -> Vec<FieldChange> {
+        let mut reverted = Vec::new();
+
+        for change in changes.iter().rev() {
+            let ours = |timestamp: &Timestamp| {
+                *timestamp == change.timestamp || written.contains(timestamp)
+            };
+
+            // The entry deciding the path's value: its own or a newer ancestor's
+            let governing = path::ancestors(&change.path)
+                .iter()
+                .chain(std::iter::once(&change.path))
+                .filter_map(|candidate| self.timestamp_of(candidate))
+                .max()
+                .cloned();
+            let Some(governing) = governing.filter(|timestamp| ours(timestamp)) else {
+                continue;
+            };
+
+            let below: Vec<&Timestamp> = self
+                .fields
+                .iter()
+                .map(|(candidate, field)| (candidate, &field.timestamp))
+                .chain(self.tombstones.iter())
+                .filter(|(candidate, _)| path::is_descendant(&change.path, candidate))
+                .map(|(_, timestamp)| timestamp)
+                .collect();
+            if below
+                .iter()
+                .any(|timestamp| timestamp.is_newer_than(&change.timestamp) && !ours(timestamp))
+            {
+                continue;
+            }
+
+            let newest = below.into_iter().fold(governing.clock, |clock, timestamp| {
+                clock.max(timestamp.clock)
+            });
+            let timestamp = match self.restore(change, newest) {
+                Ok(timestamp) => timestamp,
+                Err(_) => continue,
+            };
+            written.insert(timestamp.clone());
+            reverted.push(FieldChange {
+                path: change.path.clone(),
+                before: change.after.clone(),
+                after: change.before.clone(),
+                timestamp,
+            });
+        }
+
+        reverted
+    }
+}
+
+impl Document {
+    /// Write a change's previous value back, returning the write's timestamp
+    ///
+    /// Without a replica clock the write uses the clock after `newest`.
+    fn restore(&mut self, change: &FieldChange, newest: u64) -> Result<Timestamp> {
+        if self.clock.is_some() {
+            return match &change.before {
+                Some(value) => self.set(change.path.clone(), value.clone()),
+                None => self.delete(&change.path),
+            };
+        }
+
+        let clock = newest + 1;
+        let client_id = change.timestamp.client_id.clone();
+        match &change.before {
+            Some(value) => {
+                self.set_field(change.path.clone(), value.clone(), clock, client_id.clone())
+            }
+            None => self.delete_field(&change.path, clock, client_id.clone()),
+        }
+        Ok(Timestamp::new(clock, client_id))
+    }
+}
+
+impl UndoManager<Document> {
+    /// Set a field (see `Document::set_field`) and record the change
+    pub fn set_field(
+        &mut self,
+        document: &mut Document,
+        field_path: FieldPath,
+        value: JsonValue,
+        clock: u64,
+        client_id: ClientID,
+    ) {
+        let before = document.get_path(&field_path);
+        let timestamp = Timestamp::new(clock, client_id.clone());
+        document.set_field(field_path.clone(), value.clone(), clock, client_id);
+        self.record_write(document, &field_path, before, Some(value), timestamp);
+    }
+
+    /// Delete a field (see `Document::delete_field`) and record the change
+    pub fn delete_field(
+        &mut self,
+        document: &mut Document,
+        field_path: &FieldPath,
+        clock: u64,
+        client_id: ClientID,
+    ) {
+        let before = document.get_path(field_path);
+        let timestamp = Timestamp::new(clock, client_id.clone());
+        document.delete_field(field_path, clock, client_id);
+        self.record_write(document, field_path, before, None, timestamp);
+    }
+
+    /// Set a field stamped by the document's clock and record the change
+    pub fn set(
+        &mut self,
+        document: &mut Document,
+        field_path: FieldPath,
+        value: JsonValue,
+    ) -> Result<Timestamp> {
+        let before = document.get_path(&field_path);
+        let timestamp = document.set(field_path.clone(), value.clone())?;
+        self.record_write(
+            document,
+            &field_path,
+            before,
+            Some(value),
+            timestamp.clone(),
+        );
+        Ok(timestamp)
+    }
+
+    /// Delete a field stamped by the document's clock and record the change
+    pub fn delete(&mut self, document: &mut Document, field_path: &FieldPath) -> Result<Timestamp> {
+        let before = document.get_path(field_path);
+        let timestamp = document.delete(field_path)?;
+        self.record_write(document, field_path, before, None, timestamp.clone());
+        Ok(timestamp)
+    }
+
+    /// Record a write unless it lost against a newer entry
+    fn record_write(
+        &mut self,
+        document: &Document,
+        field_path: &str,
+        before: Option<JsonValue>,
+        after: Option<JsonValue>,
+        timestamp: Timestamp,
+    ) {
+        if document.timestamp_of(field_path) == Some(&timestamp) {
+            self.record(FieldChange {
+                path: path::normalize(field_path),
+                before,
+                after,
+                timestamp,
+            });
+        }
+    }
+}
+
+/// A local insert or delete in a Text
+#[cfg(feature = "text-crdt")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextChange {
+    /// Characters were inserted
+    Inserted(Vec<ItemId>),
+
+    /// Characters were deleted
+    Deleted(Vec<ItemId>),
+}
+
+#[cfg(feature = "text-crdt")]
+impl Undoable for Text {
+    type Change = TextChange;
+
+    /// Characters re-inserted by undo/redo, by the ID of the original
+    type Context = HashMap<ItemId, ItemId>;
+
+    /// Delete inserted characters and re-insert deleted ones
+    ///
+    /// Characters someone else deleted in the meantime stay deleted.
+    fn revert(
+        &mut self,
+        changes: &[TextChange],
+        restored: &mut HashMap<ItemId, ItemId>,
+    ) -> Vec<TextChange> {
+        let mut reverted = Vec::new();
+
+        for change in changes.iter().rev() {
+            match change {
+                TextChange::Inserted(ids) => {
+                    // Delete the copies of characters that were brought back
+                    let current: Vec<ItemId> = ids
+                        .iter()
+                        .map(|&id| {
+                            let mut id = id;
+                            while let Some(&copy) = restored.get(&id) {
+                                id = copy;
+                            }
+                            id
+                        })
+                        .collect();
+
+                    let deleted = self.delete_ids(&current);
+                    if !deleted.is_empty() {
+                        reverted.push(TextChange::Deleted(deleted));
+                    }
+                }
+                TextChange::Deleted(ids) => {
+                    let pairs = self.reinsert(ids);
+                    let copies: Vec<ItemId> = pairs.iter().map(|&(_, copy)| copy).collect();
+                    restored.extend(pairs);
+                    if !copies.is_empty() {
+                        reverted.push(TextChange::Inserted(copies));
+                    }
+                }
+            }
+        }
+
+        reverted
+    }
+}
+
+#[cfg(feature = "text-crdt")]
+impl UndoManager<Text> {
+    /// Insert text (see `Text::insert`) and record the change
+    pub fn insert(&mut self, text: &mut Text, position: usize, content: &str) -> Vec<ItemId> {
+        let ids = text.insert(position, content);
+        if !ids.is_empty() {
+            self.record(TextChange::Inserted(ids.clone()));
+        }
+        ids
+    }
+
+    /// Delete text (see `Text::delete`) and record the change
+    pub fn delete(&mut self, text: &mut Text, position: usize, length: usize) -> Vec<ItemId> {
+        let ids = text.delete(position, length);
+        if !ids.is_empty() {
+            self.record(TextChange::Deleted(ids.clone()));
+        }
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn field(doc: &Document, path: &str) -> Option<JsonValue> {
+        doc.get_path(path)
+    }
+
+    #[test]
+    fn test_undo_redo_field() {
+        let mut doc = Document::new("doc-1".to_string());
+        let mut undo = UndoManager::with_capture_timeout(0);
+
+        undo.set_field(
+            &mut doc,
+            "title".to_string(),
+            json!("a"),
+            1,
+            "c1".to_string(),
+        );
+        undo.set_field(
+            &mut doc,
+            "title".to_string(),
+            json!("b"),
+            2,
+            "c1".to_string(),
+        );
+        undo.delete_field(&mut doc, &"title".to_string(), 3, "c1".to_string());
+        assert_eq!(field(&doc, "title"), None);
+
+        assert!(undo.undo(&mut doc));
+        assert_eq!(field(&doc, "title"), Some(json!("b")));
+        assert!(undo.undo(&mut doc));
+        assert_eq!(field(&doc, "title"), Some(json!("a")));
+        assert!(undo.undo(&mut doc));
+        assert_eq!(field(&doc, "title"), None);
+        assert!(!undo.undo(&mut doc));
+
+        assert!(undo.redo(&mut doc));
+        assert!(undo.redo(&mut doc));
+        assert_eq!(field(&doc, "title"), Some(json!("b")));
+        assert!(undo.can_redo());
+    }
+
+    #[test]
+    fn test_changes_within_timeout_are_grouped() {
+        let mut doc = Document::with_replica("doc-1".to_string(), "c1".to_string());
+        let mut undo = UndoManager::with_capture_timeout(u64::MAX);
+
+        for value in ["H", "He", "Hey"] {
+            undo.set(&mut doc, "title".to_string(), json!(value))
+                .unwrap();
+        }
+        undo.stop_capturing();
+        undo.set(&mut doc, "body".to_string(), json!("text"))
+            .unwrap();
+
+        assert!(undo.undo(&mut doc));
+        assert_eq!(field(&doc, "body"), None);
+        assert_eq!(field(&doc, "title"), Some(json!("Hey")));
+
+        assert!(undo.undo(&mut doc));
+        assert_eq!(field(&doc, "title"), None);
+        assert!(!undo.can_undo());
+
+        assert!(undo.redo(&mut doc));
+        assert_eq!(field(&doc, "title"), Some(json!("Hey")));
+    }
+
+    #[test]
+    fn test_record_at_groups_by_time() {
+        let mut undo: UndoManager<Document> = UndoManager::with_capture_timeout(500);
+        let change = |clock| FieldChange {
+            path: "title".to_string(),
+            before: None,
+            after: Some(json!(clock)),
+            timestamp: Timestamp::new(clock, "c1".to_string()),
+        };
+
+        undo.record_at(change(1), 1_000);
+        undo.record_at(change(2), 1_400);
+        undo.record_at(change(3), 2_000);
+
+        assert_eq!(undo.undo_stack.len(), 2);
+        assert_eq!(undo.undo_stack[0].len(), 2);
+    }
+
+    #[test]
+    fn test_undo_keeps_remote_writes() {
+        let mut doc = Document::new("doc-1".to_string());
+        let mut undo = UndoManager::with_capture_timeout(0);
+
+        undo.set_field(
+            &mut doc,
+            "title".to_string(),
+            json!("mine"),
+            1,
+            "c1".to_string(),
+        );
+        undo.set_field(
+            &mut doc,
+            "user.name".to_string(),
+            json!("Ann"),
+            2,
+            "c1".to_string(),
+        );
+
+        // Another user overwrites the title and edits below our write
+        doc.set_field("title".to_string(), json!("theirs"), 5, "c2".to_string());
+        doc.set_field(
+            "user.name.first".to_string(),
+            json!("Bo"),
+            6,
+            "c2".to_string(),
+        );
+
+        assert!(!undo.undo(&mut doc));
+        assert_eq!(field(&doc, "title"), Some(json!("theirs")));
+        assert_eq!(field(&doc, "user.name.first"), Some(json!("Bo")));
+    }
+
+    #[test]
+    fn test_losing_write_is_not_recorded() {
+        let mut doc = Document::new("doc-1".to_string());
+        doc.set_field("title".to_string(), json!("newer"), 10, "c2".to_string());
+
+        let mut undo = UndoManager::new();
+        undo.set_field(
+            &mut doc,
+            "title".to_string(),
+            json!("older"),
+            1,
+            "c1".to_string(),
+        );
+
+        assert!(!undo.can_undo());
+    }
+
+    #[test]
+    #[cfg(feature = "text-crdt")]
+    fn test_undo_text_around_remote_edits() {
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+        let mut undo = UndoManager::with_capture_timeout(0);
+
+        undo.insert(&mut text1, 0, "Hello World");
+        text2.merge(&text1);
+
+        undo.delete(&mut text1, 0, 6); // "Hello "
+        text2.insert(11, "!");
+        text1.merge(&text2);
+
+        // Undoing the delete brings back "Hello " and keeps the remote "!"
+        assert!(undo.undo(&mut text1));
+        assert_eq!(text1.to_string(), "Hello World!");
+
+        // Undoing the insert removes only our characters
+        assert!(undo.undo(&mut text1));
+        assert_eq!(text1.to_string(), "!");
+
+        text2.merge(&text1);
+        assert_eq!(text2.to_string(), "!");
+
+        assert!(undo.redo(&mut text1));
+        assert!(undo.redo(&mut text1));
+        assert_eq!(text1.to_string(), "World!");
+    }
+
+    #[test]
+    #[cfg(feature = "text-crdt")]
+    fn test_undo_text_skips_collected_characters() {
+        use crate::crdt::text::StateVector;
+
+        let mut text1 = Text::new(1);
+        let mut text2 = Text::new(2);
+        let mut undo = UndoManager::with_capture_timeout(0);
+
+        undo.insert(&mut text1, 0, "abcd");
+        text2.merge(&text1);
+        text2.insert(2, "X");
+        text1.merge(&text2);
+
+        undo.delete(&mut text1, 0, 4); // "abXc"
+        let mut stable = StateVector::new();
+        stable.set(1, 2);
+        assert_eq!(text1.collect_garbage(&stable), 2); // "ab"
+
+        // Only "X" and "c" come back, each paired with its own copy
+        assert!(undo.undo(&mut text1));
+        assert_eq!(text1.to_string(), "Xcd");
+        assert!(undo.redo(&mut text1));
+        assert_eq!(text1.to_string(), "d");
+        assert!(undo.undo(&mut text1));
+        assert_eq!(text1.to_string(), "Xcd");
+
+        // Undoing the insert keeps the remote "X"
+        assert!(undo.undo(&mut text1));
+        assert_eq!(text1.to_string(), "X");
+    }
+
+    #[test]
+    #[cfg(feature = "text-crdt")]
+    fn test_undo_text_group() {
+        let mut text = Text::new(1);
+        let mut undo = UndoManager::with_capture_timeout(u64::MAX);
+
+        undo.insert(&mut text, 0, "ab");
+        undo.delete(&mut text, 1, 1);
+        undo.insert(&mut text, 1, "c");
+        assert_eq!(text.to_string(), "ac");
+
+        assert!(undo.undo(&mut text));
+        assert_eq!(text.to_string(), "");
+
+        assert!(undo.redo(&mut text));
+        assert_eq!(text.to_string(), "ac");
+
+        assert!(undo.undo(&mut text));
+        assert_eq!(text.to_string(), "");
+    }
+}
@@ -8,6 +8,10 @@ use wasm_bindgen::prelude::*;
 #[cfg(feature = "prost")]
 use crate::protocol::delta::DocumentDelta;
 
+// Text CRDT is only available with the text-crdt feature
+#[cfg(feature = "text-crdt")]
+use crate::crdt::text::{Assoc, Expand, PositionUnit, RelativePosition, Text, TextRun};
+
 /// JavaScript-friendly wrapper for Document
 #[wasm_bindgen]
 pub struct WasmDocument {
@@ -24,6 +28,35 @@ impl WasmDocument {
         }
     }
 
+    /// Create a new document whose writes are stamped by a hybrid logical clock
+    #[wasm_bindgen(js_name = withReplica)]
+    pub fn with_replica(id: String, replica_id: String) -> Self {
+        Self {
+            inner: Document::with_replica(id, replica_id),
+        }
+    }
+
+    /// Set a field value stamped by the document's clock (returns the clock value)
+    #[wasm_bindgen(js_name = set)]
+    pub fn set(&mut self, path: String, value_json: String) -> Result<u64, JsValue> {
+        let value: serde_json::Value = serde_json::from_str(&value_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid JSON: {}", e)))?;
+
+        self.inner
+            .set(path, value)
+            .map(|timestamp| timestamp.clock)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Delete a field stamped by the document's clock (returns the clock value)
+    #[wasm_bindgen(js_name = delete)]
+    pub fn delete(&mut self, path: String) -> Result<u64, JsValue> {
+        self.inner
+            .delete(&path)
+            .map(|timestamp| timestamp.clock)
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
     /// Set a field value (pass JSON string for value)
     #[wasm_bindgen(js_name = setField)]
     pub fn set_field(
@@ -41,17 +74,31 @@ impl WasmDocument {
     }
 
     /// Get a field value (returns JSON string)
+    ///
+    /// Accepts dotted or JSON Pointer paths and returns the merged subtree.
     #[wasm_bindgen(js_name = getField)]
     pub fn get_field(&self, path: String) -> Option<String> {
         self.inner
-            .get_field(&path)
-            .map(|field| serde_json::to_string(&field).unwrap())
+            .get_path(&path)
+            .map(|value| serde_json::to_string(&value).unwrap())
     }
 
-    /// Delete a field
+    /// Delete a field stamped by the document's clock
+    ///
+    /// Fails if the document was created without a replica clock; use
+    /// `deleteFieldAt` to supply the timestamp yourself.
     #[wasm_bindgen(js_name = deleteField)]
-    pub fn delete_field(&mut self, path: String) {
-        self.inner.delete_field(&path);
+    pub fn delete_field(&mut self, path: String) -> Result<(), JsValue> {
+        self.inner
+            .delete(&path)
+            .map(|_| ())
+            .map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Delete a field (records a tombstone at the given clock)
+    #[wasm_bindgen(js_name = deleteFieldAt)]
+    pub fn delete_field_at(&mut self, path: String, clock: u64, client_id: String) {
+        self.inner.delete_field(&path, clock, client_id);
     }
 
     /// Get document ID
@@ -178,3 +225,125 @@ impl WasmDelta {
             .map_err(|e| JsValue::from_str(&format!("JSON serialization failed: {}", e)))
     }
 }
+
+/// JavaScript-friendly wrapper for the Text CRDT
+///
+/// Positions count UTF-16 code units, like JavaScript string indices.
+#[cfg(feature = "text-crdt")]
+#[wasm_bindgen]
+pub struct WasmText {
+    inner: Text,
+}
+
+#[cfg(feature = "text-crdt")]
+#[wasm_bindgen]
+impl WasmText {
+    /// Create a new text for a client
+    #[wasm_bindgen(constructor)]
+    pub fn new(client_id: u64) -> Self {
+        Self {
+            inner: Text::with_unit(client_id, PositionUnit::Utf16),
+        }
+    }
+
+    /// Insert text at a position
+    #[wasm_bindgen(js_name = insert)]
+    pub fn insert(&mut self, position: usize, text: String) {
+        self.inner.insert(position, &text);
+    }
+
+    /// Delete a range of text
+    #[wasm_bindgen(js_name = delete)]
+    pub fn delete(&mut self, position: usize, length: usize) {
+        self.inner.delete(position, length);
+    }
+
+    /// Set an attribute on a range (value as JSON, `null` clears it)
+    ///
+    /// `expand` is one of "none", "left", "right" or "both".
+    #[wasm_bindgen(js_name = format)]
+    pub fn format(
+        &mut self,
+        position: usize,
+        length: usize,
+        name: String,
+        value_json: String,
+        expand: String,
+    ) -> Result<(), JsValue> {
+        let value: serde_json::Value = serde_json::from_str(&value_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid JSON: {}", e)))?;
+
+        self.inner
+            .format(position, length, &name, value, parse_expand(&expand)?);
+        Ok(())
+    }
+
+    /// Export as a delta-style JSON list of `{insert, attributes}` runs
+    #[wasm_bindgen(js_name = toDelta)]
+    pub fn to_delta(&self) -> String {
+        serde_json::to_string(&self.inner.to_runs()).unwrap()
+    }
+
+    /// Insert a delta-style JSON list of `{insert, attributes}` runs
+    #[wasm_bindgen(js_name = insertDelta)]
+    pub fn insert_delta(
+        &mut self,
+        position: usize,
+        delta_json: String,
+        expand: String,
+    ) -> Result<(), JsValue> {
+        let runs: Vec<TextRun> = serde_json::from_str(&delta_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid delta: {}", e)))?;
+
+        self.inner
+            .insert_runs(position, &runs, parse_expand(&expand)?);
+        Ok(())
+    }
+
+    /// Get a relative position (as JSON) for an index
+    ///
+    /// `assoc` is "before" or "after": the side whose character it sticks to.
+    #[wasm_bindgen(js_name = relativePosition)]
+    pub fn relative_position(&self, index: usize, assoc: String) -> Result<String, JsValue> {
+        let assoc: Assoc = serde_json::from_value(serde_json::Value::String(assoc.clone()))
+            .map_err(|_| JsValue::from_str(&format!("Invalid assoc: {}", assoc)))?;
+
+        Ok(serde_json::to_string(&self.inner.relative_position(index, assoc)).unwrap())
+    }
+
+    /// Resolve a relative position (JSON) to the current index
+    ///
+    /// Returns undefined if its character hasn't been received yet.
+    #[wasm_bindgen(js_name = resolvePosition)]
+    pub fn resolve_position(&self, position_json: String) -> Result<Option<usize>, JsValue> {
+        let position: RelativePosition = serde_json::from_str(&position_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid position: {}", e)))?;
+
+        Ok(self.inner.resolve_position(&position))
+    }
+
+    /// Get the length in UTF-16 code units
+    #[wasm_bindgen(js_name = length)]
+    pub fn length(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Get the plain text
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_string_js(&self) -> String {
+        self.inner.to_string()
+    }
+
+    /// Merge with another text
+    #[wasm_bindgen(js_name = merge)]
+    pub fn merge(&mut self, other: &WasmText) {
+        self.inner.merge(&other.inner);
+    }
+}
+
+/// Parse a mark expansion name
+#[cfg(feature = "text-crdt")]
+fn parse_expand(expand: &str) -> Result<Expand, JsValue> {
+    serde_json::from_value(serde_json::Value::String(expand.to_string()))
+        .map_err(|_| JsValue::from_str(&format!("Invalid expand: {}", expand)))
+}
@@ -15,3 +15,7 @@ pub use bindings::{WasmDocument, WasmVectorClock};
 // WasmDelta only available with protocol support
 #[cfg(all(feature = "wasm", feature = "prost"))]
 pub use bindings::WasmDelta;
+
+// WasmText only available with the Text CRDT
+#[cfg(all(feature = "wasm", feature = "text-crdt"))]
+pub use bindings::WasmText;
@@ -370,4 +370,645 @@ mod tests {
             }
         });
     }
+
+    /// Property: Deletes Converge
+    ///
+    /// Interleaved writes and deletes merged in any order produce the same state,
+    /// and a field is only visible if its newest write is newer than its newest delete.
+    #[test]
+    fn prop_deletes_converge() {
+        proptest!(|(ops in operations(20), deletes in prop::collection::vec(any::<bool>(), 20))| {
+            let mut doc1 = Document::new("test-doc".to_string());
+            let mut doc2 = Document::new("test-doc".to_string());
+
+            let apply = |doc: &mut Document, op: &Operation, delete: bool| {
+                if delete {
+                    doc.delete_field(&op.field, op.timestamp, op.client_id.clone());
+                } else {
+                    doc.set_field(
+                        op.field.clone(),
+                        op.value.clone(),
+                        op.timestamp,
+                        op.client_id.clone(),
+                    );
+                }
+            };
+
+            for (op, &delete) in ops.iter().zip(&deletes) {
+                apply(&mut doc1, op, delete);
+            }
+            for (op, &delete) in ops.iter().zip(&deletes).rev() {
+                apply(&mut doc2, op, delete);
+            }
+
+            prop_assert_eq!(&doc1.fields, &doc2.fields);
+            prop_assert_eq!(&doc1.tombstones, &doc2.tombstones);
+
+            // Merging replicas built from disjoint halves also converges
+            let (first, second) = ops.split_at(ops.len() / 2);
+            let mut left = Document::new("test-doc".to_string());
+            let mut right = Document::new("test-doc".to_string());
+            for (op, &delete) in first.iter().zip(&deletes) {
+                apply(&mut left, op, delete);
+            }
+            for (op, &delete) in second.iter().zip(&deletes[first.len()..]) {
+                apply(&mut right, op, delete);
+            }
+            left.merge(&right);
+            prop_assert_eq!(&left.fields, &doc1.fields);
+            prop_assert_eq!(&left.tombstones, &doc1.tombstones);
+        });
+    }
+
+    /// Property: Nested Paths Converge
+    ///
+    /// Writes and deletes at overlapping parent and child paths, applied in
+    /// any order, produce the same entries and the same JSON tree.
+    #[test]
+    fn prop_nested_paths_converge() {
+        let nested_op = (
+            prop::string::string_regex("[ab](\\.[ab]){0,2}").unwrap(),
+            prop_oneof![field_value(), Just(json!({"a": 1, "b": {"a": 2}}))],
+            1u64..20u64,
+            client_id(),
+            any::<bool>(),
+        );
+
+        proptest!(|(ops in prop::collection::vec(nested_op, 1..=20))| {
+            let apply = |doc: &mut Document, (path, value, clock, client, delete): &(String, serde_json::Value, u64, ClientID, bool)| {
+                if *delete {
+                    doc.delete_field(path, *clock, client.clone());
+                } else {
+                    doc.set_field(path.clone(), value.clone(), *clock, client.clone());
+                }
+            };
+
+            let mut doc1 = Document::new("test-doc".to_string());
+            let mut doc2 = Document::new("test-doc".to_string());
+            for op in &ops {
+                apply(&mut doc1, op);
+            }
+            for op in ops.iter().rev() {
+                apply(&mut doc2, op);
+            }
+
+            prop_assert_eq!(&doc1.fields, &doc2.fields);
+            prop_assert_eq!(&doc1.tombstones, &doc2.tombstones);
+            prop_assert_eq!(doc1.to_json(), doc2.to_json());
+
+            let (first, second) = ops.split_at(ops.len() / 2);
+            let mut left = Document::new("test-doc".to_string());
+            let mut right = Document::new("test-doc".to_string());
+            for op in first {
+                apply(&mut left, op);
+            }
+            for op in second {
+                apply(&mut right, op);
+            }
+            left.merge(&right);
+            prop_assert_eq!(left.to_json(), doc1.to_json());
+        });
+    }
+
+    /// Property: Merge policies converge with values of mixed types
+    ///
+    /// Values that don't fit a policy must not make the result depend on
+    /// the order replicas are merged in.
+    #[test]
+    fn prop_merge_policies_converge_with_mixed_types() {
+        use synckit_core::sync::MergePolicy;
+
+        let policy = prop_oneof![
+            Just(MergePolicy::Lww),
+            Just(MergePolicy::Max),
+            Just(MergePolicy::Min),
+            Just(MergePolicy::Union),
+            Just(MergePolicy::Counter),
+        ];
+        let value = prop_oneof![
+            field_value(),
+            prop::collection::vec(0i32..5, 0..3).prop_map(|items| json!(items)),
+            (0u64..5, 1u64..10).prop_map(|(replica, total)| {
+                json!({ "p": { format!("client{}", replica): total } })
+            }),
+        ];
+        let write = (value, 1u64..5, client_id());
+
+        proptest!(|(policy in policy, writes in prop::collection::vec(write, 3))| {
+            let replicas: Vec<Document> = writes
+                .iter()
+                .map(|(value, clock, client)| {
+                    let mut doc = Document::new("test-doc".to_string());
+                    doc.set_merge_policy("field", policy.clone());
+                    doc.set_field("field".to_string(), value.clone(), *clock, client.clone());
+                    doc
+                })
+                .collect();
+            let merged = |order: [usize; 3], nested: bool| {
+                let [a, b, c] = order.map(|i| replicas[i].clone());
+                if nested {
+                    // a ⊔ (b ⊔ c)
+                    let mut bc = b;
+                    bc.merge(&c);
+                    let mut result = a;
+                    result.merge(&bc);
+                    result
+                } else {
+                    // (a ⊔ b) ⊔ c
+                    let mut result = a;
+                    result.merge(&b);
+                    result.merge(&c);
+                    result
+                }
+            };
+
+            let expected = merged([0, 1, 2], false);
+            for order in [[0, 1, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]] {
+                for nested in [false, true] {
+                    prop_assert_eq!(&merged(order, nested).fields, &expected.fields);
+                }
+            }
+        });
+    }
+}
+
+/// Property tests for the Text CRDT
+#[cfg(feature = "text-crdt")]
+mod text_tests {
+    use proptest::prelude::*;
+    use synckit_core::crdt::text::Text;
+
+    /// A step in a random editing session
+    #[derive(Debug, Clone)]
+    enum TextOp {
+        /// Insert at a position (scaled to the current length)
+        Insert(usize, u16, String),
+        /// Delete a range (start scaled to the current length)
+        Delete(usize, u16, usize),
+        /// Merge another replica's state
+        Merge(usize, usize),
+    }
+
+    fn text_op(replicas: usize) -> impl Strategy<Value = TextOp> {
+        prop_oneof![
+            (
+                0..replicas,
+                any::<u16>(),
+                prop::string::string_regex("[ab é😀]{1,4}").unwrap()
+            )
+                .prop_map(|(r, pos, s)| TextOp::Insert(r, pos, s)),
+            (0..replicas, any::<u16>(), 1usize..4)
+                .prop_map(|(r, pos, len)| TextOp::Delete(r, pos, len)),
+            (0..replicas, 0..replicas).prop_map(|(to, from)| TextOp::Merge(to, from)),
+        ]
+    }
+
+    fn scaled(position: u16, len: usize) -> usize {
+        position as usize * (len + 1) / (u16::MAX as usize + 1)
+    }
+
+    /// Property: Text Convergence
+    ///
+    /// Random concurrent inserts and deletes (including inside merged
+    /// blocks) with random partial merges converge once all replicas
+    /// exchange their states.
+    #[test]
+    fn prop_text_converges() {
+        proptest!(|(ops in prop::collection::vec(text_op(3), 1..40))| {
+            let mut replicas: Vec<Text> = (1..=3).map(Text::new).collect();
+
+            for op in &ops {
+                match op {
+                    TextOp::Insert(r, pos, s) => {
+                        let position = scaled(*pos, replicas[*r].len());
+                        replicas[*r].insert(position, s);
+                    }
+                    TextOp::Delete(r, pos, len) => {
+                        let position = scaled(*pos, replicas[*r].len());
+                        replicas[*r].delete(position, *len);
+                    }
+                    TextOp::Merge(to, from) => {
+                        let other = replicas[*from].clone();
+                        replicas[*to].merge(&other);
+                    }
+                }
+            }
+
+            for i in 0..3 {
+                for j in 0..3 {
+                    let other = replicas[j].clone();
+                    replicas[i].merge(&other);
+                }
+            }
+
+            let expected = replicas[0].to_string();
+            for replica in &replicas {
+                prop_assert_eq!(replica.to_string(), expected.clone());
+            }
+
+            // Merging again changes nothing
+            let before = replicas[0].to_string();
+            let other = replicas[1].clone();
+            replicas[0].merge(&other);
+            prop_assert_eq!(replicas[0].to_string(), before);
+        });
+    }
+
+    /// Property: Text updates apply in any order
+    ///
+    /// A replica receiving every local update in a shuffled order (so
+    /// characters can arrive before their origins) ends up with the same
+    /// text as replicas that merged full states.
+    #[test]
+    fn prop_text_updates_commute() {
+        proptest!(|(
+            ops in prop::collection::vec(text_op(3), 1..40),
+            order in prop::collection::vec(any::<u32>(), 40),
+        )| {
+            let mut replicas: Vec<Text> = (1..=3).map(Text::new).collect();
+            let mut updates = Vec::new();
+
+            for op in &ops {
+                match op {
+                    TextOp::Insert(r, pos, s) => {
+                        let position = scaled(*pos, replicas[*r].len());
+                        replicas[*r].insert(position, s);
+                        updates.push(replicas[*r].take_update());
+                    }
+                    TextOp::Delete(r, pos, len) => {
+                        let position = scaled(*pos, replicas[*r].len());
+                        replicas[*r].delete(position, *len);
+                        updates.push(replicas[*r].take_update());
+                    }
+                    TextOp::Merge(to, from) => {
+                        let other = replicas[*from].clone();
+                        replicas[*to].merge(&other);
+                    }
+                }
+            }
+
+            for i in 0..3 {
+                for j in 0..3 {
+                    let other = replicas[j].clone();
+                    replicas[i].merge(&other);
+                }
+            }
+
+            let mut indices: Vec<usize> = (0..updates.len()).collect();
+            indices.sort_by_key(|&i| (order[i % order.len()], i));
+
+            let mut observer = Text::new(4);
+            for i in indices {
+                observer.apply_update(&updates[i]);
+            }
+
+            prop_assert!(!observer.has_pending());
+            prop_assert_eq!(observer.to_string(), replicas[0].to_string());
+        });
+    }
+}
+
+#[cfg(feature = "sets")]
+mod or_set_tests {
+    use proptest::prelude::*;
+    use synckit_core::crdt::ORSet;
+    use synckit_core::protocol::serialize::{deserialize_or_set, serialize_or_set};
+
+    /// A step in a random session on a few replicas
+    #[derive(Debug, Clone)]
+    enum SetOp {
+        Add(usize, u8),
+        Remove(usize, u8),
+        Merge(usize, usize),
+    }
+
+    fn set_op(replicas: usize) -> impl Strategy<Value = SetOp> {
+        prop_oneof![
+            (0..replicas, 0u8..5).prop_map(|(r, e)| SetOp::Add(r, e)),
+            (0..replicas, 0u8..5).prop_map(|(r, e)| SetOp::Remove(r, e)),
+            (0..replicas, 0..replicas).prop_map(|(to, from)| SetOp::Merge(to, from)),
+        ]
+    }
+
+    /// Run a session; also returns each replica's own deltas joined
+    fn run(ops: &[SetOp]) -> (Vec<ORSet<u8>>, Vec<ORSet<u8>>) {
+        let mut replicas: Vec<ORSet<u8>> = (0..3)
+            .map(|r| ORSet::new(format!("replica{}", r)))
+            .collect();
+        let mut buffers = replicas.clone();
+        for op in ops {
+            match *op {
+                SetOp::Add(r, e) => {
+                    let delta = replicas[r].add(e);
+                    buffers[r].merge(&delta);
+                }
+                SetOp::Remove(r, e) => {
+                    let delta = replicas[r].remove(&e);
+                    buffers[r].merge(&delta);
+                }
+                SetOp::Merge(to, from) => {
+                    let other = replicas[from].clone();
+                    replicas[to].merge(&other);
+                }
+            }
+        }
+        (replicas, buffers)
+    }
+
+    /// Property: Delta-states replace full states
+    ///
+    /// Merging every replica's joined deltas gives the same set as merging
+    /// every replica's full state.
+    #[test]
+    fn prop_or_set_deltas_converge_like_full_states() {
+        proptest!(|(ops in prop::collection::vec(set_op(3), 1..40))| {
+            let (replicas, buffers) = run(&ops);
+
+            for replica in &replicas {
+                let mut via_states = replica.clone();
+                let mut via_deltas = replica.clone();
+                for (other, buffer) in replicas.iter().zip(&buffers) {
+                    via_states.merge(other);
+                    via_deltas.merge(buffer);
+                }
+                prop_assert_eq!(via_deltas, via_states);
+            }
+        });
+    }
+
+    /// Property: Lossless OR-Set encoding
+    ///
+    /// Decoding an encoded set gives back the same tags and causal context,
+    /// so it merges with every other replica exactly like the original.
+    #[test]
+    fn prop_or_set_round_trip_merges_identically() {
+        proptest!(|(ops in prop::collection::vec(set_op(3), 1..40))| {
+            let (replicas, _) = run(&ops);
+
+            for (r, replica) in replicas.iter().enumerate() {
+                let id = format!("replica{}", r);
+                let decoded: ORSet<u8> =
+                    deserialize_or_set(&serialize_or_set(replica, &id), &id).unwrap();
+                prop_assert_eq!(&decoded, replica);
+
+                for other in &replicas {
+                    let mut via_original = other.clone();
+                    via_original.merge(replica);
+                    let mut via_decoded = other.clone();
+                    via_decoded.merge(&decoded);
+                    prop_assert_eq!(via_decoded, via_original);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(feature = "fractional-index")]
+mod fractional_index_tests {
+    use proptest::prelude::*;
+    use std::collections::HashSet;
+    use synckit_core::crdt::FractionalIndex;
+
+    /// Replica IDs, including prefixes of each other and non-ASCII bytes
+    const REPLICAS: [&str; 4] = ["a", "ab", "b", "réplica"];
+
+    /// Property: Concurrent unique keys never collide
+    ///
+    /// Every round, each replica inserts into the same snapshot of the list
+    /// (often into the same gap). Every key lands between its neighbours
+    /// and no two keys are equal.
+    #[test]
+    fn prop_concurrent_unique_keys_never_collide() {
+        proptest!(|(rounds in prop::collection::vec(prop::collection::vec(0usize..1000, 4), 1..30))| {
+            let mut keys: Vec<FractionalIndex> = Vec::new();
+
+            for picks in rounds {
+                let snapshot = keys.clone();
+                for (replica, pick) in REPLICAS.iter().zip(picks) {
+                    let gap = pick % (snapshot.len() + 1);
+                    let left = gap.checked_sub(1).map(|i| &snapshot[i]);
+                    let right = snapshot.get(gap);
+
+                    let key = FractionalIndex::between_unique(left, right, replica);
+                    prop_assert!(left.is_none_or(|left| left < &key));
+                    prop_assert!(right.is_none_or(|right| &key < right));
+                    prop_assert!(!key.as_str().ends_with('0'));
+                    keys.push(key);
+                }
+                keys.sort();
+            }
+
+            let distinct: HashSet<&str> = keys.iter().map(|key| key.as_str()).collect();
+            prop_assert_eq!(distinct.len(), keys.len());
+        });
+    }
+
+    /// Property: `between` always terminates with a key in range
+    ///
+    /// Repeatedly inserting into the same side of a gap makes keys longer
+    /// without bound; no depth cap may break the ordering.
+    #[test]
+    fn prop_between_stays_in_range() {
+        proptest!(|(sides in prop::collection::vec(any::<bool>(), 1..300))| {
+            let mut left = FractionalIndex::first();
+            let mut right = FractionalIndex::after(&left);
+
+            for go_left in sides {
+                let middle = FractionalIndex::between(&left, &right);
+                prop_assert!(left < middle && middle < right);
+                if go_left {
+                    right = middle;
+                } else {
+                    left = middle;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(feature = "fractional-index")]
+mod list_tests {
+    use proptest::prelude::*;
+    use serde_json::json;
+    use synckit_core::crdt::List;
+
+    /// A step in a random session on a few replicas
+    #[derive(Debug, Clone)]
+    enum ListOp {
+        Insert(usize, usize, u8),
+        Move(usize, usize, usize),
+        Delete(usize, usize),
+        Merge(usize, usize),
+    }
+
+    fn list_op(replicas: usize) -> impl Strategy<Value = ListOp> {
+        prop_oneof![
+            (0..replicas, 0usize..20, any::<u8>()).prop_map(|(r, i, v)| ListOp::Insert(r, i, v)),
+            (0..replicas, 0usize..20, 0usize..20).prop_map(|(r, e, i)| ListOp::Move(r, e, i)),
+            (0..replicas, 0usize..20).prop_map(|(r, e)| ListOp::Delete(r, e)),
+            (0..replicas, 0..replicas).prop_map(|(to, from)| ListOp::Merge(to, from)),
+        ]
+    }
+
+    /// Property: Lists converge after exchanging states
+    ///
+    /// After random concurrent inserts, moves and deletes, every replica
+    /// holds the same elements in the same order, each exactly once.
+    #[test]
+    fn prop_list_converges() {
+        proptest!(|(ops in prop::collection::vec(list_op(3), 1..60))| {
+            let mut replicas: Vec<List> = (0..3)
+                .map(|r| List::new(format!("replica{}", r)))
+                .collect();
+
+            for op in ops {
+                match op {
+                    ListOp::Insert(r, i, v) => {
+                        let len = replicas[r].len();
+                        replicas[r].insert(i % (len + 1), json!(v)).unwrap();
+                    }
+                    ListOp::Move(r, e, i) => {
+                        let len = replicas[r].len();
+                        if len > 0 {
+                            let id = replicas[r].iter().nth(e % len).unwrap().0.clone();
+                            replicas[r].move_to(&id, i % len).unwrap();
+                        }
+                    }
+                    ListOp::Delete(r, e) => {
+                        let len = replicas[r].len();
+                        if len > 0 {
+                            let id = replicas[r].iter().nth(e % len).unwrap().0.clone();
+                            replicas[r].delete(&id);
+                        }
+                    }
+                    ListOp::Merge(to, from) => {
+                        let other = replicas[from].clone();
+                        replicas[to].merge(&other);
+                    }
+                }
+            }
+
+            for i in 0..3 {
+                for j in 0..3 {
+                    let other = replicas[j].clone();
+                    replicas[i].merge(&other);
+                }
+            }
+
+            let ids: Vec<_> = replicas[0].iter().map(|(id, _)| id.clone()).collect();
+            let mut distinct = ids.clone();
+            distinct.sort();
+            distinct.dedup();
+            prop_assert_eq!(distinct.len(), ids.len());
+
+            for replica in &replicas[1..] {
+                let other: Vec<_> = replica.iter().map(|(id, _)| id.clone()).collect();
+                prop_assert_eq!(&other, &ids);
+                prop_assert_eq!(replica.values(), replicas[0].values());
+            }
+        });
+    }
+}
+
+#[cfg(feature = "fractional-index")]
+mod tree_tests {
+    use proptest::prelude::*;
+    use serde_json::json;
+    use std::collections::HashSet;
+    use synckit_core::crdt::tree::{NodeId, Tree};
+
+    /// A step in a random session on a few replicas
+    #[derive(Debug, Clone)]
+    enum TreeOp {
+        Create(usize, usize, usize),
+        Move(usize, usize, usize, usize),
+        Delete(usize, usize),
+        Merge(usize, usize),
+    }
+
+    fn tree_op(replicas: usize) -> impl Strategy<Value = TreeOp> {
+        prop_oneof![
+            (0..replicas, 0usize..50, 0usize..5).prop_map(|(r, p, i)| TreeOp::Create(r, p, i)),
+            (0..replicas, 0usize..50, 0usize..50, 0usize..5)
+                .prop_map(|(r, n, p, i)| TreeOp::Move(r, n, p, i)),
+            (0..replicas, 0usize..50).prop_map(|(r, n)| TreeOp::Delete(r, n)),
+            (0..replicas, 0..replicas).prop_map(|(to, from)| TreeOp::Merge(to, from)),
+        ]
+    }
+
+    /// Every node reachable from the root, depth-first; fails on a repeat
+    fn walk(tree: &Tree) -> Result<Vec<(NodeId, Vec<NodeId>)>, TestCaseError> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![NodeId::root()];
+        let mut nodes = Vec::new();
+        while let Some(node) = stack.pop() {
+            prop_assert!(seen.insert(node.clone()), "node {} reached twice", node);
+            let children = tree.children(&node);
+            for child in &children {
+                prop_assert_eq!(tree.parent(child), Some(&node));
+            }
+            stack.extend(children.iter().cloned());
+            nodes.push((node, children));
+        }
+        Ok(nodes)
+    }
+
+    /// Property: Concurrent moves never create cycles
+    ///
+    /// After random concurrent creates, moves (many of them crossing each
+    /// other) and deletes, every replica computes the same tree, and every
+    /// node in it is reached from the root exactly once.
+    #[test]
+    fn prop_tree_converges_without_cycles() {
+        proptest!(|(ops in prop::collection::vec(tree_op(3), 1..80))| {
+            let mut replicas: Vec<Tree> = (0..3)
+                .map(|r| Tree::new(format!("replica{}", r)))
+                .collect();
+
+            for op in ops {
+                match op {
+                    TreeOp::Create(r, p, i) => {
+                        let nodes = walk(&replicas[r])?;
+                        let (parent, children) = &nodes[p % nodes.len()];
+                        let index = i % (children.len() + 1);
+                        let parent = parent.clone();
+                        replicas[r].create(&parent, index, json!(p)).unwrap();
+                    }
+                    TreeOp::Move(r, n, p, i) => {
+                        let nodes = walk(&replicas[r])?;
+                        let node = nodes[n % nodes.len()].0.clone();
+                        let (parent, children) = &nodes[p % nodes.len()];
+                        let index = i % (children.len() + 1);
+                        let parent = parent.clone();
+                        // Moves into the own subtree are rejected locally
+                        let _ = replicas[r].move_node(&node, &parent, index);
+                    }
+                    TreeOp::Delete(r, n) => {
+                        let nodes = walk(&replicas[r])?;
+                        let node = nodes[n % nodes.len()].0.clone();
+                        replicas[r].delete(&node);
+                    }
+                    TreeOp::Merge(to, from) => {
+                        let other = replicas[from].clone();
+                        replicas[to].merge(&other);
+                    }
+                }
+                for replica in &replicas {
+                    walk(replica)?;
+                }
+            }
+
+            for i in 0..3 {
+                for j in 0..3 {
+                    let other = replicas[j].clone();
+                    replicas[i].merge(&other);
+                }
+            }
+
+            let expected = walk(&replicas[0])?;
+            for replica in &replicas[1..] {
+                prop_assert_eq!(&walk(replica)?, &expected);
+            }
+        });
+    }
 }
@@ -177,7 +177,11 @@ export class SyncDocument<T extends Record<string, unknown> = Record<string, unk
       throw new DocumentError('Document not initialized')
     }
     
-    this.wasmDoc.deleteField(String(field))
+    // Increment vector clock for this client (deletes are timestamped like writes)
+    const newCount = (this.vectorClock[this.clientId] || 0) + 1
+    this.vectorClock[this.clientId] = newCount
+
+    this.wasmDoc.deleteFieldAt(String(field), BigInt(newCount), this.clientId)
     this.updateLocalState()
     await this.persist()
     this.notifySubscribers()
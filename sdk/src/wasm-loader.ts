@@ -25,6 +25,7 @@ export interface WasmDocument {
   setField(path: string, valueJson: string, clock: bigint, clientId: string): void
   getField(path: string): string | undefined
   deleteField(path: string): void
+  deleteFieldAt(path: string, clock: bigint, clientId: string): void
   fieldCount(): number
   toJSON(): string
   merge(other: WasmDocument): void
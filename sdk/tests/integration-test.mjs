@@ -135,7 +135,7 @@ await asyncTest('Document operations', async () => {
   if (parsed !== 'Alice') throw new Error(`Expected 'Alice', got '${parsed}'`);
   
   // Delete field
-  doc.deleteField('name');
+  doc.deleteFieldAt('name', BigInt(2), 'client1');
   const deleted = doc.getField('name');
   if (deleted !== undefined) throw new Error('Field should be deleted');
   